@@ -0,0 +1,284 @@
+use crate::errors::ApiError;
+use crate::state::AppState;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, http::header, web};
+use argon2::Argon2;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::future::{Ready, ready};
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+/// Env var holding the HMAC secret tokens are signed with.
+pub const JWT_SECRET_VAR: &str = "JWT_SECRET";
+
+/// Lifetimes of issued tokens, in seconds.
+pub const ACCESS_TOKEN_TTL: i64 = 15 * 60;
+pub const REFRESH_TOKEN_TTL: i64 = 14 * 24 * 60 * 60;
+
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("argon2 accepts any password with default params")
+        .to_string()
+}
+
+/// `false` for a wrong password and for a hash that does not parse.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+// Hashed like any account's password, so checking it costs the same
+static DUMMY_HASH: LazyLock<String> = LazyLock::new(|| hash_password("no account"));
+
+/// Checks `password` against a hash no account has, always failing. Logins
+/// for unknown emails call it so they take as long as wrong passwords.
+pub fn verify_no_account(password: &str) {
+    verify_password(password, &DUMMY_HASH);
+}
+
+/// What a caller is allowed to do. Tutor accounts are tutors or admins,
+/// student accounts are always students.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Tutor,
+    Student,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Tutor => "tutor",
+            Role::Student => "student",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Access,
+    Refresh,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// The tutor or student the token was issued to.
+    sub: Uuid,
+    role: Role,
+    kind: TokenKind,
+    iat: i64,
+    exp: i64,
+}
+
+/// Body of a successful `POST /auth/login` or `POST /auth/refresh`.
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the access token expires.
+    pub expires_in: i64,
+}
+
+/// Signs and checks HS256 tokens.
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl JwtKeys {
+    pub fn new(secret: &[u8]) -> Self {
+        JwtKeys {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// Uses `JWT_SECRET`, or a random secret when it is unset, in which case
+    /// tokens stop working when the server restarts.
+    pub fn from_env() -> Self {
+        match std::env::var(JWT_SECRET_VAR) {
+            Ok(secret) if !secret.is_empty() => Self::new(secret.as_bytes()),
+            _ => {
+                let mut secret = [0u8; 32];
+                OsRng.fill_bytes(&mut secret);
+                Self::new(&secret)
+            }
+        }
+    }
+
+    pub fn issue(&self, caller: Caller) -> TokenPair {
+        TokenPair {
+            access_token: self.sign(caller, TokenKind::Access, ACCESS_TOKEN_TTL),
+            refresh_token: self.sign(caller, TokenKind::Refresh, REFRESH_TOKEN_TTL),
+            token_type: "Bearer",
+            expires_in: ACCESS_TOKEN_TTL,
+        }
+    }
+
+    fn sign(&self, caller: Caller, kind: TokenKind, ttl: i64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = Claims {
+            sub: caller.id,
+            role: caller.role,
+            kind,
+            iat: now,
+            exp: now + ttl,
+        };
+        jsonwebtoken::encode(&Header::default(), &claims, &self.encoding)
+            .expect("HS256 signing cannot fail")
+    }
+
+    /// Who a valid, unexpired token of the given kind was issued to.
+    pub fn verify(&self, token: &str, kind: TokenKind) -> Result<Caller, ApiError> {
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.decoding, &Validation::default())
+            .map_err(|e| ApiError::Unauthorized(format!("Invalid token: {e}")))?
+            .claims;
+        if claims.kind != kind {
+            return Err(ApiError::Unauthorized(format!(
+                "Expected an {} token",
+                match kind {
+                    TokenKind::Access => "access",
+                    TokenKind::Refresh => "refresh",
+                }
+            )));
+        }
+        Ok(Caller {
+            id: claims.sub,
+            role: claims.role,
+        })
+    }
+}
+
+/// Whoever's access token came in the `Authorization: Bearer` header.
+/// Extracting it fails the request with 401 when the token is missing or
+/// invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caller {
+    /// A tutor id, or a student id when `role` is `Student`.
+    pub id: Uuid,
+    pub role: Role,
+}
+
+impl Caller {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+}
+
+impl FromRequest for Caller {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        // Routes behind `require_role` have already checked the token
+        let authenticated = req.extensions().get::<Caller>().copied();
+        ready(authenticated.map_or_else(|| authenticate(req), Ok))
+    }
+}
+
+fn authenticate(req: &HttpRequest) -> Result<Caller, ApiError> {
+    let app_state = req
+        .app_data::<web::Data<AppState>>()
+        .expect("AppState is registered on the app");
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+
+    app_state.jwt.verify(token.trim(), TokenKind::Access)
+}
+
+/// Middleware admitting only callers holding one of `roles`: 401 without a
+/// valid access token, 403 with one for another role.
+pub async fn require_role(
+    roles: &'static [Role],
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let caller = authenticate(req.request())?;
+    if !roles.contains(&caller.role) {
+        let allowed: Vec<&str> = roles.iter().map(Role::as_str).collect();
+        return Err(ApiError::Forbidden(format!(
+            "{} {} requires one of the roles: {}",
+            req.method(),
+            req.path(),
+            allowed.join(", ")
+        ))
+        .into());
+    }
+    req.extensions_mut().insert(caller);
+    next.call(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwords_round_trip() {
+        let hash = hash_password("correct horse");
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    #[test]
+    fn unknown_accounts_pay_for_a_verification() {
+        verify_no_account("correct horse");
+        let dummy = PasswordHash::new(&DUMMY_HASH).expect("the stand-in hash parses");
+        let hash = hash_password("correct horse");
+        let real = PasswordHash::new(&hash).unwrap();
+        assert_eq!(
+            (dummy.algorithm, dummy.version, &dummy.params),
+            (real.algorithm, real.version, &real.params)
+        );
+        assert!(!verify_password("correct horse", &DUMMY_HASH));
+    }
+
+    #[test]
+    fn tokens_are_bound_to_their_kind_and_key() {
+        let keys = JwtKeys::new(b"test secret");
+        let caller = Caller {
+            id: Uuid::new_v4(),
+            role: Role::Admin,
+        };
+        let pair = keys.issue(caller);
+
+        assert_eq!(
+            keys.verify(&pair.access_token, TokenKind::Access).unwrap(),
+            caller
+        );
+        assert_eq!(
+            keys.verify(&pair.refresh_token, TokenKind::Refresh)
+                .unwrap(),
+            caller
+        );
+        assert!(keys.verify(&pair.refresh_token, TokenKind::Access).is_err());
+
+        let other_keys = JwtKeys::new(b"another secret");
+        assert!(
+            other_keys
+                .verify(&pair.access_token, TokenKind::Access)
+                .is_err()
+        );
+    }
+}
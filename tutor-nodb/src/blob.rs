@@ -0,0 +1,341 @@
+//! Content-addressed storage for uploaded files. Blobs are keyed by the
+//! SHA-256 of their bytes, so identical uploads are stored once.
+
+use actix_web::web::Bytes;
+use async_trait::async_trait;
+use futures_util::{Stream, StreamExt, stream};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use uuid::Uuid;
+
+/// Bytes going into or coming out of a [`BlobStore`]. Not `Send`, as actix
+/// serves each request on a single thread.
+pub type ByteStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>>>>;
+
+/// Largest chunk a read yields.
+const READ_CHUNK: u64 = 64 * 1024;
+
+/// What [`BlobStore::commit`] stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredBlob {
+    /// Lowercase hex SHA-256 of the bytes, and the key to read them by.
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Bytes [`BlobStore::stage`] has hashed but not yet stored under the hash.
+#[derive(Debug)]
+pub struct StagedBlob {
+    pub sha256: String,
+    pub size: u64,
+    // Where the bytes wait until they are committed
+    key: Uuid,
+}
+
+#[async_trait(?Send)]
+pub trait BlobStore: Send + Sync {
+    /// Writes everything `data` yields aside and hashes it. An error from
+    /// `data` aborts the write and is returned as is.
+    async fn stage(&self, data: ByteStream) -> io::Result<StagedBlob>;
+
+    /// Stores staged bytes under their hash, keeping the existing copy if
+    /// that content is already stored.
+    async fn commit(&self, staged: StagedBlob) -> io::Result<StoredBlob>;
+
+    /// Drops staged bytes that will not be committed.
+    async fn discard(&self, staged: StagedBlob) -> io::Result<()>;
+
+    /// Streams the bytes in `range`, which must lie within the blob.
+    async fn read(&self, sha256: &str, range: Range<u64>) -> io::Result<ByteStream>;
+
+    /// Removing a blob that is not stored is not an error.
+    async fn delete(&self, sha256: &str) -> io::Result<()>;
+}
+
+/// Blobs as files under `root`, at `blobs/<first two hex digits>/<hash>`.
+/// Uploads are written to `tmp/` and renamed into place once hashed.
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    /// Directories are created on the first upload.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalBlobStore { root: root.into() }
+    }
+
+    // Keys are checked so they can never name a path outside `root`
+    fn blob_path(&self, sha256: &str) -> io::Result<PathBuf> {
+        let is_key = sha256.len() == 64
+            && sha256
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if !is_key {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{sha256}` is not a SHA-256 hex digest"),
+            ));
+        }
+        Ok(self.root.join("blobs").join(&sha256[..2]).join(sha256))
+    }
+
+    fn tmp_path(&self, key: Uuid) -> PathBuf {
+        self.root.join("tmp").join(key.to_string())
+    }
+}
+
+// Copies `data` into a new file at `path`, hashing it on the way
+async fn write_hashed(path: &Path, mut data: ByteStream) -> io::Result<StoredBlob> {
+    let mut file = fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut size = 0;
+    while let Some(chunk) = data.next().await {
+        let chunk = chunk?;
+        hasher.update(&chunk);
+        size += chunk.len() as u64;
+        file.write_all(&chunk).await?;
+    }
+    file.sync_all().await?;
+    Ok(StoredBlob {
+        sha256: format!("{:x}", hasher.finalize()),
+        size,
+    })
+}
+
+#[async_trait(?Send)]
+impl BlobStore for LocalBlobStore {
+    async fn stage(&self, data: ByteStream) -> io::Result<StagedBlob> {
+        fs::create_dir_all(self.root.join("tmp")).await?;
+        let key = Uuid::new_v4();
+        let tmp_path = self.tmp_path(key);
+
+        match write_hashed(&tmp_path, data).await {
+            Ok(StoredBlob { sha256, size }) => Ok(StagedBlob { sha256, size, key }),
+            Err(e) => {
+                let _ = fs::remove_file(&tmp_path).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn commit(&self, staged: StagedBlob) -> io::Result<StoredBlob> {
+        let tmp_path = self.tmp_path(staged.key);
+        let path = self.blob_path(&staged.sha256)?;
+        if fs::try_exists(&path).await? {
+            fs::remove_file(&tmp_path).await?;
+        } else {
+            fs::create_dir_all(path.parent().expect("blob paths have a parent")).await?;
+            fs::rename(&tmp_path, &path).await?;
+        }
+        Ok(StoredBlob {
+            sha256: staged.sha256,
+            size: staged.size,
+        })
+    }
+
+    async fn discard(&self, staged: StagedBlob) -> io::Result<()> {
+        fs::remove_file(self.tmp_path(staged.key)).await
+    }
+
+    async fn read(&self, sha256: &str, range: Range<u64>) -> io::Result<ByteStream> {
+        let mut file = fs::File::open(self.blob_path(sha256)?).await?;
+        file.seek(SeekFrom::Start(range.start)).await?;
+        let remaining = range.end.saturating_sub(range.start);
+
+        let chunks = stream::try_unfold((file, remaining), |(mut file, remaining)| async move {
+            if remaining == 0 {
+                return Ok(None);
+            }
+            let mut buf = vec![0; remaining.min(READ_CHUNK) as usize];
+            let read = file.read(&mut buf).await?;
+            if read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "blob ended early",
+                ));
+            }
+            buf.truncate(read);
+            Ok(Some((Bytes::from(buf), (file, remaining - read as u64))))
+        });
+        Ok(Box::pin(chunks))
+    }
+
+    async fn delete(&self, sha256: &str) -> io::Result<()> {
+        match fs::remove_file(self.blob_path(sha256)?).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A lock per blob. Whoever commits a blob holds it until the blob is
+/// attached, and whoever deletes one holds it from checking that the blob is
+/// unused until it is gone, so the two cannot interleave.
+#[derive(Default)]
+pub struct BlobLocks {
+    locks: Mutex<HashMap<String, Weak<AsyncMutex<()>>>>,
+}
+
+impl BlobLocks {
+    pub async fn lock(&self, sha256: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            // Locks nobody holds or waits for are dropped
+            locks.retain(|_, lock| lock.strong_count() > 0);
+            match locks.get(sha256).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    let lock = Arc::new(AsyncMutex::new(()));
+                    locks.insert(sha256.to_string(), Arc::downgrade(&lock));
+                    lock
+                }
+            }
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> LocalBlobStore {
+        LocalBlobStore::new(std::env::temp_dir().join(format!("blob-test-{}", Uuid::new_v4())))
+    }
+
+    fn chunks(parts: &[&'static [u8]]) -> ByteStream {
+        let parts: Vec<io::Result<Bytes>> =
+            parts.iter().map(|p| Ok(Bytes::from_static(p))).collect();
+        Box::pin(stream::iter(parts))
+    }
+
+    async fn put(store: &LocalBlobStore, data: ByteStream) -> io::Result<StoredBlob> {
+        let staged = store.stage(data).await?;
+        store.commit(staged).await
+    }
+
+    async fn collect(data: ByteStream) -> Vec<u8> {
+        let parts: Vec<io::Result<Bytes>> = data.collect().await;
+        parts
+            .into_iter()
+            .flat_map(|p| p.unwrap().to_vec())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn stores_identical_content_once() {
+        let store = store();
+        let first = put(&store, chunks(&[b"hello ", b"world"])).await.unwrap();
+        assert_eq!(
+            first.sha256,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(first.size, 11);
+        let second = put(&store, chunks(&[b"hello world"])).await.unwrap();
+        assert_eq!(first, second);
+
+        let stored: Vec<_> = std::fs::read_dir(store.root.join("blobs").join("b9"))
+            .unwrap()
+            .collect();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(
+            std::fs::read_dir(store.root.join("tmp")).unwrap().count(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_ranges() {
+        let store = store();
+        let blob = put(&store, chunks(&[b"0123456789"])).await.unwrap();
+        assert_eq!(
+            collect(store.read(&blob.sha256, 0..10).await.unwrap()).await,
+            b"0123456789"
+        );
+        assert_eq!(
+            collect(store.read(&blob.sha256, 3..7).await.unwrap()).await,
+            b"3456"
+        );
+        assert!(
+            collect(store.read(&blob.sha256, 4..4).await.unwrap())
+                .await
+                .is_empty()
+        );
+
+        store.delete(&blob.sha256).await.unwrap();
+        store.delete(&blob.sha256).await.unwrap();
+        let e = store.read(&blob.sha256, 0..10).await.err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn failed_uploads_leave_nothing_behind() {
+        let store = store();
+        let failing: ByteStream = Box::pin(stream::iter(vec![
+            Ok(Bytes::from_static(b"partial")),
+            Err(io::Error::new(io::ErrorKind::FileTooLarge, "too large")),
+        ]));
+        let e = put(&store, failing).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::FileTooLarge);
+        assert_eq!(
+            std::fs::read_dir(store.root.join("tmp")).unwrap().count(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_keys_that_are_not_digests() {
+        let store = store();
+        for key in ["../../etc/passwd", "ABC", &"g".repeat(64)] {
+            assert_eq!(
+                store.delete(key).await.unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn staged_blobs_are_stored_once_committed() {
+        let store = store();
+        let staged = store.stage(chunks(&[b"hello world"])).await.unwrap();
+        let e = store.read(&staged.sha256, 0..11).await.err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+
+        let stored = store.commit(staged).await.unwrap();
+        assert_eq!(
+            collect(store.read(&stored.sha256, 0..11).await.unwrap()).await,
+            b"hello world"
+        );
+
+        let staged = store.stage(chunks(&[b"goodbye"])).await.unwrap();
+        let sha256 = staged.sha256.clone();
+        store.discard(staged).await.unwrap();
+        let e = store.read(&sha256, 0..7).await.err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            std::fs::read_dir(store.root.join("tmp")).unwrap().count(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn locks_are_per_blob() {
+        let locks = BlobLocks::default();
+        let held = locks.lock("a").await;
+        // Another blob's lock is free
+        drop(locks.lock("b").await);
+        let waiting = locks.lock("a");
+        tokio::pin!(waiting);
+        assert!(futures_util::poll!(waiting.as_mut()).is_pending());
+        drop(held);
+        waiting.await;
+    }
+}
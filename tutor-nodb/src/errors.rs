@@ -0,0 +1,142 @@
+use crate::store::StoreError;
+use crate::validation::ValidationErrors;
+use actix_web::{HttpRequest, HttpResponse, ResponseError, error, http::StatusCode};
+use serde::Serialize;
+use std::fmt;
+
+/// Every failure the HTTP API can report. Rendered as
+/// `{"code": ..., "message": ..., "details": ...}` with a matching status.
+#[derive(Debug)]
+pub enum ApiError {
+    Validation(ValidationErrors),
+    BadRequest(String),
+    NotFound(String),
+    Conflict(String),
+    Unauthorized(String),
+    Forbidden(String),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Database(sqlx::Error),
+    /// Reading or writing uploaded files failed.
+    Storage(std::io::Error),
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    code: &'static str,
+    message: String,
+    details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::PayloadTooLarge(_) => "payload_too_large",
+            ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            ApiError::Database(_) | ApiError::Storage(_) => "internal_error",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Validation(_) => write!(f, "Request validation failed"),
+            ApiError::BadRequest(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::PayloadTooLarge(msg)
+            | ApiError::UnsupportedMediaType(msg) => write!(f, "{msg}"),
+            // Never leak database or filesystem internals to clients
+            ApiError::Database(_) | ApiError::Storage(_) => write!(f, "Internal server error"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation(_) | ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Database(_) | ApiError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            ApiError::Database(e) => eprintln!("Database error: {e}"),
+            ApiError::Storage(e) => eprintln!("Storage error: {e}"),
+            _ => {}
+        }
+        let details = match self {
+            ApiError::Validation(errors) => serde_json::to_value(&errors.errors).ok(),
+            _ => None,
+        };
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            details,
+        })
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::Validation(errors)
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(e: StoreError) -> Self {
+        match e {
+            StoreError::Conflict(msg) => ApiError::Conflict(msg),
+            StoreError::NotFound(msg) => ApiError::NotFound(msg),
+            StoreError::Database(e) => ApiError::Database(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(e: std::io::Error) -> Self {
+        ApiError::Storage(e)
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        StoreError::from(e).into()
+    }
+}
+
+/// Turns malformed JSON bodies (bad types, unknown fields) into the same
+/// shape as field validation failures.
+pub fn json_error_handler(err: error::JsonPayloadError, _req: &HttpRequest) -> error::Error {
+    let mut errors = ValidationErrors::default();
+    errors.add("body", err.to_string());
+    ApiError::Validation(errors).into()
+}
+
+pub fn query_error_handler(err: error::QueryPayloadError, _req: &HttpRequest) -> error::Error {
+    ApiError::BadRequest(err.to_string()).into()
+}
+
+pub fn path_error_handler(err: error::PathError, req: &HttpRequest) -> error::Error {
+    ApiError::NotFound(format!("{} does not exist: {err}", req.path())).into()
+}
+
+pub async fn not_found_handler(req: HttpRequest) -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound(format!("{} does not exist", req.path())))
+}
@@ -1,10 +1,88 @@
-use crate::models::{Course, Tutor};
+use crate::auth::{self, Caller, Role, TokenKind, TokenPair};
+use crate::errors::ApiError;
+use crate::ical::{self, CalendarEvent, EventTime};
+use crate::models::{
+    AccountKind, Attendance, AttendanceMarks, AttendanceStatus, AttendanceTally, Availability,
+    AvailabilityException, AvailabilityKind, Booking, BookingStatus, CALENDAR_FUTURE_DAYS,
+    CALENDAR_PAST_DAYS, CANCELLATION_NOTICE_HOURS, CHECK_IN_CODE_MINUTES, CHECK_IN_OPENS_MINUTES,
+    CalendarImport, CheckIn, CheckInCode, Course, CourseModule, CoursePage, CourseQuery,
+    CourseSession, CourseType, CourseUpdate, Curriculum, LATE_AFTER_MINUTES, Lesson, LessonUpdate,
+    LoginRequest, MAX_IMPORTED_EVENTS, MAX_NOTE_LEN, Material, ModuleTitle,
+    NewAvailabilityException, NewBooking, NewCourse, NewCourseSession, NewEnrollment, NewLesson,
+    NewReview, NewStudent, NewTutor, PublicStudent, PublicTutor, RefreshRequest, Reorder, Review,
+    RoleUpdate, ScheduleQuery, SearchHit, SearchQuery, TimeRange, Tutor, TutorProfile, TutorSearch,
+    TutorUpdate, UpcomingQuery, WaitlistEntry, check_pricing,
+};
+use crate::schedule;
 use crate::state::AppState;
-use actix_web::{web, HttpResponse, Responder};
-use std::collections::HashMap;
+use crate::store::{AttachOutcome, EnrollOutcome, ReviewOutcome};
+use crate::upload::{ALLOWED_CONTENT_TYPES, SNIFF_LEN, clean_filename, matches_content_type};
+use crate::validation::{Validate, ValidationErrors};
+use actix_multipart::{Multipart, MultipartError};
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use futures_util::{StreamExt, TryStreamExt};
+use std::io;
+use std::str::FromStr;
 use uuid::Uuid;
 
-pub async fn health_check_handler(app_state: web::Data<AppState>) -> impl Responder {
+fn course_not_found(course_id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Course with ID {course_id} not found"))
+}
+
+fn tutor_not_found(tutor_id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Tutor with ID {tutor_id} not found"))
+}
+
+fn student_not_found(student_id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Student with ID {student_id} not found"))
+}
+
+// Tutors may only change their own profile; admins may change any
+fn ensure_tutor_self(caller: &Caller, tutor_id: Uuid) -> Result<(), ApiError> {
+    if !caller.is_admin() && caller.id != tutor_id {
+        return Err(ApiError::Forbidden(format!(
+            "Tutor {} may not modify tutor {tutor_id}",
+            caller.id
+        )));
+    }
+    Ok(())
+}
+
+// Students act only for themselves; admins may act for any student
+fn ensure_student_self(caller: &Caller, student_id: Uuid) -> Result<(), ApiError> {
+    if !caller.is_admin() && caller.id != student_id {
+        return Err(ApiError::Forbidden(format!(
+            "Caller {} may not act for student {student_id}",
+            caller.id
+        )));
+    }
+    Ok(())
+}
+
+// Loads a course and checks the caller posted it, unless they are an admin
+async fn find_owned_course(
+    app_state: &AppState,
+    course_id: Uuid,
+    caller: &Caller,
+) -> Result<Course, ApiError> {
+    let tutor_id = caller.id;
+    let course = app_state
+        .courses
+        .find(course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    if !caller.is_admin() && !course.is_posted_by_tutor(tutor_id) {
+        return Err(ApiError::Forbidden(format!(
+            "Course with ID {course_id} is not owned by tutor {tutor_id}"
+        )));
+    }
+    Ok(course)
+}
+
+pub async fn health_check_handler(app_state: web::Data<AppState>) -> HttpResponse {
     let health_check_response = &app_state.health_check_response;
     let mut visit_count = app_state.visit_count.lock().unwrap();
     let response = format!("{health_check_response} {visit_count} times");
@@ -14,130 +92,1926 @@ pub async fn health_check_handler(app_state: web::Data<AppState>) -> impl Respon
 
 pub async fn create_new_tutor(
     app_state: web::Data<AppState>,
-    tutor: web::Json<HashMap<String, String>>,
-) -> impl Responder {
-    let tutor_name = match tutor.get("name") {
-        Some(name) => name.clone(),
-        None => return HttpResponse::BadRequest().body("No tutor name provided"),
+    tutor: web::Json<NewTutor>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor = tutor.into_inner();
+    tutor.validate()?;
+
+    let password_hash = auth::hash_password(&tutor.password);
+    let new_tutor = app_state
+        .tutors
+        .create(tutor.name, tutor.email, password_hash)
+        .await?;
+    Ok(HttpResponse::Ok().json(new_tutor.tutor_id)) // return tutor_id as JSON
+}
+
+pub async fn login_handler(
+    app_state: web::Data<AppState>,
+    login: web::Json<LoginRequest>,
+) -> Result<web::Json<TokenPair>, ApiError> {
+    login.validate()?;
+    let LoginRequest {
+        email,
+        password,
+        account,
+    } = login.into_inner();
+
+    let (caller, password_hash) = match account {
+        AccountKind::Tutor => app_state.tutors.find_credentials(&email).await?.map(|c| {
+            let caller = Caller {
+                id: c.tutor_id,
+                role: c.role,
+            };
+            (caller, c.password_hash)
+        }),
+        AccountKind::Student => app_state.students.find_credentials(&email).await?.map(|c| {
+            let caller = Caller {
+                id: c.student_id,
+                role: Role::Student,
+            };
+            (caller, c.password_hash)
+        }),
+    }
+    .unzip();
+
+    // One message, and about the same wait, for an unknown email and a
+    // wrong password alike
+    let invalid = || ApiError::Unauthorized("Invalid email or password".to_string());
+    let (Some(caller), Some(password_hash)) = (caller, password_hash.flatten()) else {
+        auth::verify_no_account(&password);
+        return Err(invalid());
     };
+    if !auth::verify_password(&password, &password_hash) {
+        return Err(invalid());
+    }
+
+    Ok(web::Json(app_state.jwt.issue(caller)))
+}
+
+pub async fn refresh_token_handler(
+    app_state: web::Data<AppState>,
+    refresh: web::Json<RefreshRequest>,
+) -> Result<web::Json<TokenPair>, ApiError> {
+    refresh.validate()?;
 
-    let tutor_email = match tutor.get("email") {
-        Some(email) => email.clone(),
-        None => return HttpResponse::BadRequest().body("No tutor email provided"),
+    let caller = app_state
+        .jwt
+        .verify(&refresh.refresh_token, TokenKind::Refresh)?;
+    // The role is looked up again so promotions and demotions take effect,
+    // and deleted accounts keep no access through older refresh tokens
+    let role = match caller.role {
+        Role::Student => app_state
+            .students
+            .find(caller.id)
+            .await?
+            .map(|_| Role::Student),
+        Role::Admin | Role::Tutor => app_state.tutors.find_role(caller.id).await?,
     };
+    let role = role
+        .ok_or_else(|| ApiError::Unauthorized(format!("Account {} no longer exists", caller.id)))?;
+    Ok(web::Json(app_state.jwt.issue(Caller {
+        id: caller.id,
+        role,
+    })))
+}
 
-    let new_tutor = Tutor::new(tutor_name, tutor_email);
-    let tutor_id = new_tutor.tutor_id;
+pub async fn get_my_profile_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+) -> Result<web::Json<TutorProfile>, ApiError> {
+    let tutor =
+        app_state.tutors.find(caller.id).await?.ok_or_else(|| {
+            ApiError::Unauthorized(format!("Account {} no longer exists", caller.id))
+        })?;
+    // The token's role may predate a promotion or demotion
+    let role = app_state
+        .tutors
+        .find_role(caller.id)
+        .await?
+        .unwrap_or(caller.role);
+    let courses = app_state.courses.list_for_tutor(caller.id).await?;
 
-    {
-        let mut tutors = app_state.tutors.lock().unwrap();
-        tutors.push(new_tutor);
+    Ok(web::Json(TutorProfile {
+        tutor,
+        role,
+        courses,
+    }))
+}
+
+pub async fn search_tutors_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    query: web::Query<TutorSearch>,
+) -> Result<web::Json<Vec<Tutor>>, ApiError> {
+    query.validate()?;
+
+    let tutors = app_state
+        .tutors
+        .search_by_email(&query.email, query.limit)
+        .await?;
+    // Searches can reveal accounts, so every one is attributable to an admin
+    app_state
+        .audit
+        .record(
+            caller.id,
+            "tutor.search",
+            &format!(
+                "email contains {:?}, {} match(es)",
+                query.email,
+                tutors.len()
+            ),
+        )
+        .await?;
+    Ok(web::Json(tutors))
+}
+
+/// The tutor's profile, with their email only for the tutor and admins.
+pub async fn get_tutor_handler(
+    app_state: web::Data<AppState>,
+    caller: Option<Caller>,
+    params: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor_id = params.into_inner();
+
+    let tutor = app_state
+        .tutors
+        .find(tutor_id)
+        .await?
+        .ok_or_else(|| tutor_not_found(tutor_id))?;
+    if caller.is_some_and(|c| c.role != Role::Student && ensure_tutor_self(&c, tutor_id).is_ok()) {
+        return Ok(HttpResponse::Ok().json(tutor));
     }
+    Ok(HttpResponse::Ok().json(PublicTutor::from(tutor)))
+}
 
-    HttpResponse::Ok().json(tutor_id) // return tutor_id as JSON
+pub async fn update_tutor_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    update: web::Json<TutorUpdate>,
+) -> Result<web::Json<Tutor>, ApiError> {
+    let tutor_id = params.into_inner();
+    ensure_tutor_self(&caller, tutor_id)?;
+    let update = update.into_inner();
+    update.validate()?;
+
+    let mut tutor = app_state
+        .tutors
+        .find(tutor_id)
+        .await?
+        .ok_or_else(|| tutor_not_found(tutor_id))?;
+
+    if let Some(name) = update.name {
+        tutor.name = name;
+    }
+    if let Some(email) = update.email {
+        tutor.email = email;
+    }
+
+    let tutor = app_state
+        .tutors
+        .update(tutor)
+        .await?
+        .ok_or_else(|| tutor_not_found(tutor_id))?;
+    Ok(web::Json(tutor))
 }
 
-pub async fn get_tutor_id(
+pub async fn set_tutor_role_handler(
     app_state: web::Data<AppState>,
-    tutor_details: web::Json<HashMap<String, String>>,
-) -> impl Responder {
-    let tutor_name = match tutor_details.get("name") {
-        Some(name) => name,
-        None => return HttpResponse::BadRequest().body("No name provided"),
-    };
+    params: web::Path<Uuid>,
+    update: web::Json<RoleUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor_id = params.into_inner();
+    update.validate()?;
 
-    let tutor_email = match tutor_details.get("email") {
-        Some(email) => email,
-        None => return HttpResponse::BadRequest().body("No email provided"),
-    };
+    if !app_state.tutors.set_role(tutor_id, update.role).await? {
+        return Err(tutor_not_found(tutor_id));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn delete_tutor_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor_id = params.into_inner();
+    ensure_tutor_self(&caller, tutor_id)?;
+
+    // Refuse rather than cascade: a tutor's courses must be removed first
+    let courses = app_state.courses.list_for_tutor(tutor_id).await?;
+    if !courses.is_empty() {
+        return Err(ApiError::Conflict(format!(
+            "Tutor with ID {tutor_id} still has {} course(s)",
+            courses.len()
+        )));
+    }
+
+    app_state
+        .tutors
+        .delete(tutor_id)
+        .await?
+        .ok_or_else(|| tutor_not_found(tutor_id))?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn new_course_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    new_course: web::Json<NewCourse>,
+) -> Result<HttpResponse, ApiError> {
+    new_course.validate()?;
+    let tutor_id = caller.id;
+    let NewCourse {
+        course_name,
+        description,
+        enrolled_limit,
+        course_type,
+        price,
+        currency,
+    } = new_course.into_inner();
+
+    // No orphan courses: a token can outlive the tutor it was issued to
+    if app_state.tutors.find(tutor_id).await?.is_none() {
+        return Err(ApiError::Unauthorized(format!(
+            "Tutor {tutor_id} no longer exists"
+        )));
+    }
+
+    let mut course = Course::with_current_time(tutor_id, course_name);
+    course.description = description;
+    if let Some(enrolled_limit) = enrolled_limit {
+        course.enrolled_limit = enrolled_limit;
+    }
+    course.course_type = course_type;
+    course.price = price;
+    course.currency = currency;
+    let course = app_state.courses.add(course).await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("/courses/{}", course.course_id)))
+        .json(course))
+}
+
+pub async fn list_courses_handler(
+    app_state: web::Data<AppState>,
+    query: web::Query<CourseQuery>,
+) -> Result<web::Json<CoursePage>, ApiError> {
+    query.validate()?;
+
+    let page = app_state.courses.list(&query).await?;
+    Ok(web::Json(page))
+}
+
+pub async fn search_courses_handler(
+    app_state: web::Data<AppState>,
+    query: web::Query<SearchQuery>,
+) -> Result<web::Json<Vec<SearchHit>>, ApiError> {
+    query.validate()?;
+
+    let hits = app_state.courses.search(&query.q, query.limit).await?;
+    Ok(web::Json(hits))
+}
+
+pub async fn get_tutor_courses_handler(
+    app_state: web::Data<AppState>,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Vec<Course>>, ApiError> {
+    let tutor_id = params.into_inner();
+
+    let courses = app_state.courses.list_for_tutor(tutor_id).await?;
+    Ok(web::Json(courses))
+}
+
+pub async fn get_course_details(
+    app_state: web::Data<AppState>,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Course>, ApiError> {
+    let course_id = params.into_inner();
 
-    let tutors = app_state.tutors.lock().unwrap();
+    let course = app_state
+        .courses
+        .find(course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+    Ok(web::Json(course))
+}
 
-    if let Some(tutor) = tutors
+pub async fn update_course_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    update: web::Json<CourseUpdate>,
+) -> Result<web::Json<Course>, ApiError> {
+    let course_id = params.into_inner();
+    let update = update.into_inner();
+    update.validate()?;
+
+    let mut course = find_owned_course(&app_state, course_id, &caller).await?;
+
+    if let Some(course_name) = update.course_name {
+        course.course_name = course_name;
+    }
+    if let Some(description) = update.description {
+        course.description = description;
+    }
+    if update.refresh_posted_time {
+        course.update_posted_time();
+    }
+    if let Some(course_type) = update.course_type {
+        course.course_type = course_type;
+        if course_type == CourseType::Free {
+            course.price = None;
+            course.currency = None;
+        }
+    }
+    if let Some(price) = update.price {
+        course.price = Some(price);
+    }
+    if let Some(currency) = update.currency {
+        course.currency = Some(currency);
+    }
+    // Pricing is checked on the result so partial updates stay consistent
+    let mut errors = ValidationErrors::default();
+    check_pricing(
+        &mut errors,
+        course.course_type,
+        course.price.as_ref(),
+        course.currency.as_deref(),
+    );
+    errors.into_result()?;
+
+    let course = app_state
+        .courses
+        .update(course)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+    Ok(web::Json(course))
+}
+
+pub async fn delete_course_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let course_id = params.into_inner();
+
+    find_owned_course(&app_state, course_id, &caller).await?;
+    let lesson_ids: Vec<Uuid> = app_state
+        .curriculum
+        .list_lessons(course_id, false)
+        .await?
         .iter()
-        .find(|t| t.name == *tutor_name && t.email == *tutor_email)
-    {
-        HttpResponse::Ok().json(tutor.tutor_id)
-    } else {
-        HttpResponse::NotFound().body(format!(
-            "Tutor with name `{}` and email `{}` does not exist",
-            tutor_name, tutor_email
+        .map(|l| l.lesson_id)
+        .collect();
+    let blobs = attached_blobs(&app_state, &lesson_ids).await?;
+
+    app_state
+        .courses
+        .delete(course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+    release_blobs(&app_state, &blobs).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn create_new_student(
+    app_state: web::Data<AppState>,
+    student: web::Json<NewStudent>,
+) -> Result<HttpResponse, ApiError> {
+    let student = student.into_inner();
+    student.validate()?;
+
+    let password_hash = auth::hash_password(&student.password);
+    let student = app_state
+        .students
+        .create(student.name, student.email, password_hash)
+        .await?;
+    Ok(HttpResponse::Created()
+        .insert_header((
+            header::LOCATION,
+            format!("/students/{}", student.student_id),
         ))
+        .json(student))
+}
+
+/// The student's profile, with their email only for the student and admins.
+pub async fn get_student_handler(
+    app_state: web::Data<AppState>,
+    caller: Option<Caller>,
+    params: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let student_id = params.into_inner();
+
+    let student = app_state
+        .students
+        .find(student_id)
+        .await?
+        .ok_or_else(|| student_not_found(student_id))?;
+    let own = |c: &Caller| c.role == Role::Student && c.id == student_id;
+    if caller.is_some_and(|c| c.is_admin() || own(&c)) {
+        return Ok(HttpResponse::Ok().json(student));
     }
+    Ok(HttpResponse::Ok().json(PublicStudent::from(student)))
 }
 
-pub async fn new_course_handler(
+pub async fn enroll_student_handler(
     app_state: web::Data<AppState>,
-    new_course: web::Json<HashMap<String, String>>,
-) -> impl Responder {
-    let tutor_id = match new_course.get("tutor_id") {
-        Some(id_str) => match Uuid::parse_str(id_str) {
-            Ok(id) => id,
-            Err(_) => return HttpResponse::BadRequest().body("Invalid tutor_id format"),
-        },
-        None => return HttpResponse::BadRequest().body("No tutor_id provided"),
-    };
+    caller: Caller,
+    params: web::Path<Uuid>,
+    new_enrollment: web::Json<NewEnrollment>,
+) -> Result<HttpResponse, ApiError> {
+    let course_id = params.into_inner();
+    new_enrollment.validate()?;
+    let student_id = new_enrollment.student_id;
+    ensure_student_self(&caller, student_id)?;
 
-    let course_name = match new_course.get("course_name") {
-        Some(name) => name.clone(),
-        None => return HttpResponse::BadRequest().body("No course_name provided"),
-    };
+    if app_state.courses.find(course_id).await?.is_none() {
+        return Err(course_not_found(course_id));
+    }
+    if app_state.students.find(student_id).await?.is_none() {
+        let mut errors = ValidationErrors::default();
+        errors.add(
+            "student_id",
+            format!("Student with ID {student_id} does not exist"),
+        );
+        return Err(errors.into());
+    }
+
+    match app_state.courses.enroll(course_id, student_id).await? {
+        EnrollOutcome::Enrolled(enrollment) => Ok(HttpResponse::Created()
+            .insert_header((
+                header::LOCATION,
+                format!("/courses/{course_id}/enrollments/{student_id}"),
+            ))
+            .json(enrollment)),
+        // Not enrolled yet: the seat is granted once someone withdraws
+        EnrollOutcome::Waitlisted(entry) => Ok(HttpResponse::Accepted()
+            .insert_header((header::LOCATION, format!("/courses/{course_id}/waitlist")))
+            .json(entry)),
+    }
+}
+
+pub async fn withdraw_student_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, student_id) = params.into_inner();
+    ensure_student_self(&caller, student_id)?;
+
+    if !app_state.courses.withdraw(course_id, student_id).await? {
+        return Err(ApiError::NotFound(format!(
+            "Student {student_id} is not enrolled in or waitlisted for course {course_id}"
+        )));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn get_waitlist_handler(
+    app_state: web::Data<AppState>,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Vec<WaitlistEntry>>, ApiError> {
+    let course_id = params.into_inner();
+
+    if app_state.courses.find(course_id).await?.is_none() {
+        return Err(course_not_found(course_id));
+    }
+    let waitlist = app_state.courses.list_waitlist(course_id).await?;
+    Ok(web::Json(waitlist))
+}
+
+pub async fn post_review_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    new_review: web::Json<NewReview>,
+) -> Result<HttpResponse, ApiError> {
+    let course_id = params.into_inner();
+    new_review.validate()?;
+    let NewReview {
+        student_id,
+        rating,
+        body,
+    } = new_review.into_inner();
+    ensure_student_self(&caller, student_id)?;
+
+    if app_state.courses.find(course_id).await?.is_none() {
+        return Err(course_not_found(course_id));
+    }
+    if app_state.students.find(student_id).await?.is_none() {
+        let mut errors = ValidationErrors::default();
+        errors.add(
+            "student_id",
+            format!("Student with ID {student_id} does not exist"),
+        );
+        return Err(errors.into());
+    }
+
+    match app_state
+        .courses
+        .post_review(course_id, student_id, rating, body)
+        .await?
+    {
+        ReviewOutcome::Created(review) => Ok(HttpResponse::Created().json(review)),
+        ReviewOutcome::Updated(review) => Ok(HttpResponse::Ok().json(review)),
+        ReviewOutcome::NotEnrolled => Err(ApiError::Forbidden(format!(
+            "Student {student_id} is not enrolled in course {course_id}"
+        ))),
+    }
+}
+
+pub async fn get_course_reviews_handler(
+    app_state: web::Data<AppState>,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Vec<Review>>, ApiError> {
+    let course_id = params.into_inner();
+
+    if app_state.courses.find(course_id).await?.is_none() {
+        return Err(course_not_found(course_id));
+    }
+    let reviews = app_state.courses.list_reviews(course_id).await?;
+    Ok(web::Json(reviews))
+}
+
+async fn ensure_tutor_exists(app_state: &AppState, tutor_id: Uuid) -> Result<(), ApiError> {
+    match app_state.tutors.find(tutor_id).await? {
+        Some(_) => Ok(()),
+        None => Err(tutor_not_found(tutor_id)),
+    }
+}
+
+fn timezone_of(availability: &Availability) -> Tz {
+    // Timezones are validated before they are stored
+    availability.timezone.parse().unwrap_or(Tz::UTC)
+}
+
+// Weekly slots and exceptions, minus confirmed bookings and course sessions
+async fn open_windows(
+    app_state: &AppState,
+    tutor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<TimeRange>, ApiError> {
+    let availability = app_state.schedules.availability(tutor_id).await?;
+    let timezone = timezone_of(&availability);
+    let exceptions = app_state
+        .schedules
+        .list_exceptions(tutor_id, from, to)
+        .await?;
+    let mut busy: Vec<TimeRange> = app_state
+        .schedules
+        .list_bookings(tutor_id, from, to)
+        .await?
+        .into_iter()
+        .filter(|b| b.status == BookingStatus::Confirmed)
+        .map(|b| TimeRange {
+            starts_at: b.starts_at,
+            ends_at: b.ends_at,
+        })
+        .collect();
+    // Course sessions keep the tutor busy too
+    busy.extend(
+        app_state
+            .courses
+            .list_tutor_sessions(tutor_id, from, to)
+            .await?
+            .into_iter()
+            .map(|s| TimeRange {
+                starts_at: s.starts_at,
+                ends_at: s.ends_at,
+            }),
+    );
+    Ok(schedule::open_windows(
+        timezone,
+        &availability.slots,
+        &exceptions,
+        &busy,
+        from,
+        to,
+    ))
+}
+
+pub async fn get_availability_handler(
+    app_state: web::Data<AppState>,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Availability>, ApiError> {
+    let tutor_id = params.into_inner();
+    ensure_tutor_exists(&app_state, tutor_id).await?;
+
+    Ok(web::Json(app_state.schedules.availability(tutor_id).await?))
+}
+
+pub async fn set_availability_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    availability: web::Json<Availability>,
+) -> Result<web::Json<Availability>, ApiError> {
+    let tutor_id = params.into_inner();
+    availability.validate()?;
+    ensure_tutor_self(&caller, tutor_id)?;
+    ensure_tutor_exists(&app_state, tutor_id).await?;
+
+    let mut availability = availability.into_inner();
+    availability
+        .slots
+        .sort_by_key(|slot| (slot.weekday.number_from_monday(), slot.start));
+    Ok(web::Json(
+        app_state
+            .schedules
+            .set_availability(tutor_id, availability)
+            .await?,
+    ))
+}
+
+pub async fn get_open_windows_handler(
+    app_state: web::Data<AppState>,
+    params: web::Path<Uuid>,
+    query: web::Query<ScheduleQuery>,
+) -> Result<web::Json<Vec<TimeRange>>, ApiError> {
+    let tutor_id = params.into_inner();
+    query.validate()?;
+    ensure_tutor_exists(&app_state, tutor_id).await?;
+
+    let (from, to) = query.range();
+    Ok(web::Json(
+        open_windows(&app_state, tutor_id, from, to).await?,
+    ))
+}
 
-    let course = Course::with_current_time(tutor_id, course_name);
+pub async fn add_availability_exception_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    new_exception: web::Json<NewAvailabilityException>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor_id = params.into_inner();
+    new_exception.validate()?;
+    ensure_tutor_self(&caller, tutor_id)?;
+    ensure_tutor_exists(&app_state, tutor_id).await?;
 
-    // Count existing courses for this tutor
-    let course_count = {
-        let courses = app_state.courses.lock().unwrap();
-        courses.iter().filter(|c| c.tutor_id == tutor_id).count()
+    let new_exception = new_exception.into_inner();
+    let exception = AvailabilityException {
+        exception_id: Uuid::new_v4(),
+        tutor_id,
+        kind: new_exception.kind,
+        starts_at: new_exception.starts_at.expect("validated"),
+        ends_at: new_exception.ends_at.expect("validated"),
+        reason: new_exception.reason,
+        ical_uid: None,
     };
+    let exception = app_state.schedules.add_exception(exception).await?;
+    Ok(HttpResponse::Created()
+        .insert_header((
+            header::LOCATION,
+            format!(
+                "/tutors/{tutor_id}/availability/exceptions/{}",
+                exception.exception_id
+            ),
+        ))
+        .json(exception))
+}
 
-    // Add new course
+pub async fn list_availability_exceptions_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    query: web::Query<ScheduleQuery>,
+) -> Result<web::Json<Vec<AvailabilityException>>, ApiError> {
+    let tutor_id = params.into_inner();
+    query.validate()?;
+    ensure_tutor_self(&caller, tutor_id)?;
+
+    let (from, to) = query.range();
+    Ok(web::Json(
+        app_state
+            .schedules
+            .list_exceptions(tutor_id, from, to)
+            .await?,
+    ))
+}
+
+pub async fn delete_availability_exception_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (tutor_id, exception_id) = params.into_inner();
+    ensure_tutor_self(&caller, tutor_id)?;
+
+    if !app_state
+        .schedules
+        .delete_exception(tutor_id, exception_id)
+        .await?
     {
-        let mut courses = app_state.courses.lock().unwrap();
-        courses.push(course);
+        return Err(ApiError::NotFound(format!(
+            "Tutor {tutor_id} has no availability exception {exception_id}"
+        )));
     }
+    Ok(HttpResponse::NoContent().finish())
+}
 
-    HttpResponse::Ok().body(format!(
-        "Added course for tutor {}, total courses: {}",
+pub async fn book_tutor_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    new_booking: web::Json<NewBooking>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor_id = params.into_inner();
+    new_booking.validate()?;
+    let new_booking = new_booking.into_inner();
+    ensure_student_self(&caller, new_booking.student_id)?;
+    ensure_tutor_exists(&app_state, tutor_id).await?;
+    if app_state
+        .students
+        .find(new_booking.student_id)
+        .await?
+        .is_none()
+    {
+        let mut errors = ValidationErrors::default();
+        errors.add(
+            "student_id",
+            format!("Student with ID {} does not exist", new_booking.student_id),
+        );
+        return Err(errors.into());
+    }
+
+    let starts_at = new_booking.starts_at.expect("validated");
+    let ends_at = new_booking.ends_at.expect("validated");
+    let windows = open_windows(&app_state, tutor_id, starts_at, ends_at).await?;
+    if !schedule::covers(&windows, starts_at, ends_at) {
+        return Err(ApiError::Conflict(format!(
+            "Tutor {tutor_id} is not available from {starts_at} to {ends_at}"
+        )));
+    }
+
+    // The store still refuses an overlapping booking that raced this one
+    let booking = Booking::new(
         tutor_id,
-        course_count + 1
+        new_booking.student_id,
+        starts_at,
+        ends_at,
+        new_booking.note,
+    );
+    let booking = app_state.schedules.book(booking).await?;
+    Ok(HttpResponse::Created()
+        .insert_header((
+            header::LOCATION,
+            format!("/tutors/{tutor_id}/bookings/{}", booking.booking_id),
+        ))
+        .json(booking))
+}
+
+pub async fn list_bookings_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    query: web::Query<ScheduleQuery>,
+) -> Result<web::Json<Vec<Booking>>, ApiError> {
+    let tutor_id = params.into_inner();
+    query.validate()?;
+    ensure_tutor_self(&caller, tutor_id)?;
+
+    let (from, to) = query.range();
+    Ok(web::Json(
+        app_state
+            .schedules
+            .list_bookings(tutor_id, from, to)
+            .await?,
     ))
 }
 
-pub async fn get_tutor_courses_handler(
+/// Admins and the booked tutor may cancel until the session starts; the
+/// student only until `CANCELLATION_NOTICE_HOURS` before.
+pub async fn cancel_booking_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<web::Json<Booking>, ApiError> {
+    let (tutor_id, booking_id) = params.into_inner();
+    let not_found = || ApiError::NotFound(format!("Tutor {tutor_id} has no booking {booking_id}"));
+
+    let booking = app_state
+        .schedules
+        .find_booking(booking_id)
+        .await?
+        .filter(|b| b.tutor_id == tutor_id)
+        .ok_or_else(not_found)?;
+    let is_tutor = caller.role == Role::Tutor && caller.id == booking.tutor_id;
+    let is_student = caller.role == Role::Student && caller.id == booking.student_id;
+    if !(caller.is_admin() || is_tutor || is_student) {
+        return Err(ApiError::Forbidden(format!(
+            "Account {} may not cancel booking {booking_id}",
+            caller.id
+        )));
+    }
+
+    if booking.status == BookingStatus::Cancelled {
+        return Err(ApiError::Conflict(format!(
+            "Booking {booking_id} is already cancelled"
+        )));
+    }
+    let now = Utc::now();
+    if booking.starts_at <= now {
+        return Err(ApiError::Conflict(format!(
+            "Booking {booking_id} has already started"
+        )));
+    }
+    if is_student && booking.starts_at - now < Duration::hours(CANCELLATION_NOTICE_HOURS) {
+        return Err(ApiError::Forbidden(format!(
+            "Students must cancel at least {CANCELLATION_NOTICE_HOURS} hours before the session; ask the tutor instead"
+        )));
+    }
+
+    let booking = app_state
+        .schedules
+        .cancel_booking(booking_id)
+        .await?
+        // Cancelled by someone else since it was read
+        .ok_or_else(|| ApiError::Conflict(format!("Booking {booking_id} is already cancelled")))?;
+    Ok(web::Json(booking))
+}
+
+/// The tutor's bookings, cancelled ones included so subscribed calendars
+/// drop them, course sessions and the days courses start, in the timezone
+/// of their weekly availability.
+pub async fn export_calendar_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let tutor_id = params.into_inner();
+    ensure_tutor_self(&caller, tutor_id)?;
+    let tutor = app_state
+        .tutors
+        .find(tutor_id)
+        .await?
+        .ok_or_else(|| tutor_not_found(tutor_id))?;
+
+    let timezone = timezone_of(&app_state.schedules.availability(tutor_id).await?);
+    let now = Utc::now();
+    let bookings = app_state
+        .schedules
+        .list_bookings(
+            tutor_id,
+            now - Duration::days(CALENDAR_PAST_DAYS),
+            now + Duration::days(CALENDAR_FUTURE_DAYS),
+        )
+        .await?;
+    let mut events: Vec<CalendarEvent> = bookings
+        .into_iter()
+        .map(|booking| {
+            let mut description = format!("Student {}", booking.student_id);
+            if !booking.note.is_empty() {
+                description = format!("{description}\n\n{}", booking.note);
+            }
+            CalendarEvent {
+                uid: format!("booking-{}@eazytutors", booking.booking_id),
+                time: EventTime::Timed(booking.starts_at, booking.ends_at),
+                summary: "Tutoring session".to_string(),
+                description,
+                cancelled: booking.status == BookingStatus::Cancelled,
+            }
+        })
+        .collect();
+    let courses = app_state.courses.list_for_tutor(tutor_id).await?;
+    let sessions = app_state
+        .courses
+        .list_tutor_sessions(
+            tutor_id,
+            now - Duration::days(CALENDAR_PAST_DAYS),
+            now + Duration::days(CALENDAR_FUTURE_DAYS),
+        )
+        .await?;
+    for session in sessions {
+        let Some(course) = courses.iter().find(|c| c.course_id == session.course_id) else {
+            continue;
+        };
+        let place = [session.location, session.meeting_url];
+        events.push(CalendarEvent {
+            uid: format!("session-{}@eazytutors", session.session_id),
+            time: EventTime::Timed(session.starts_at, session.ends_at),
+            summary: course.course_name.clone(),
+            description: place
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join("\n"),
+            cancelled: false,
+        });
+    }
+    // A course starts when it is posted
+    for course in courses {
+        let Some(posted_time) = course.posted_time else {
+            continue;
+        };
+        events.push(CalendarEvent {
+            uid: format!("course-{}@eazytutors", course.course_id),
+            time: EventTime::AllDay(
+                Utc.from_utc_datetime(&posted_time)
+                    .with_timezone(&timezone)
+                    .date_naive(),
+            ),
+            summary: format!("{} starts", course.course_name),
+            description: course.description,
+            cancelled: false,
+        });
+    }
+
+    let calendar = ical::render_calendar(
+        &format!("{} (eazytutors)", tutor.name),
+        timezone,
+        &events,
+        now,
+    );
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"calendar.ics\"",
+        ))
+        .body(calendar))
+}
+
+/// Blocks out the busy times of an uploaded `.ics` file. Events keep their
+/// UID, so importing an updated export replaces the earlier import and
+/// cancelled events free their time again; past events are ignored.
+pub async fn import_calendar_handler(
     app_state: web::Data<AppState>,
+    caller: Caller,
     params: web::Path<Uuid>,
-) -> impl Responder {
+    body: String,
+) -> Result<web::Json<CalendarImport>, ApiError> {
     let tutor_id = params.into_inner();
+    ensure_tutor_self(&caller, tutor_id)?;
+    ensure_tutor_exists(&app_state, tutor_id).await?;
 
-    let courses: Vec<Course> = {
-        let courses = app_state.courses.lock().unwrap();
-        courses
-            .iter()
-            .filter(|course| course.tutor_id == tutor_id)
-            .cloned()
-            .collect()
+    // Floating and all-day times are the tutor's local times
+    let timezone = timezone_of(&app_state.schedules.availability(tutor_id).await?);
+    let parsed = ical::parse_calendar(&body, timezone).map_err(ApiError::BadRequest)?;
+    if parsed.busy.len() + parsed.skipped.len() > MAX_IMPORTED_EVENTS {
+        return Err(ApiError::BadRequest(format!(
+            "A calendar may hold at most {MAX_IMPORTED_EVENTS} events"
+        )));
+    }
+
+    let now = Utc::now();
+    let mut exceptions: Vec<AvailabilityException> = Vec::new();
+    let mut released = Vec::new();
+    for busy in parsed.busy {
+        // Without a UID an event can only be recognised by its times
+        let uid = if busy.uid.is_empty() {
+            format!(
+                "{}/{}",
+                busy.starts_at.to_rfc3339(),
+                busy.ends_at.to_rfc3339()
+            )
+        } else {
+            busy.uid
+        };
+        if busy.cancelled {
+            released.push(uid);
+            continue;
+        }
+        if busy.ends_at <= now {
+            continue;
+        }
+        let exception = AvailabilityException {
+            exception_id: Uuid::new_v4(),
+            tutor_id,
+            kind: AvailabilityKind::Blocked,
+            starts_at: busy.starts_at,
+            ends_at: busy.ends_at,
+            reason: busy.summary.chars().take(MAX_NOTE_LEN).collect(),
+            ical_uid: Some(uid),
+        };
+        // The last copy of a repeated UID wins, as it would on re-import
+        match exceptions
+            .iter_mut()
+            .find(|e| e.ical_uid == exception.ical_uid)
+        {
+            Some(earlier) => *earlier = exception,
+            None => exceptions.push(exception),
+        }
+    }
+
+    let blocked = app_state
+        .schedules
+        .import_exceptions(tutor_id, exceptions, &released)
+        .await?;
+    Ok(web::Json(CalendarImport {
+        blocked,
+        released,
+        skipped: parsed.skipped,
+    }))
+}
+
+/// Schedules one session or a weekly series. Every session must be free of
+/// the tutor's other sessions and confirmed bookings, or none is saved.
+pub async fn schedule_sessions_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    new_session: web::Json<NewCourseSession>,
+) -> Result<HttpResponse, ApiError> {
+    let course_id = params.into_inner();
+    new_session.validate()?;
+    let course = find_owned_course(&app_state, course_id, &caller).await?;
+    let new_session = new_session.into_inner();
+
+    let starts_at = new_session.starts_at.expect("validated");
+    let ends_at = new_session.ends_at.expect("validated");
+    let ranges = match &new_session.repeat {
+        None => vec![TimeRange { starts_at, ends_at }],
+        Some(repeat) => {
+            let timezone = match &repeat.timezone {
+                Some(timezone) => timezone.parse().expect("validated"),
+                None => timezone_of(&app_state.schedules.availability(course.tutor_id).await?),
+            };
+            schedule::weekly_occurrences(timezone, starts_at, ends_at, repeat.weeks, &repeat.skip)
+                .map_err(|date| {
+                let mut errors = ValidationErrors::default();
+                errors.add("repeat.skip", format!("no session falls on {date}"));
+                ApiError::from(errors)
+            })?
+        }
     };
 
-    HttpResponse::Ok().json(courses)
+    let first = ranges
+        .iter()
+        .map(|r| r.starts_at)
+        .min()
+        .expect("at least one session");
+    let last = ranges
+        .iter()
+        .map(|r| r.ends_at)
+        .max()
+        .expect("at least one session");
+    let bookings = app_state
+        .schedules
+        .list_bookings(course.tutor_id, first, last)
+        .await?;
+    if let Some(booking) = bookings.iter().find(|b| {
+        b.status == BookingStatus::Confirmed
+            && ranges.iter().any(|r| b.overlaps(r.starts_at, r.ends_at))
+    }) {
+        return Err(ApiError::Conflict(format!(
+            "Tutor {} has a booking from {} to {}",
+            course.tutor_id, booking.starts_at, booking.ends_at
+        )));
+    }
+
+    let series_id = new_session.repeat.as_ref().map(|_| Uuid::new_v4());
+    let sessions = ranges
+        .into_iter()
+        .map(|range| CourseSession {
+            session_id: Uuid::new_v4(),
+            course_id,
+            tutor_id: course.tutor_id,
+            series_id,
+            starts_at: range.starts_at,
+            ends_at: range.ends_at,
+            location: new_session.location.clone(),
+            meeting_url: new_session.meeting_url.clone(),
+            capacity: new_session.capacity,
+        })
+        .collect();
+    let sessions = app_state.courses.schedule_sessions(sessions).await?;
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("/courses/{course_id}/sessions")))
+        .json(sessions))
 }
 
-pub async fn get_course_details(
+pub async fn list_course_sessions_handler(
     app_state: web::Data<AppState>,
     params: web::Path<Uuid>,
-) -> impl Responder {
+    query: web::Query<UpcomingQuery>,
+) -> Result<web::Json<Vec<CourseSession>>, ApiError> {
     let course_id = params.into_inner();
+    query.validate()?;
+
+    if app_state.courses.find(course_id).await?.is_none() {
+        return Err(course_not_found(course_id));
+    }
+    let from = query.from.unwrap_or_else(Utc::now);
+    Ok(web::Json(
+        app_state
+            .courses
+            .list_sessions(course_id, from, query.limit)
+            .await?,
+    ))
+}
+
+pub async fn delete_session_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, session_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+
+    if !app_state
+        .courses
+        .delete_session(course_id, session_id)
+        .await?
+    {
+        return Err(ApiError::NotFound(format!(
+            "Course {course_id} has no session {session_id}"
+        )));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Upcoming sessions of the courses the student holds a seat in.
+pub async fn list_student_sessions_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    query: web::Query<UpcomingQuery>,
+) -> Result<web::Json<Vec<CourseSession>>, ApiError> {
+    let student_id = params.into_inner();
+    query.validate()?;
+    ensure_student_self(&caller, student_id)?;
 
-    let course: Option<Course> = {
-        let courses = app_state.courses.lock().unwrap();
-        courses
-            .iter()
-            .find(|course| course.course_id == course_id)
-            .cloned()
+    if app_state.students.find(student_id).await?.is_none() {
+        return Err(student_not_found(student_id));
+    }
+    let from = query.from.unwrap_or_else(Utc::now);
+    Ok(web::Json(
+        app_state
+            .courses
+            .list_student_sessions(student_id, from, query.limit)
+            .await?,
+    ))
+}
+
+// Loads one of the course's sessions
+async fn find_course_session(
+    app_state: &AppState,
+    course_id: Uuid,
+    session_id: Uuid,
+) -> Result<CourseSession, ApiError> {
+    match app_state.courses.find_session(session_id).await? {
+        Some(session) if session.course_id == course_id => Ok(session),
+        _ => Err(ApiError::NotFound(format!(
+            "Course {course_id} has no session {session_id}"
+        ))),
+    }
+}
+
+// Six characters with the easily confused 0/O and 1/I left out
+fn new_check_in_code() -> String {
+    const ALPHABET: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+    Uuid::new_v4().as_bytes()[..6]
+        .iter()
+        .map(|b| ALPHABET[*b as usize % ALPHABET.len()] as char)
+        .collect()
+}
+
+/// Marks many students at once, overwriting earlier marks and check-ins.
+pub async fn mark_attendance_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+    marks: web::Json<AttendanceMarks>,
+) -> Result<web::Json<Vec<Attendance>>, ApiError> {
+    let (course_id, session_id) = params.into_inner();
+    marks.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+    let session = find_course_session(&app_state, course_id, session_id).await?;
+    let marks = marks.into_inner().marks;
+
+    let opens_at = session.starts_at - Duration::minutes(CHECK_IN_OPENS_MINUTES);
+    if Utc::now() < opens_at && marks.iter().any(|m| m.status != AttendanceStatus::Excused) {
+        return Err(ApiError::Conflict(format!(
+            "Attendance for session {session_id} can be taken from {opens_at}"
+        )));
+    }
+    let enrolled = app_state.courses.list_enrolled(course_id).await?;
+    let mut errors = ValidationErrors::default();
+    for mark in marks
+        .iter()
+        .filter(|m| !enrolled.iter().any(|e| e.student_id == m.student_id))
+    {
+        errors.add(
+            "marks",
+            format!(
+                "student {} is not enrolled in course {course_id}",
+                mark.student_id
+            ),
+        );
+    }
+    errors.into_result()?;
+
+    Ok(web::Json(
+        app_state.attendance.mark(session_id, marks).await?,
+    ))
+}
+
+pub async fn list_attendance_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<web::Json<Vec<Attendance>>, ApiError> {
+    let (course_id, session_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_course_session(&app_state, course_id, session_id).await?;
+
+    Ok(web::Json(app_state.attendance.list(session_id).await?))
+}
+
+/// Issues a fresh check-in code, replacing any earlier one. It expires after
+/// `CHECK_IN_CODE_MINUTES` or when the session ends, whichever is sooner.
+pub async fn open_check_in_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, session_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+    let session = find_course_session(&app_state, course_id, session_id).await?;
+
+    let now = Utc::now();
+    let opens_at = session.starts_at - Duration::minutes(CHECK_IN_OPENS_MINUTES);
+    if now < opens_at {
+        return Err(ApiError::Conflict(format!(
+            "Check-in for session {session_id} opens at {opens_at}"
+        )));
+    }
+    if now >= session.ends_at {
+        return Err(ApiError::Conflict(format!(
+            "Session {session_id} has ended"
+        )));
+    }
+    let code = CheckInCode {
+        session_id,
+        code: new_check_in_code(),
+        expires_at: (now + Duration::minutes(CHECK_IN_CODE_MINUTES)).min(session.ends_at),
     };
+    let code = app_state.attendance.open_check_in(code).await?;
+    Ok(HttpResponse::Created().json(code))
+}
+
+/// A student checking themselves in with the code the tutor shared. Late
+/// arrivals are marked late; a mark the tutor already gave is kept.
+pub async fn check_in_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+    check_in: web::Json<CheckIn>,
+) -> Result<web::Json<Attendance>, ApiError> {
+    let (course_id, session_id) = params.into_inner();
+    check_in.validate()?;
+    let CheckIn { student_id, code } = check_in.into_inner();
+    ensure_student_self(&caller, student_id)?;
+    let session = find_course_session(&app_state, course_id, session_id).await?;
 
-    match course {
-        Some(c) => HttpResponse::Ok().json(c),
-        None => HttpResponse::NotFound().body(format!("Course with ID {course_id} not found")),
+    let enrolled = app_state.courses.list_enrolled(course_id).await?;
+    if !enrolled.iter().any(|e| e.student_id == student_id) {
+        return Err(ApiError::Forbidden(format!(
+            "Student {student_id} is not enrolled in course {course_id}"
+        )));
     }
+    let status = if Utc::now() > session.starts_at + Duration::minutes(LATE_AFTER_MINUTES) {
+        AttendanceStatus::Late
+    } else {
+        AttendanceStatus::Present
+    };
+    let code = code.trim().to_uppercase();
+    app_state
+        .attendance
+        .check_in(session_id, student_id, &code, status)
+        .await?
+        .map(web::Json)
+        .ok_or_else(|| {
+            ApiError::Forbidden(format!(
+                "Check-in code for session {session_id} is wrong or has expired"
+            ))
+        })
+}
+
+/// Per-student tallies over the course's sessions.
+pub async fn course_attendance_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Vec<AttendanceTally>>, ApiError> {
+    let course_id = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+
+    Ok(web::Json(
+        app_state
+            .attendance
+            .course_summary(course_id, Utc::now())
+            .await?,
+    ))
+}
+
+/// Per-course tallies for the student.
+pub async fn student_attendance_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Vec<AttendanceTally>>, ApiError> {
+    let student_id = params.into_inner();
+    ensure_student_self(&caller, student_id)?;
+
+    if app_state.students.find(student_id).await?.is_none() {
+        return Err(student_not_found(student_id));
+    }
+    Ok(web::Json(
+        app_state
+            .attendance
+            .student_summary(student_id, Utc::now())
+            .await?,
+    ))
+}
+
+// Loads one of the course's modules
+async fn find_course_module(
+    app_state: &AppState,
+    course_id: Uuid,
+    module_id: Uuid,
+) -> Result<CourseModule, ApiError> {
+    app_state
+        .curriculum
+        .list_modules(course_id)
+        .await?
+        .into_iter()
+        .find(|m| m.module_id == module_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Course {course_id} has no module {module_id}")))
+}
+
+fn lesson_not_found(module_id: Uuid, lesson_id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Module {module_id} has no lesson {lesson_id}"))
+}
+
+// What the `.../order` endpoints answer when the ids are not a permutation
+fn reorder_mismatch(what: &str) -> ApiError {
+    let mut errors = ValidationErrors::default();
+    errors.add(
+        "ids",
+        format!("ids must list each of the {what} exactly once"),
+    );
+    ApiError::from(errors)
+}
+
+/// The course's modules and lessons in order. The course's tutor, and
+/// admins, also see draft lessons; everyone else only sees published lessons
+/// and the modules holding any.
+pub async fn get_curriculum_handler(
+    app_state: web::Data<AppState>,
+    caller: Option<Caller>,
+    params: web::Path<Uuid>,
+) -> Result<web::Json<Curriculum>, ApiError> {
+    let course_id = params.into_inner();
+    let course = app_state
+        .courses
+        .find(course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    let drafts = caller.is_some_and(|c| c.is_admin() || course.is_posted_by_tutor(c.id));
+    let modules = app_state.curriculum.list_modules(course_id).await?;
+    let lessons = app_state
+        .curriculum
+        .list_lessons(course_id, !drafts)
+        .await?;
+    let mut curriculum = Curriculum::new(course_id, modules, lessons);
+    if !drafts {
+        curriculum.modules.retain(|m| !m.lessons.is_empty());
+    }
+    Ok(web::Json(curriculum))
+}
+
+/// Adds a module after the course's others.
+pub async fn add_module_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    module: web::Json<ModuleTitle>,
+) -> Result<HttpResponse, ApiError> {
+    let course_id = params.into_inner();
+    module.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+
+    let module = CourseModule {
+        module_id: Uuid::new_v4(),
+        course_id,
+        title: module.into_inner().title,
+        position: 0,
+    };
+    let module = app_state.curriculum.add_module(module).await?;
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("/courses/{course_id}/curriculum")))
+        .json(module))
+}
+
+pub async fn rename_module_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+    module: web::Json<ModuleTitle>,
+) -> Result<web::Json<CourseModule>, ApiError> {
+    let (course_id, module_id) = params.into_inner();
+    module.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+
+    app_state
+        .curriculum
+        .rename_module(course_id, module_id, module.into_inner().title)
+        .await?
+        .map(web::Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Course {course_id} has no module {module_id}")))
+}
+
+/// Deletes the module along with its lessons.
+pub async fn delete_module_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, module_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+    let lesson_ids: Vec<Uuid> = app_state
+        .curriculum
+        .list_lessons(course_id, false)
+        .await?
+        .iter()
+        .filter(|l| l.module_id == module_id)
+        .map(|l| l.lesson_id)
+        .collect();
+    let blobs = attached_blobs(&app_state, &lesson_ids).await?;
+
+    if !app_state
+        .curriculum
+        .delete_module(course_id, module_id)
+        .await?
+    {
+        return Err(ApiError::NotFound(format!(
+            "Course {course_id} has no module {module_id}"
+        )));
+    }
+    release_blobs(&app_state, &blobs).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn reorder_modules_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<Uuid>,
+    order: web::Json<Reorder>,
+) -> Result<web::Json<Vec<CourseModule>>, ApiError> {
+    let course_id = params.into_inner();
+    order.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+
+    app_state
+        .curriculum
+        .reorder_modules(course_id, order.into_inner().ids)
+        .await?
+        .map(web::Json)
+        .ok_or_else(|| reorder_mismatch("course's modules"))
+}
+
+/// Adds a lesson after the module's others.
+pub async fn add_lesson_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+    lesson: web::Json<NewLesson>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, module_id) = params.into_inner();
+    lesson.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_course_module(&app_state, course_id, module_id).await?;
+
+    let NewLesson {
+        title,
+        body,
+        duration_minutes,
+        published,
+    } = lesson.into_inner();
+    let lesson = Lesson {
+        lesson_id: Uuid::new_v4(),
+        module_id,
+        title,
+        body,
+        duration_minutes,
+        position: 0,
+        published,
+        updated_at: Utc::now(),
+    };
+    let lesson = app_state.curriculum.add_lesson(lesson).await?;
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("/courses/{course_id}/curriculum")))
+        .json(lesson))
+}
+
+pub async fn update_lesson_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid, Uuid)>,
+    update: web::Json<LessonUpdate>,
+) -> Result<web::Json<Lesson>, ApiError> {
+    let (course_id, module_id, lesson_id) = params.into_inner();
+    let update = update.into_inner();
+    update.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_course_module(&app_state, course_id, module_id).await?;
+
+    let mut lesson = app_state
+        .curriculum
+        .list_lessons(course_id, false)
+        .await?
+        .into_iter()
+        .find(|l| l.lesson_id == lesson_id && l.module_id == module_id)
+        .ok_or_else(|| lesson_not_found(module_id, lesson_id))?;
+    if let Some(title) = update.title {
+        lesson.title = title;
+    }
+    if let Some(body) = update.body {
+        lesson.body = body;
+    }
+    if update.clear_duration {
+        lesson.duration_minutes = None;
+    } else if update.duration_minutes.is_some() {
+        lesson.duration_minutes = update.duration_minutes;
+    }
+    if let Some(published) = update.published {
+        lesson.published = published;
+    }
+    app_state
+        .curriculum
+        .update_lesson(lesson)
+        .await?
+        .map(web::Json)
+        .ok_or_else(|| lesson_not_found(module_id, lesson_id))
+}
+
+pub async fn delete_lesson_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, module_id, lesson_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_course_module(&app_state, course_id, module_id).await?;
+    let blobs = attached_blobs(&app_state, &[lesson_id]).await?;
+
+    if !app_state
+        .curriculum
+        .delete_lesson(module_id, lesson_id)
+        .await?
+    {
+        return Err(lesson_not_found(module_id, lesson_id));
+    }
+    release_blobs(&app_state, &blobs).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn reorder_lessons_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid)>,
+    order: web::Json<Reorder>,
+) -> Result<web::Json<Vec<Lesson>>, ApiError> {
+    let (course_id, module_id) = params.into_inner();
+    order.validate()?;
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_course_module(&app_state, course_id, module_id).await?;
+
+    app_state
+        .curriculum
+        .reorder_lessons(module_id, order.into_inner().ids)
+        .await?
+        .map(web::Json)
+        .ok_or_else(|| reorder_mismatch("module's lessons"))
+}
+
+// Loads one of the module's lessons, drafts included
+async fn find_module_lesson(
+    app_state: &AppState,
+    course_id: Uuid,
+    module_id: Uuid,
+    lesson_id: Uuid,
+) -> Result<Lesson, ApiError> {
+    find_course_module(app_state, course_id, module_id).await?;
+    app_state
+        .curriculum
+        .list_lessons(course_id, false)
+        .await?
+        .into_iter()
+        .find(|l| l.lesson_id == lesson_id && l.module_id == module_id)
+        .ok_or_else(|| lesson_not_found(module_id, lesson_id))
+}
+
+// Loads a lesson whose materials the caller may see: any lesson for the
+// course's tutor and admins, published ones for students enrolled in it
+async fn find_readable_lesson(
+    app_state: &AppState,
+    caller: &Caller,
+    course_id: Uuid,
+    module_id: Uuid,
+    lesson_id: Uuid,
+) -> Result<Lesson, ApiError> {
+    let course = app_state
+        .courses
+        .find(course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+    let lesson = find_module_lesson(app_state, course_id, module_id, lesson_id).await?;
+    if caller.is_admin() || course.is_posted_by_tutor(caller.id) {
+        return Ok(lesson);
+    }
+    if caller.role != Role::Student {
+        return Err(ApiError::Forbidden(format!(
+            "Course with ID {course_id} is not owned by tutor {}",
+            caller.id
+        )));
+    }
+    // Drafts stay hidden, as they are from the curriculum
+    if !lesson.published {
+        return Err(lesson_not_found(module_id, lesson_id));
+    }
+    let enrolled = app_state.courses.list_enrolled(course_id).await?;
+    if !enrolled.iter().any(|e| e.student_id == caller.id) {
+        return Err(ApiError::Forbidden(format!(
+            "Student {} is not enrolled in course {course_id}",
+            caller.id
+        )));
+    }
+    Ok(lesson)
+}
+
+fn material_not_found(lesson_id: Uuid, material_id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Lesson {lesson_id} has no material {material_id}"))
+}
+
+fn invalid_file(message: String) -> ApiError {
+    let mut errors = ValidationErrors::default();
+    errors.add("file", message);
+    ApiError::from(errors)
+}
+
+fn multipart_error(e: MultipartError) -> ApiError {
+    match e {
+        MultipartError::ContentTypeMissing
+        | MultipartError::ContentTypeParse
+        | MultipartError::ContentTypeIncompatible => ApiError::UnsupportedMediaType(
+            "Uploads must be sent as multipart/form-data".to_string(),
+        ),
+        e => ApiError::BadRequest(format!("Malformed multipart body: {e}")),
+    }
+}
+
+// Removes the blob unless another lesson still has the same content
+async fn release_blob(app_state: &AppState, sha256: &str) -> Result<(), ApiError> {
+    let _lock = app_state.blob_locks.lock(sha256).await;
+    remove_unused_blob(app_state, sha256).await
+}
+
+// As `release_blob`, for callers already holding the blob's lock
+async fn remove_unused_blob(app_state: &AppState, sha256: &str) -> Result<(), ApiError> {
+    if !app_state.materials.content_in_use(sha256).await? {
+        app_state.blobs.delete(sha256).await?;
+    }
+    Ok(())
+}
+
+// The contents attached to the lessons, listed before deleting them as the
+// materials go with them
+async fn attached_blobs(
+    app_state: &AppState,
+    lesson_ids: &[Uuid],
+) -> Result<Vec<String>, ApiError> {
+    let mut hashes: Vec<String> = app_state
+        .materials
+        .list_for_lessons(lesson_ids)
+        .await?
+        .into_iter()
+        .map(|m| m.sha256)
+        .collect();
+    hashes.sort();
+    hashes.dedup();
+    Ok(hashes)
+}
+
+async fn release_blobs(app_state: &AppState, hashes: &[String]) -> Result<(), ApiError> {
+    for sha256 in hashes {
+        release_blob(app_state, sha256).await?;
+    }
+    Ok(())
+}
+
+/// Attaches a file sent as the `file` field of a multipart form. The bytes
+/// are stored once per content; uploading a file the lesson already has
+/// returns the existing material.
+pub async fn upload_material_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid, Uuid)>,
+    mut form: Multipart,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, module_id, lesson_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_module_lesson(&app_state, course_id, module_id, lesson_id).await?;
+
+    let field = form
+        .try_next()
+        .await
+        .map_err(multipart_error)?
+        .ok_or_else(|| invalid_file("file is required".to_string()))?;
+    if field.name() != Some("file") {
+        return Err(invalid_file("file must be the only form field".to_string()));
+    }
+    let filename = field
+        .content_disposition()
+        .and_then(|cd| cd.get_filename())
+        .and_then(clean_filename)
+        .ok_or_else(|| invalid_file("file must have a file name".to_string()))?;
+    let content_type = field
+        .content_type()
+        .map(|mime| mime.essence_str().to_string())
+        .unwrap_or_default();
+    if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Err(ApiError::UnsupportedMediaType(format!(
+            "Materials may be one of: {}",
+            ALLOWED_CONTENT_TYPES.join(", ")
+        )));
+    }
+
+    // Counted as it arrives, so an oversized file is cut off rather than
+    // read to the end
+    let limit = app_state.max_upload_bytes;
+    let mut received = 0;
+    let data = field.map(move |chunk| {
+        let chunk = chunk.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        received += chunk.len() as u64;
+        if received > limit {
+            return Err(io::Error::new(
+                io::ErrorKind::FileTooLarge,
+                "upload over the size limit",
+            ));
+        }
+        Ok(chunk)
+    });
+    let staged = app_state
+        .blobs
+        .stage(Box::pin(data))
+        .await
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::FileTooLarge => {
+                ApiError::PayloadTooLarge(format!("Materials may be at most {limit} bytes"))
+            }
+            io::ErrorKind::InvalidData => {
+                ApiError::BadRequest(format!("Malformed multipart body: {e}"))
+            }
+            _ => ApiError::Storage(e),
+        })?;
+
+    // Read before the blob is committed, so a malformed form leaves nothing
+    let extra_field = match form.try_next().await.map_err(multipart_error) {
+        Ok(None) => None,
+        Ok(Some(_)) => Some(invalid_file("file must be the only form field".to_string())),
+        Err(e) => Some(e),
+    };
+    if let Some(rejection) = extra_field {
+        app_state.blobs.discard(staged).await?;
+        return Err(rejection);
+    }
+
+    // Held until the material is attached, so the blob is not released
+    // in between by a lesson dropping the same content
+    let _lock = app_state.blob_locks.lock(&staged.sha256).await;
+    let stored = app_state.blobs.commit(staged).await?;
+    let material = Material {
+        material_id: Uuid::new_v4(),
+        lesson_id,
+        filename,
+        content_type,
+        size_bytes: stored.size as i64,
+        sha256: stored.sha256.clone(),
+        uploaded_at: Utc::now(),
+    };
+    let outcome = match attach_committed(&app_state, material).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            remove_unused_blob(&app_state, &stored.sha256).await?;
+            return Err(e);
+        }
+    };
+    match outcome {
+        AttachOutcome::Attached(material) => Ok(HttpResponse::Created()
+            .insert_header((
+                header::LOCATION,
+                format!(
+                    "/courses/{course_id}/modules/{module_id}/lessons/{lesson_id}/materials/{}",
+                    material.material_id
+                ),
+            ))
+            .json(material)),
+        AttachOutcome::AlreadyAttached(material) => Ok(HttpResponse::Ok().json(material)),
+    }
+}
+
+// Checks a committed blob is the type the upload claimed, then attaches it
+async fn attach_committed(
+    app_state: &AppState,
+    material: Material,
+) -> Result<AttachOutcome, ApiError> {
+    let sniffed = (material.size_bytes as u64).min(SNIFF_LEN as u64);
+    let head = app_state.blobs.read(&material.sha256, 0..sniffed).await?;
+    let head: Vec<u8> = head.map_ok(|chunk| chunk.to_vec()).try_concat().await?;
+    if !matches_content_type(&material.content_type, &head) {
+        return Err(ApiError::UnsupportedMediaType(format!(
+            "{} does not look like {}",
+            material.filename, material.content_type
+        )));
+    }
+    Ok(app_state.materials.attach(material).await?)
+}
+
+pub async fn list_materials_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid, Uuid)>,
+) -> Result<web::Json<Vec<Material>>, ApiError> {
+    let (course_id, module_id, lesson_id) = params.into_inner();
+    find_readable_lesson(&app_state, &caller, course_id, module_id, lesson_id).await?;
+
+    Ok(web::Json(app_state.materials.list(lesson_id).await?))
+}
+
+/// Streams the file, or the single byte range asked for. Requests for
+/// several ranges get the whole file.
+pub async fn download_material_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid, Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, module_id, lesson_id, material_id) = params.into_inner();
+    find_readable_lesson(&app_state, &caller, course_id, module_id, lesson_id).await?;
+    let material = app_state
+        .materials
+        .find(lesson_id, material_id)
+        .await?
+        .ok_or_else(|| material_not_found(lesson_id, material_id))?;
+
+    let size = material.size_bytes as u64;
+    // A Range header that does not parse is ignored, as RFC 9110 allows
+    let requested = req
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| header::Range::from_str(value).ok());
+    let (mut response, range) = match requested {
+        Some(header::Range::Bytes(specs)) if specs.len() == 1 => {
+            let Some((first, last)) = specs[0].to_satisfiable_range(size) else {
+                return Ok(HttpResponse::RangeNotSatisfiable()
+                    .insert_header(header::ContentRange(header::ContentRangeSpec::Bytes {
+                        range: None,
+                        instance_length: Some(size),
+                    }))
+                    .finish());
+            };
+            let mut response = HttpResponse::PartialContent();
+            response.insert_header(header::ContentRange(header::ContentRangeSpec::Bytes {
+                range: Some((first, last)),
+                instance_length: Some(size),
+            }));
+            (response, first..last + 1)
+        }
+        _ => (HttpResponse::Ok(), 0..size),
+    };
+    let len = range.end - range.start;
+    let body = app_state.blobs.read(&material.sha256, range).await?;
+    Ok(response
+        .insert_header((header::CONTENT_TYPE, material.content_type))
+        .insert_header(header::ContentDisposition::attachment(material.filename))
+        .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header(header::ETag(header::EntityTag::new_strong(material.sha256)))
+        .no_chunking(len)
+        .streaming(body))
+}
+
+/// Detaches the material, removing its bytes once no lesson has them.
+pub async fn delete_material_handler(
+    app_state: web::Data<AppState>,
+    caller: Caller,
+    params: web::Path<(Uuid, Uuid, Uuid, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let (course_id, module_id, lesson_id, material_id) = params.into_inner();
+    find_owned_course(&app_state, course_id, &caller).await?;
+    find_module_lesson(&app_state, course_id, module_id, lesson_id).await?;
+
+    let material = app_state
+        .materials
+        .detach(lesson_id, material_id)
+        .await?
+        .ok_or_else(|| material_not_found(lesson_id, material_id))?;
+    release_blob(&app_state, &material.sha256).await?;
+    Ok(HttpResponse::NoContent().finish())
 }
@@ -0,0 +1,700 @@
+//! Just enough RFC 5545 to publish a tutor's schedule as an iCalendar feed
+//! and to read busy times back out of calendars exported by other apps.
+
+use crate::models::SkippedEvent;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+/// Identifies this service as the producer of exported calendars.
+const PRODID: &str = "-//eazytutors//Tutor Schedule//EN";
+/// RFC 5545 §3.1: lines longer than this many octets are folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// When an exported event happens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventTime {
+    /// `[start, end)` in UTC, written in the calendar's timezone.
+    Timed(DateTime<Utc>, DateTime<Utc>),
+    /// A whole day in the calendar's timezone.
+    AllDay(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    /// Stable across exports, so calendar apps update events in place.
+    pub uid: String,
+    pub time: EventTime,
+    pub summary: String,
+    pub description: String,
+    pub cancelled: bool,
+}
+
+/// A busy period read from an imported calendar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusyTime {
+    pub uid: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub summary: String,
+    /// `STATUS:CANCELLED`: the time is free again.
+    pub cancelled: bool,
+}
+
+/// What an imported calendar contained.
+#[derive(Debug, Default)]
+pub struct ParsedCalendar {
+    pub busy: Vec<BusyTime>,
+    pub skipped: Vec<SkippedEvent>,
+}
+
+/// Escapes TEXT values (RFC 5545 §3.3.11).
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Appends `line` folded to `MAX_LINE_OCTETS`, never splitting a UTF-8
+/// character, and terminated by CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut rest = line;
+    let mut limit = MAX_LINE_OCTETS;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        out.push_str(&rest[..split]);
+        out.push_str("\r\n ");
+        rest = &rest[split..];
+        // Continuation lines start with the folding space
+        limit = MAX_LINE_OCTETS - 1;
+    }
+    out.push_str(rest);
+    out.push_str("\r\n");
+}
+
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    format!("{sign}{:02}{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+fn format_local(local: NaiveDateTime) -> String {
+    local.format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn format_utc(instant: DateTime<Utc>) -> String {
+    instant.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// One STANDARD or DAYLIGHT observance starting at `at`
+fn push_observance(out: &mut String, tz: Tz, at: DateTime<Utc>, offset_from: i32) {
+    let offset = tz.offset_from_utc_datetime(&at.naive_utc());
+    let offset_to = offset.fix().local_minus_utc();
+    let kind = if offset.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    push_line(out, &format!("BEGIN:{kind}"));
+    // Onsets are local times in the offset being left
+    let onset = at.naive_utc() + Duration::seconds(offset_from as i64);
+    push_line(out, &format!("DTSTART:{}", format_local(onset)));
+    push_line(out, &format!("TZOFFSETFROM:{}", format_offset(offset_from)));
+    push_line(out, &format!("TZOFFSETTO:{}", format_offset(offset_to)));
+    if let Some(name) = offset.abbreviation() {
+        push_line(out, &format!("TZNAME:{}", escape_text(name)));
+    }
+    push_line(out, &format!("END:{kind}"));
+}
+
+fn utc_offset(tz: Tz, at: DateTime<Utc>) -> i32 {
+    tz.offset_from_utc_datetime(&at.naive_utc())
+        .fix()
+        .local_minus_utc()
+}
+
+/// A VTIMEZONE for `tz` with every offset change from the start of
+/// `first_year` to the end of `last_year`, found by scanning day by day and
+/// narrowing each change down to the second.
+fn push_vtimezone(out: &mut String, tz: Tz, first_year: i32, last_year: i32) {
+    let start = Utc.with_ymd_and_hms(first_year, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(last_year + 1, 1, 1, 0, 0, 0).unwrap();
+
+    push_line(out, "BEGIN:VTIMEZONE");
+    push_line(out, &format!("TZID:{}", tz.name()));
+    // Covers the span before the first change in range
+    let mut offset = utc_offset(tz, start);
+    push_observance(out, tz, start, offset);
+
+    let mut day = start;
+    while day < end {
+        let next = day + Duration::days(1);
+        if utc_offset(tz, next) != offset {
+            let (mut before, mut after) = (day, next);
+            while after - before > Duration::seconds(1) {
+                let middle = before + (after - before) / 2;
+                if utc_offset(tz, middle) == offset {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            push_observance(out, tz, after, offset);
+            offset = utc_offset(tz, after);
+        }
+        day = next;
+    }
+    push_line(out, "END:VTIMEZONE");
+}
+
+/// `events` as a VCALENDAR whose times are written in `tz`, with the
+/// VTIMEZONE describing it. UTC calendars use `Z` times instead.
+pub fn render_calendar(name: &str, tz: Tz, events: &[CalendarEvent], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{PRODID}"));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    push_line(&mut out, &format!("X-WR-TIMEZONE:{}", tz.name()));
+
+    let is_utc = tz == Tz::UTC;
+    if !is_utc {
+        let years = events.iter().flat_map(|event| match event.time {
+            EventTime::Timed(start, end) => vec![
+                start.with_timezone(&tz).year(),
+                end.with_timezone(&tz).year(),
+            ],
+            EventTime::AllDay(day) => vec![day.year()],
+        });
+        let (first, last) = years.fold((now.year(), now.year()), |(first, last), year| {
+            (first.min(year), last.max(year))
+        });
+        push_vtimezone(&mut out, tz, first, last);
+    }
+
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", escape_text(&event.uid)));
+        push_line(&mut out, &format!("DTSTAMP:{}", format_utc(now)));
+        match event.time {
+            EventTime::Timed(start, end) if is_utc => {
+                push_line(&mut out, &format!("DTSTART:{}", format_utc(start)));
+                push_line(&mut out, &format!("DTEND:{}", format_utc(end)));
+            }
+            EventTime::Timed(start, end) => {
+                let local =
+                    |instant: DateTime<Utc>| format_local(instant.with_timezone(&tz).naive_local());
+                push_line(
+                    &mut out,
+                    &format!("DTSTART;TZID={}:{}", tz.name(), local(start)),
+                );
+                push_line(
+                    &mut out,
+                    &format!("DTEND;TZID={}:{}", tz.name(), local(end)),
+                );
+            }
+            EventTime::AllDay(day) => {
+                push_line(
+                    &mut out,
+                    &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")),
+                );
+                let next = day.succ_opt().expect("dates stay far from the end of time");
+                push_line(
+                    &mut out,
+                    &format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+                );
+                push_line(&mut out, "TRANSP:TRANSPARENT");
+            }
+        }
+        push_line(
+            &mut out,
+            &format!("SUMMARY:{}", escape_text(&event.summary)),
+        );
+        if !event.description.is_empty() {
+            push_line(
+                &mut out,
+                &format!("DESCRIPTION:{}", escape_text(&event.description)),
+            );
+        }
+        let status = if event.cancelled {
+            "CANCELLED"
+        } else {
+            "CONFIRMED"
+        };
+        push_line(&mut out, &format!("STATUS:{status}"));
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// One unfolded content line: `NAME;PARAM=VALUE:value`.
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn parse(line: &str) -> Option<ContentLine> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.trim().to_ascii_uppercase(),
+                    value.trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+        Some(ContentLine {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Undoes line folding: a line starting with a space or tab continues the
+/// one before it.
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// When a DTSTART or DTEND says: an instant, or a date for all-day events.
+enum Moment {
+    Instant(DateTime<Utc>),
+    Date(NaiveDate),
+}
+
+fn local_to_utc(tz: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            let later = local.checked_add_signed(Duration::hours(1))?;
+            tz.from_local_datetime(&later).earliest()
+        })
+        .map(|instant| instant.with_timezone(&Utc))
+}
+
+/// Floating times, without `Z` or `TZID`, are read in `default_tz`.
+fn parse_moment(line: &ContentLine, default_tz: Tz) -> Result<Moment, String> {
+    let value = line.value.trim();
+    if line.param("VALUE") == Some("DATE") || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(Moment::Date)
+            .map_err(|_| format!("{} `{value}` is not a date", line.name));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map(|naive| Moment::Instant(naive.and_utc()))
+            .map_err(|_| format!("{} `{value}` is not a date-time", line.name));
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map_err(|_| format!("{} `{value}` is not a date-time", line.name))?;
+    let tz = match line.param("TZID") {
+        Some(name) => name
+            .parse::<Tz>()
+            .map_err(|_| format!("timezone `{name}` is not an IANA timezone"))?,
+        None => default_tz,
+    };
+    local_to_utc(tz, local)
+        .map(Moment::Instant)
+        .ok_or_else(|| format!("{} `{value}` does not exist in {}", line.name, tz.name()))
+}
+
+/// `P1DT2H30M`, `PT45M`, `P2W` and the like (RFC 5545 §3.3.6). `None` for
+/// durations too long for chrono, as well as malformed ones.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let part = match (unit, in_time) {
+                    ('W', false) => Duration::try_weeks(n),
+                    ('D', false) => Duration::try_days(n),
+                    ('H', true) => Duration::try_hours(n),
+                    ('M', true) => Duration::try_minutes(n),
+                    ('S', true) => Duration::try_seconds(n),
+                    _ => None,
+                };
+                total = total.checked_add(&part?)?;
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Every VEVENT that makes its owner busy. Floating and all-day times are
+/// read in `default_tz`. Transparent (free) events are ignored; `RRULE`s
+/// are not expanded, so recurring events count only their first occurrence.
+pub fn parse_calendar(ics: &str, default_tz: Tz) -> Result<ParsedCalendar, String> {
+    let lines = unfold(ics);
+    if !lines
+        .first()
+        .is_some_and(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err("calendar must start with BEGIN:VCALENDAR".to_string());
+    }
+
+    let mut parsed = ParsedCalendar::default();
+    let mut event: Option<Vec<ContentLine>> = None;
+    for line in &lines {
+        let Some(line) = ContentLine::parse(line) else {
+            continue;
+        };
+        match (line.name.as_str(), line.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => event = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(properties) = event.take() {
+                    match busy_time(&properties, default_tz) {
+                        Ok(Some(busy)) => parsed.busy.push(busy),
+                        Ok(None) => {}
+                        Err(reason) => parsed.skipped.push(SkippedEvent {
+                            uid: property(&properties, "UID")
+                                .map(unescape_text)
+                                .unwrap_or_default(),
+                            reason,
+                        }),
+                    }
+                }
+            }
+            _ => {
+                if let Some(properties) = event.as_mut() {
+                    properties.push(line);
+                }
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn property<'a>(properties: &'a [ContentLine], name: &str) -> Option<&'a str> {
+    properties
+        .iter()
+        .find(|p| p.name == name)
+        .map(|p| p.value.as_str())
+}
+
+// `Ok(None)` for events that leave their owner free
+fn busy_time(properties: &[ContentLine], default_tz: Tz) -> Result<Option<BusyTime>, String> {
+    if property(properties, "TRANSP").is_some_and(|t| t.eq_ignore_ascii_case("TRANSPARENT")) {
+        return Ok(None);
+    }
+    let start = properties
+        .iter()
+        .find(|p| p.name == "DTSTART")
+        .ok_or("event has no DTSTART")?;
+    let start = parse_moment(start, default_tz)?;
+    let end = match properties.iter().find(|p| p.name == "DTEND") {
+        Some(end) => Some(parse_moment(end, default_tz)?),
+        None => None,
+    };
+    let duration = match property(properties, "DURATION") {
+        Some(value) => Some(parse_duration(value).ok_or(format!("DURATION `{value}` is invalid"))?),
+        None => None,
+    };
+
+    let midnight = |day: NaiveDate| {
+        local_to_utc(default_tz, day.and_time(chrono::NaiveTime::MIN))
+            .ok_or_else(|| format!("{day} has no midnight in {}", default_tz.name()))
+    };
+    let end_after = |starts_at: DateTime<Utc>, duration: Duration| {
+        starts_at
+            .checked_add_signed(duration)
+            .ok_or_else(|| format!("event ends after {}", DateTime::<Utc>::MAX_UTC))
+    };
+    let (starts_at, ends_at) = match (start, end) {
+        (Moment::Instant(start), Some(Moment::Instant(end))) => (start, end),
+        (Moment::Date(start), Some(Moment::Date(end))) => (midnight(start)?, midnight(end)?),
+        (Moment::Instant(start), None) => (
+            start,
+            end_after(start, duration.unwrap_or_else(Duration::zero))?,
+        ),
+        // All-day events last a day unless they say otherwise
+        (Moment::Date(start), None) => {
+            let starts_at = midnight(start)?;
+            (
+                starts_at,
+                end_after(starts_at, duration.unwrap_or_else(|| Duration::days(1)))?,
+            )
+        }
+        _ => return Err("DTSTART and DTEND mix dates and date-times".to_string()),
+    };
+    if ends_at <= starts_at {
+        // Zero-length events (reminders) block nothing
+        return Ok(None);
+    }
+
+    let mut uid = property(properties, "UID")
+        .map(unescape_text)
+        .unwrap_or_default();
+    // Changed occurrences of a recurring event share its UID
+    if let Some(recurrence_id) = property(properties, "RECURRENCE-ID") {
+        uid = format!("{uid}#{recurrence_id}");
+    }
+    Ok(Some(BusyTime {
+        uid,
+        starts_at,
+        ends_at,
+        summary: property(properties, "SUMMARY")
+            .map(unescape_text)
+            .unwrap_or_default(),
+        cancelled: property(properties, "STATUS")
+            .is_some_and(|s| s.eq_ignore_ascii_case("CANCELLED")),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        text.parse().unwrap()
+    }
+
+    fn event(uid: &str, time: EventTime) -> CalendarEvent {
+        CalendarEvent {
+            uid: uid.to_string(),
+            time,
+            summary: "Tutoring; algebra, part 1".to_string(),
+            description: "Bring\nnotes \\ questions".to_string(),
+            cancelled: false,
+        }
+    }
+
+    #[test]
+    fn rendered_calendars_round_trip() {
+        let london: Tz = "Europe/London".parse().unwrap();
+        let events = vec![
+            // Either side of the clocks changing
+            event(
+                "winter",
+                EventTime::Timed(utc("2025-03-24T09:00:00Z"), utc("2025-03-24T10:00:00Z")),
+            ),
+            event(
+                "summer",
+                EventTime::Timed(utc("2025-03-31T08:00:00Z"), utc("2025-03-31T09:30:00Z")),
+            ),
+            CalendarEvent {
+                cancelled: true,
+                ..event(
+                    "gone",
+                    EventTime::Timed(utc("2025-04-01T08:00:00Z"), utc("2025-04-01T09:00:00Z")),
+                )
+            },
+            event(
+                "course",
+                EventTime::AllDay(NaiveDate::from_ymd_opt(2025, 4, 7).unwrap()),
+            ),
+        ];
+        let ics = render_calendar(
+            "Ada's schedule",
+            london,
+            &events,
+            utc("2025-03-01T00:00:00Z"),
+        );
+
+        assert!(ics.lines().all(|line| line.len() <= MAX_LINE_OCTETS + 1));
+        assert!(ics.contains("DTSTART;TZID=Europe/London:20250331T090000\r\n"));
+        assert!(ics.contains("TZOFFSETTO:+0100\r\n"));
+        assert!(ics.contains("TZNAME:BST\r\n"));
+
+        let parsed = parse_calendar(&ics, Tz::UTC).expect("Failed to parse");
+        assert!(parsed.skipped.is_empty());
+        // The all-day course start is transparent and blocks nothing
+        let busy: Vec<(&str, DateTime<Utc>, DateTime<Utc>, bool)> = parsed
+            .busy
+            .iter()
+            .map(|b| (b.uid.as_str(), b.starts_at, b.ends_at, b.cancelled))
+            .collect();
+        assert_eq!(
+            busy,
+            vec![
+                (
+                    "winter",
+                    utc("2025-03-24T09:00:00Z"),
+                    utc("2025-03-24T10:00:00Z"),
+                    false
+                ),
+                (
+                    "summer",
+                    utc("2025-03-31T08:00:00Z"),
+                    utc("2025-03-31T09:30:00Z"),
+                    false
+                ),
+                (
+                    "gone",
+                    utc("2025-04-01T08:00:00Z"),
+                    utc("2025-04-01T09:00:00Z"),
+                    true
+                ),
+            ]
+        );
+        assert_eq!(parsed.busy[0].summary, "Tutoring; algebra, part 1");
+    }
+
+    #[test]
+    fn long_lines_fold_on_character_boundaries() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        push_line(&mut out, &line);
+        assert!(out.split("\r\n").all(|part| part.len() <= MAX_LINE_OCTETS));
+        assert_eq!(unfold(&out), vec![line]);
+    }
+
+    #[test]
+    fn foreign_calendars_parse() {
+        let ics = "BEGIN:VCALENDAR\nVERSION:2.0\n\
+            BEGIN:VEVENT\nUID:utc\nDTSTART:20250602T090000Z\nDURATION:PT1H30M\nEND:VEVENT\n\
+            BEGIN:VEVENT\nUID:floating\nDTSTART:20250603T090000\nDTEND:20250603T100000\nEND:VEVENT\n\
+            BEGIN:VEVENT\nUID:ny\nDTSTART;TZID=\"America/New_York\":20250604T090000\n\
+            DTEND;TZID=America/New_York:20250604T100000\nEND:VEVENT\n\
+            BEGIN:VEVENT\nUID:holiday\nDTSTART;VALUE=DATE:20250605\nEND:VEVENT\n\
+            BEGIN:VEVENT\nUID:free\nDTSTART:20250606T090000Z\nDTEND:20250606T100000Z\nTRANSP:TRANSPARENT\nEND:VEVENT\n\
+            BEGIN:VEVENT\nUID:windows\nDTSTART;TZID=Eastern Standard Time:20250607T090000\nEND:VEVENT\n\
+            END:VCALENDAR\n";
+        let paris: Tz = "Europe/Paris".parse().unwrap();
+        let parsed = parse_calendar(ics, paris).expect("Failed to parse");
+
+        let busy: Vec<(&str, DateTime<Utc>, DateTime<Utc>)> = parsed
+            .busy
+            .iter()
+            .map(|b| (b.uid.as_str(), b.starts_at, b.ends_at))
+            .collect();
+        assert_eq!(
+            busy,
+            vec![
+                (
+                    "utc",
+                    utc("2025-06-02T09:00:00Z"),
+                    utc("2025-06-02T10:30:00Z")
+                ),
+                (
+                    "floating",
+                    utc("2025-06-03T07:00:00Z"),
+                    utc("2025-06-03T08:00:00Z")
+                ),
+                (
+                    "ny",
+                    utc("2025-06-04T13:00:00Z"),
+                    utc("2025-06-04T14:00:00Z")
+                ),
+                (
+                    "holiday",
+                    utc("2025-06-04T22:00:00Z"),
+                    utc("2025-06-05T22:00:00Z")
+                ),
+            ]
+        );
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!(parsed.skipped[0].uid, "windows");
+
+        assert!(parse_calendar("not a calendar", paris).is_err());
+    }
+
+    #[test]
+    fn durations_parse() {
+        assert_eq!(parse_duration("PT45M"), Some(Duration::minutes(45)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("P2W"), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("-PT5M"), Some(Duration::minutes(-5)));
+        assert_eq!(parse_duration("PT5"), None);
+        assert_eq!(parse_duration("1H"), None);
+    }
+
+    #[test]
+    fn oversized_durations_are_rejected() {
+        assert_eq!(parse_duration("P99999999999999W"), None);
+        assert_eq!(parse_duration("PT9223372036854775807S"), None);
+        assert_eq!(parse_duration("P99999999999999999999D"), None);
+        // Each part fits, their sum does not
+        assert_eq!(parse_duration("P100000000000DT9000000000000000S"), None);
+        // Fits a duration, but not when added to any date
+        assert_eq!(
+            parse_duration("P1000000000W"),
+            Some(Duration::weeks(1_000_000_000))
+        );
+
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:far\r\nDTSTART:20250101T090000Z\r\n\
+                   DURATION:P15000000000000D\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:late\r\n\
+                   DTSTART;VALUE=DATE:20250101\r\nDURATION:P1000000000W\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let parsed = parse_calendar(ics, Tz::UTC).unwrap();
+        assert!(parsed.busy.is_empty());
+        let skipped: Vec<&str> = parsed.skipped.iter().map(|s| s.uid.as_str()).collect();
+        assert_eq!(skipped, ["far", "late"]);
+    }
+}
@@ -1,52 +1,198 @@
-use sqlx::Postgres;
-use sqlx::Pool;
-use sqlx::postgres::{PgPool,PgPoolOptions};
 use actix_web::{App, HttpServer, dev::Server, web};
+use dotenvy::dotenv;
+use sqlx::Pool;
+use sqlx::Postgres;
+use sqlx::postgres::{PgPool, PgPoolOptions};
 use std::io;
 use std::net::TcpListener;
-use dotenvy::dotenv;
-use std::sync::Mutex;
+use std::path::PathBuf;
+use std::sync::Arc;
+#[path = "auth.rs"]
+mod auth;
+#[path = "blob.rs"]
+mod blob;
+#[path = "errors.rs"]
+mod errors;
 #[path = "handlers.rs"]
 mod handlers;
+#[path = "ical.rs"]
+mod ical;
 #[path = "models.rs"]
 mod models;
 #[path = "routes.rs"]
 mod routes;
+#[path = "schedule.rs"]
+mod schedule;
+#[path = "search.rs"]
+mod search;
 #[path = "state.rs"]
 mod state;
+#[path = "store/mod.rs"]
+mod store;
+#[path = "upload.rs"]
+mod upload;
+#[path = "validation.rs"]
+mod validation;
 
-use routes::{course_routes, general_routes};
+use blob::LocalBlobStore;
+pub use errors::ApiError;
+use routes::{auth_routes, course_routes, general_routes, student_routes};
 use state::AppState;
+pub use validation::{FieldError, ValidationErrors};
+
+/// Which store implementation backs the HTTP API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    #[default]
+    InMemory,
+    Postgres,
+}
+
+impl StorageBackend {
+    /// Reads `STORAGE_BACKEND` (`memory` or `postgres`), defaulting to in-memory.
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        match std::env::var("STORAGE_BACKEND").as_deref() {
+            Ok("postgres") => StorageBackend::Postgres,
+            _ => StorageBackend::InMemory,
+        }
+    }
+}
+
+/// An admin account created at startup. Signing up never makes anyone an
+/// admin, as emails are not verified; further admins are promoted with
+/// `tutordb tutor role` or `PUT /tutors/{id}/role`.
+#[derive(Debug, Clone)]
+pub struct AdminSeed {
+    pub name: String,
+    pub email: String,
+    pub password: String,
+}
+
+/// Startup options for [`run_with`]. The pool is only required by the
+/// Postgres backend, so `Config::default()` runs without any database.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub backend: StorageBackend,
+    pub db_pool: Option<PgPool>,
+    /// Created before the server accepts requests, unless it exists.
+    pub admin: Option<AdminSeed>,
+    /// Where uploaded materials are kept; a directory under the system temp
+    /// directory when unset.
+    pub upload_dir: Option<PathBuf>,
+    /// Largest material accepted, in bytes.
+    pub max_upload_bytes: Option<u64>,
+}
+
+impl Config {
+    pub fn in_memory() -> Self {
+        Config::default()
+    }
+
+    pub fn postgres(db_pool: PgPool) -> Self {
+        Config {
+            backend: StorageBackend::Postgres,
+            db_pool: Some(db_pool),
+            ..Config::default()
+        }
+    }
+
+    /// `ADMIN_EMAIL` and `ADMIN_PASSWORD`, if both are set and non-empty,
+    /// with `ADMIN_NAME` or "Admin" as the name.
+    pub fn admin_from_env() -> Option<AdminSeed> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Some(AdminSeed {
+            name: var("ADMIN_NAME").unwrap_or_else(|| "Admin".to_string()),
+            email: var("ADMIN_EMAIL")?,
+            password: var("ADMIN_PASSWORD")?,
+        })
+    }
+
+    /// `UPLOAD_DIR`, if set and non-empty.
+    pub fn upload_dir_from_env() -> Option<PathBuf> {
+        std::env::var("UPLOAD_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// `MAX_UPLOAD_BYTES`, if set to a number.
+    pub fn max_upload_bytes_from_env() -> Option<u64> {
+        std::env::var("MAX_UPLOAD_BYTES")
+            .ok()
+            .and_then(|bytes| bytes.parse().ok())
+    }
+}
+
+pub async fn run(listener: TcpListener, db_pool: PgPool) -> Result<Server, io::Error> {
+    let config = Config {
+        backend: StorageBackend::from_env(),
+        db_pool: Some(db_pool),
+        admin: Config::admin_from_env(),
+        upload_dir: Config::upload_dir_from_env(),
+        max_upload_bytes: Config::max_upload_bytes_from_env(),
+    };
+    run_with(listener, config).await
+}
 
-pub fn run(listener: TcpListener,db_pool:PgPool) -> Result<Server, io::Error> {
-    let shared_data = web::Data::new(AppState {
-        health_check_response: "Tutor Services running fine".to_string(),
-        visit_count: Mutex::new(0u32),
-        courses: Mutex::new(vec![]),
-        db_pool:db_pool,
-        tutors:Mutex::new(vec![])
-    });
+/// Builds the state `config` asks for, seeding the admin account first, and
+/// starts serving on `listener`.
+pub async fn run_with(listener: TcpListener, config: Config) -> Result<Server, io::Error> {
+    let mut state = match (config.backend, config.db_pool) {
+        (StorageBackend::InMemory, _) => AppState::in_memory(),
+        (StorageBackend::Postgres, Some(db_pool)) => AppState::postgres(db_pool),
+        (StorageBackend::Postgres, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Postgres storage backend requires a database pool",
+            ));
+        }
+    };
+    if let Some(admin) = config.admin {
+        state.seed_admin(admin).await?;
+    }
+    if let Some(upload_dir) = config.upload_dir {
+        state.blobs = Arc::new(LocalBlobStore::new(upload_dir));
+    }
+    if let Some(max_upload_bytes) = config.max_upload_bytes {
+        state.max_upload_bytes = max_upload_bytes;
+    }
+    let shared_data = web::Data::new(state);
 
     let app = move || {
         App::new()
             .app_data(shared_data.clone())
+            .app_data(web::JsonConfig::default().error_handler(errors::json_error_handler))
+            .app_data(web::QueryConfig::default().error_handler(errors::query_error_handler))
+            .app_data(web::PathConfig::default().error_handler(errors::path_error_handler))
             .configure(general_routes)
+            .configure(auth_routes)
             .configure(course_routes)
+            .configure(student_routes)
+            .default_service(web::route().to(errors::not_found_handler))
     };
 
     let server = HttpServer::new(app).listen(listener)?.run();
     Ok(server)
 }
 
-pub async fn connect_db()->Result<Pool<Postgres>,sqlx::Error> {
+pub async fn connect_db() -> Result<Pool<Postgres>, sqlx::Error> {
     dotenv().ok();
     let database_url = match std::env::var("DATABASE_URL") {
-        Ok(value)=>{value},
-        Err(e)=>{eprintln!("Failed to load DATABASE_URL from environment {e}");
-        return Err(sqlx::Error::Configuration(Box::new(e)))
-    }
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("Failed to load DATABASE_URL from environment {e}");
+            return Err(sqlx::Error::Configuration(Box::new(e)));
+        }
     };
 
-    let pool = PgPoolOptions::new().max_connections(5).connect(&database_url).await?;
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&database_url)
+        .await?;
+    // Opt out with SKIP_MIGRATIONS=1, e.g. when a deploy step migrates instead
+    if tutordb::migrations_enabled() {
+        tutordb::run_migrations(&pool).await?;
+    }
     Ok(pool)
-}
\ No newline at end of file
+}
@@ -1,11 +1,19 @@
-use tokio::runtime::Handle;
-use tutor_nodb::connect_db;
 use std::io;
 use std::net::TcpListener;
-use tutor_nodb::run; // from lib.rs
+use tutor_nodb::run_with;
+use tutor_nodb::{Config, StorageBackend, connect_db}; // from lib.rs
 #[tokio::main]
 async fn main() -> io::Result<()> {
     let listener = TcpListener::bind("127.0.0.1:8080")?;
-    let pool = Handle::current().block_on(connect_db()).expect("Could not connect to database");
-    run(listener,pool)?.await
+    let mut config = match StorageBackend::from_env() {
+        StorageBackend::Postgres => {
+            let pool = connect_db().await.expect("Could not connect to database");
+            Config::postgres(pool)
+        }
+        StorageBackend::InMemory => Config::in_memory(),
+    };
+    config.admin = Config::admin_from_env();
+    config.upload_dir = Config::upload_dir_from_env();
+    config.max_upload_bytes = Config::max_upload_bytes_from_env();
+    run_with(listener, config).await?.await
 }
@@ -1,51 +1,89 @@
+use crate::auth::Role;
+use crate::validation::{
+    Validate, ValidationErrors, check_email, check_name, check_password, check_required,
+};
 use actix_web::web;
-use chrono::NaiveDateTime;
+use bigdecimal::{BigDecimal, Signed};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use tutordb::models::courses::DEFAULT_ENROLLED_LIMIT;
+use tutordb::models::review::{MAX_RATING, MIN_RATING};
 use uuid::Uuid;
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CourseType {
+    #[default]
+    Free,
+    Paid,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Course {
     pub tutor_id: Uuid,
     pub course_id: Uuid,
     pub course_name: String,
+    #[serde(default)]
+    pub description: String,
     pub posted_time: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub enrolled: i64,
+    #[serde(default = "default_enrolled_limit")]
+    pub enrolled_limit: i64,
+    /// Average review rating, `None` until the course is first reviewed.
+    #[serde(default)]
+    pub rating: Option<f64>,
+    #[serde(default)]
+    pub course_type: CourseType,
+    /// Set, with `currency`, exactly when the course is PAID.
+    #[serde(default)]
+    pub price: Option<BigDecimal>,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+fn default_enrolled_limit() -> i64 {
+    DEFAULT_ENROLLED_LIMIT
 }
 
 impl From<web::Json<Course>> for Course {
     fn from(course: web::Json<Course>) -> Self {
         match course.posted_time {
-            Some(time) => Self::new(
-                course.tutor_id,
-                
-                course.course_name.clone(),
-                Some(time),
-            ),
-            None => Self::with_current_time(
-                course.tutor_id,
-                course.course_name.clone(),
-            ),
+            Some(time) => Self::new(course.tutor_id, course.course_name.clone(), Some(time)),
+            None => Self::with_current_time(course.tutor_id, course.course_name.clone()),
         }
     }
 }
 impl Course {
-    pub fn new(
-        tutor_id: Uuid,
-        course_name: String,
-        posted_time: Option<NaiveDateTime>,
-    ) -> Self {
+    pub fn new(tutor_id: Uuid, course_name: String, posted_time: Option<NaiveDateTime>) -> Self {
         Course {
             tutor_id,
-            course_id:Uuid::new_v4(),
+            course_id: Uuid::new_v4(),
             course_name,
+            description: String::new(),
             posted_time,
+            enrolled: 0,
+            enrolled_limit: DEFAULT_ENROLLED_LIMIT,
+            rating: None,
+            course_type: CourseType::Free,
+            price: None,
+            currency: None,
         }
     }
 
     pub fn with_current_time(tutor_id: Uuid, course_name: String) -> Self {
         Course {
             tutor_id,
-            course_id:Uuid::new_v4(),
+            course_id: Uuid::new_v4(),
             course_name,
+            description: String::new(),
             posted_time: Some(chrono::Utc::now().naive_utc()),
+            enrolled: 0,
+            enrolled_limit: DEFAULT_ENROLLED_LIMIT,
+            rating: None,
+            course_type: CourseType::Free,
+            price: None,
+            currency: None,
         }
     }
 
@@ -56,19 +94,1470 @@ impl Course {
     pub fn is_posted_by_tutor(&self, tutor_id: Uuid) -> bool {
         self.tutor_id == tutor_id
     }
+
+    pub fn is_full(&self) -> bool {
+        self.enrolled >= self.enrolled_limit
+    }
+
+    /// What the course costs, FREE courses counting as 0.
+    pub fn effective_price(&self) -> BigDecimal {
+        self.price.clone().unwrap_or_default()
+    }
+}
+
+/// Checks `price` and `currency` suit the course type: both are required for
+/// PAID courses and must be left out for FREE ones.
+pub fn check_pricing(
+    errors: &mut ValidationErrors,
+    course_type: CourseType,
+    price: Option<&BigDecimal>,
+    currency: Option<&str>,
+) {
+    match course_type {
+        CourseType::Paid => {
+            match price {
+                None => errors.add("price", "price is required for PAID courses"),
+                Some(price) if price.is_negative() => {
+                    errors.add("price", "price must not be negative")
+                }
+                Some(price) if !fits_price_column(price) => errors.add(
+                    "price",
+                    "price must have at most 8 digits before and 2 after the decimal point",
+                ),
+                Some(_) => {}
+            }
+            match currency {
+                None => errors.add("currency", "currency is required for PAID courses"),
+                Some(currency) if !is_currency_code(currency) => errors.add(
+                    "currency",
+                    format!("`{currency}` is not a three-letter ISO 4217 currency code"),
+                ),
+                Some(_) => {}
+            }
+        }
+        CourseType::Free => {
+            if price.is_some() {
+                errors.add("price", "FREE courses cannot have a price");
+            }
+            if currency.is_some() {
+                errors.add("currency", "FREE courses cannot have a currency");
+            }
+        }
+    }
+}
+
+/// Longest course description accepted, in characters.
+pub const MAX_DESCRIPTION_LEN: usize = 5000;
+
+fn check_description(errors: &mut ValidationErrors, description: &str) {
+    if description.chars().count() > MAX_DESCRIPTION_LEN {
+        errors.add(
+            "description",
+            format!("description must be at most {MAX_DESCRIPTION_LEN} characters"),
+        );
+    }
+}
+
+// NUMERIC(10,2) in the course table
+fn fits_price_column(price: &BigDecimal) -> bool {
+    let price = price.normalized();
+    let fractional_digits = price.fractional_digit_count();
+    let integer_digits = price.digits() as i64 - fractional_digits;
+    fractional_digits <= 2 && integer_digits <= 8
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+fn is_currency_code(currency: &str) -> bool {
+    currency.len() == 3 && currency.chars().all(|c| c.is_ascii_uppercase())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Tutor {
-    pub name:String,
-    pub email:String,
-    pub tutor_id:Uuid,
+    pub name: String,
+    pub email: String,
+    pub tutor_id: Uuid,
+    /// Average of every review across the tutor's courses, 0 until reviewed.
+    #[serde(default)]
+    pub rating: f64,
+}
+
+impl Tutor {
+    pub fn new(name: String, email: String) -> Self {
+        Tutor {
+            name,
+            email,
+            tutor_id: Uuid::new_v4(),
+            rating: 0.0,
+        }
+    }
+}
+
+/// Body of `POST /tutors/`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewTutor {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+impl Validate for NewTutor {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_name(&mut errors, "name", &self.name);
+        check_email(&mut errors, "email", &self.email);
+        check_password(&mut errors, "password", &self.password);
+        errors.into_result()
+    }
+}
+
+/// What anyone may see of a tutor; the email is only shown to the tutor
+/// and admins.
+#[derive(Debug, Serialize)]
+pub struct PublicTutor {
+    pub name: String,
+    pub tutor_id: Uuid,
+    pub rating: f64,
+}
+
+impl From<Tutor> for PublicTutor {
+    fn from(tutor: Tutor) -> Self {
+        PublicTutor {
+            name: tutor.name,
+            tutor_id: tutor.tutor_id,
+            rating: tutor.rating,
+        }
+    }
+}
+
+/// Which kind of account `POST /auth/login` checks the email against.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountKind {
+    #[default]
+    Tutor,
+    Student,
+}
+
+/// Body of `POST /auth/login`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginRequest {
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub account: AccountKind,
+}
+
+impl Validate for LoginRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_required(&mut errors, "email", &self.email);
+        check_required(&mut errors, "password", &self.password);
+        errors.into_result()
+    }
+}
+
+/// Body of `POST /auth/refresh`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshRequest {
+    #[serde(default)]
+    pub refresh_token: String,
+}
+
+impl Validate for RefreshRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_required(&mut errors, "refresh_token", &self.refresh_token);
+        errors.into_result()
+    }
+}
+
+/// Query of `GET /tutors/search`, for admins.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TutorSearch {
+    /// Matched case-insensitively anywhere in the email.
+    #[serde(default)]
+    pub email: String,
+    #[serde(default = "default_page_limit")]
+    pub limit: i64,
+}
+
+impl Validate for TutorSearch {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_required(&mut errors, "email", &self.email);
+        if self.email.chars().count() > MAX_QUERY_LEN {
+            errors.add(
+                "email",
+                format!("email must be at most {MAX_QUERY_LEN} characters"),
+            );
+        }
+        if !(1..=MAX_PAGE_LIMIT).contains(&self.limit) {
+            errors.add(
+                "limit",
+                format!("limit must be between 1 and {MAX_PAGE_LIMIT}"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// Body of `GET /tutors/me`.
+#[derive(Debug, Serialize)]
+pub struct TutorProfile {
+    #[serde(flatten)]
+    pub tutor: Tutor,
+    pub role: Role,
+    pub courses: Vec<Course>,
+}
+
+/// Body of `PUT /tutors/{tutor_id}/role`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleUpdate {
+    pub role: Role,
+}
+
+impl Validate for RoleUpdate {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.role == Role::Student {
+            errors.add("role", "tutors can only be given the admin or tutor role");
+        }
+        errors.into_result()
+    }
+}
+
+/// Body of `PATCH /tutors/{tutor_id}`; absent fields are left unchanged.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TutorUpdate {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Validate for TutorUpdate {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some(name) = &self.name {
+            check_name(&mut errors, "name", name);
+        }
+        if let Some(email) = &self.email {
+            check_email(&mut errors, "email", email);
+        }
+        errors.into_result()
+    }
+}
+
+/// Body of `POST /courses/`. The course is posted by the authenticated
+/// tutor.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewCourse {
+    #[serde(default)]
+    pub course_name: String,
+    #[serde(default)]
+    pub description: String,
+    pub enrolled_limit: Option<i64>,
+    #[serde(default)]
+    pub course_type: CourseType,
+    pub price: Option<BigDecimal>,
+    pub currency: Option<String>,
+}
+
+impl Validate for NewCourse {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_name(&mut errors, "course_name", &self.course_name);
+        check_description(&mut errors, &self.description);
+        if let Some(limit) = self.enrolled_limit
+            && limit < 1
+        {
+            errors.add("enrolled_limit", "enrolled_limit must be at least 1");
+        }
+        check_pricing(
+            &mut errors,
+            self.course_type,
+            self.price.as_ref(),
+            self.currency.as_deref(),
+        );
+        errors.into_result()
+    }
+}
+
+/// Body of `PUT`/`PATCH /courses/{course_id}`; only the owning tutor may
+/// send it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CourseUpdate {
+    pub course_name: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub refresh_posted_time: bool,
+    /// Switching to FREE drops the price and currency.
+    pub course_type: Option<CourseType>,
+    pub price: Option<BigDecimal>,
+    pub currency: Option<String>,
+}
+
+impl Validate for CourseUpdate {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some(course_name) = &self.course_name {
+            check_name(&mut errors, "course_name", course_name);
+        }
+        if let Some(description) = &self.description {
+            check_description(&mut errors, description);
+        }
+        errors.into_result()
+    }
+}
+
+/// Column `GET /courses/` orders by.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CourseSort {
+    #[default]
+    PostedTime,
+    Name,
+    Rating,
+    Price,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+pub const DEFAULT_PAGE_LIMIT: i64 = 20;
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+fn default_page_limit() -> i64 {
+    DEFAULT_PAGE_LIMIT
+}
+
+/// Query of `GET /courses/`; absent filters match every course. FREE
+/// courses count as costing 0 for the price bounds.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CourseQuery {
+    pub tutor_id: Option<Uuid>,
+    pub course_type: Option<CourseType>,
+    pub min_price: Option<BigDecimal>,
+    pub max_price: Option<BigDecimal>,
+    /// Only courses posted strictly after this time.
+    pub posted_after: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub sort: CourseSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default = "default_page_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+impl CourseQuery {
+    pub fn matches(&self, course: &Course) -> bool {
+        let price = course.effective_price();
+        self.tutor_id
+            .is_none_or(|tutor_id| course.tutor_id == tutor_id)
+            && self
+                .course_type
+                .is_none_or(|course_type| course.course_type == course_type)
+            && self.min_price.as_ref().is_none_or(|min| &price >= min)
+            && self.max_price.as_ref().is_none_or(|max| &price <= max)
+            && self
+                .posted_after
+                .is_none_or(|after| course.posted_time.is_some_and(|posted| posted > after))
+    }
+
+    /// Catalogue order: the requested column, unset ratings and posting
+    /// times last either way, then the course id so pages are stable.
+    pub fn compare(&self, a: &Course, b: &Course) -> Ordering {
+        let descending = self.order == SortOrder::Desc;
+        let ordering = match self.sort {
+            CourseSort::PostedTime => nulls_last(a.posted_time, b.posted_time, descending),
+            CourseSort::Rating => nulls_last(a.rating, b.rating, descending),
+            CourseSort::Name => directed(
+                a.course_name
+                    .to_lowercase()
+                    .cmp(&b.course_name.to_lowercase()),
+                descending,
+            ),
+            CourseSort::Price => {
+                directed(a.effective_price().cmp(&b.effective_price()), descending)
+            }
+        };
+        ordering.then_with(|| a.course_id.cmp(&b.course_id))
+    }
+}
+
+fn directed(ordering: Ordering, descending: bool) -> Ordering {
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn nulls_last<T: PartialOrd>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => directed(a.partial_cmp(&b).unwrap_or(Ordering::Equal), descending),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl Validate for CourseQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let (Some(min), Some(max)) = (&self.min_price, &self.max_price)
+            && min > max
+        {
+            errors.add("min_price", "min_price must not exceed max_price");
+        }
+        if !(1..=MAX_PAGE_LIMIT).contains(&self.limit) {
+            errors.add(
+                "limit",
+                format!("limit must be between 1 and {MAX_PAGE_LIMIT}"),
+            );
+        }
+        if self.offset < 0 {
+            errors.add("offset", "offset must not be negative");
+        }
+        errors.into_result()
+    }
+}
+
+/// One page of `GET /courses/`.
+#[derive(Debug, Serialize)]
+pub struct CoursePage {
+    pub courses: Vec<Course>,
+    /// Matching courses across all pages.
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    /// Offset of the following page, `None` on the last one.
+    pub next_offset: Option<i64>,
+}
+
+impl CoursePage {
+    pub fn new(courses: Vec<Course>, total: i64, query: &CourseQuery) -> Self {
+        let next_offset = query.offset + courses.len() as i64;
+        CoursePage {
+            courses,
+            total,
+            limit: query.limit,
+            offset: query.offset,
+            next_offset: (next_offset < total).then_some(next_offset),
+        }
+    }
+}
+
+/// Query of `GET /courses/search`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+    #[serde(default = "default_page_limit")]
+    pub limit: i64,
+}
+
+/// Longest search query accepted, in characters.
+pub const MAX_QUERY_LEN: usize = 200;
+
+impl Validate for SearchQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_required(&mut errors, "q", &self.q);
+        if self.q.chars().count() > MAX_QUERY_LEN {
+            errors.add("q", format!("q must be at most {MAX_QUERY_LEN} characters"));
+        }
+        if !(1..=MAX_PAGE_LIMIT).contains(&self.limit) {
+            errors.add(
+                "limit",
+                format!("limit must be between 1 and {MAX_PAGE_LIMIT}"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// A course matching a search, best first. Highlights wrap matched words in
+/// `<mark>`/`</mark>` and are not HTML-escaped.
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub course: Course,
+    pub rank: f64,
+    pub highlight: Highlight,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Highlight {
+    pub course_name: String,
+    pub description: String,
 }
 
-impl Tutor{
-    pub fn new(name:String,email:String) ->Self{
-        Tutor{
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Student {
+    pub name: String,
+    pub email: String,
+    pub student_id: Uuid,
+}
+
+impl Student {
+    pub fn new(name: String, email: String) -> Self {
+        Student {
             name,
             email,
-            tutor_id:Uuid::new_v4()}
-}}
+            student_id: Uuid::new_v4(),
+        }
+    }
+}
+
+/// What anyone may see of a student; the email is only shown to the student
+/// and admins.
+#[derive(Debug, Serialize)]
+pub struct PublicStudent {
+    pub name: String,
+    pub student_id: Uuid,
+}
+
+impl From<Student> for PublicStudent {
+    fn from(student: Student) -> Self {
+        PublicStudent {
+            name: student.name,
+            student_id: student.student_id,
+        }
+    }
+}
+
+/// Body of `POST /students/`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewStudent {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+impl Validate for NewStudent {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_name(&mut errors, "name", &self.name);
+        check_email(&mut errors, "email", &self.email);
+        check_password(&mut errors, "password", &self.password);
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Enrollment {
+    pub course_id: Uuid,
+    pub student_id: Uuid,
+    pub enrolled_at: NaiveDateTime,
+}
+
+/// A student queued for a seat on a full course.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WaitlistEntry {
+    pub course_id: Uuid,
+    pub student_id: Uuid,
+    pub joined_at: NaiveDateTime,
+    /// 1-based place in the queue.
+    pub position: i64,
+}
+
+/// Body of `POST /courses/{course_id}/enrollments`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewEnrollment {
+    #[serde(default)]
+    pub student_id: Uuid,
+}
+
+impl Validate for NewEnrollment {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.student_id.is_nil() {
+            errors.add("student_id", "student_id is required");
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Review {
+    pub course_id: Uuid,
+    pub student_id: Uuid,
+    pub rating: i16,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Longest review text accepted, in characters.
+pub const MAX_REVIEW_LEN: usize = 2000;
+
+/// Body of `POST /courses/{course_id}/reviews`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewReview {
+    #[serde(default)]
+    pub student_id: Uuid,
+    #[serde(default)]
+    pub rating: i16,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl Validate for NewReview {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.student_id.is_nil() {
+            errors.add("student_id", "student_id is required");
+        }
+        if !(MIN_RATING..=MAX_RATING).contains(&self.rating) {
+            errors.add(
+                "rating",
+                format!("rating must be between {MIN_RATING} and {MAX_RATING}"),
+            );
+        }
+        check_required(&mut errors, "body", &self.body);
+        if self.body.chars().count() > MAX_REVIEW_LEN {
+            errors.add(
+                "body",
+                format!("body must be at most {MAX_REVIEW_LEN} characters"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// Most weekly slots a tutor may have.
+pub const MAX_WEEKLY_SLOTS: usize = 50;
+
+/// A weekly window in which a tutor can be booked, as wall-clock times in
+/// the timezone of its `Availability`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklySlot {
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// Body and response of `PUT /tutors/{tutor_id}/availability`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Availability {
+    /// IANA name such as `Europe/London`, so slots follow daylight saving.
+    #[serde(default)]
+    pub timezone: String,
+    #[serde(default)]
+    pub slots: Vec<WeeklySlot>,
+}
+
+impl Default for Availability {
+    fn default() -> Self {
+        Availability {
+            timezone: "UTC".to_string(),
+            slots: Vec::new(),
+        }
+    }
+}
+
+impl Validate for Availability {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_required(&mut errors, "timezone", &self.timezone);
+        if !self.timezone.trim().is_empty() && self.timezone.parse::<chrono_tz::Tz>().is_err() {
+            errors.add(
+                "timezone",
+                format!("`{}` is not an IANA timezone", self.timezone),
+            );
+        }
+        if self.slots.len() > MAX_WEEKLY_SLOTS {
+            errors.add(
+                "slots",
+                format!("at most {MAX_WEEKLY_SLOTS} slots are allowed"),
+            );
+        }
+        for slot in &self.slots {
+            if slot.start >= slot.end {
+                errors.add(
+                    "slots",
+                    format!(
+                        "{} slot ending at {} must end after {}",
+                        slot.weekday, slot.end, slot.start
+                    ),
+                );
+            }
+        }
+        let overlapping = self.slots.iter().enumerate().any(|(i, a)| {
+            self.slots[i + 1..]
+                .iter()
+                .any(|b| a.weekday == b.weekday && a.start < b.end && b.start < a.end)
+        });
+        if overlapping {
+            errors.add("slots", "slots on the same day must not overlap");
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AvailabilityKind {
+    /// Time off inside the weekly slots.
+    Blocked,
+    /// Bookable time outside the weekly slots.
+    Extra,
+}
+
+/// A one-off change to a tutor's weekly availability.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailabilityException {
+    pub exception_id: Uuid,
+    pub tutor_id: Uuid,
+    pub kind: AvailabilityKind,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: String,
+    /// UID of the calendar event it was imported from, if any.
+    #[serde(default)]
+    pub ical_uid: Option<String>,
+}
+
+/// A calendar event `POST /tutors/{tutor_id}/calendar.ics` could not use.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SkippedEvent {
+    /// The event's UID, or empty when it had none.
+    pub uid: String,
+    pub reason: String,
+}
+
+/// Reply to `POST /tutors/{tutor_id}/calendar.ics`.
+#[derive(Debug, Serialize)]
+pub struct CalendarImport {
+    /// Busy times now blocked out, new or updated since an earlier import.
+    pub blocked: Vec<AvailabilityException>,
+    /// UIDs of cancelled events whose earlier import no longer blocks time.
+    pub released: Vec<String>,
+    pub skipped: Vec<SkippedEvent>,
+}
+
+/// Most events one calendar import may hold.
+pub const MAX_IMPORTED_EVENTS: usize = 1000;
+/// How many days of past bookings an exported calendar keeps.
+pub const CALENDAR_PAST_DAYS: i64 = 90;
+/// How many days of upcoming bookings an exported calendar shows.
+pub const CALENDAR_FUTURE_DAYS: i64 = 365;
+
+/// Longest exception reason or booking note accepted, in characters.
+pub const MAX_NOTE_LEN: usize = 500;
+
+// Both ends are present and in order; returns them when they are
+fn check_time_range(
+    errors: &mut ValidationErrors,
+    starts_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if starts_at.is_none() {
+        errors.add("starts_at", "starts_at is required");
+    }
+    if ends_at.is_none() {
+        errors.add("ends_at", "ends_at is required");
+    }
+    let (starts_at, ends_at) = (starts_at?, ends_at?);
+    if ends_at <= starts_at {
+        errors.add("ends_at", "ends_at must be after starts_at");
+        return None;
+    }
+    Some((starts_at, ends_at))
+}
+
+/// Body of `POST /tutors/{tutor_id}/availability/exceptions`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewAvailabilityException {
+    pub kind: AvailabilityKind,
+    /// RFC 3339 with any offset; stored in UTC.
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl Validate for NewAvailabilityException {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some((_, ends_at)) = check_time_range(&mut errors, self.starts_at, self.ends_at)
+            && ends_at <= Utc::now()
+        {
+            errors.add("ends_at", "ends_at must be in the future");
+        }
+        if self.reason.chars().count() > MAX_NOTE_LEN {
+            errors.add(
+                "reason",
+                format!("reason must be at most {MAX_NOTE_LEN} characters"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// Longest span one schedule query may cover, in days.
+pub const MAX_SCHEDULE_DAYS: i64 = 62;
+/// Span covered when a schedule query gives no `to`, in days.
+pub const DEFAULT_SCHEDULE_DAYS: i64 = 7;
+
+/// Query of the schedule listings under `/tutors/{tutor_id}`. `from`
+/// defaults to now and `to` to a week after `from`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ScheduleQuery {
+    pub fn range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let from = self.from.unwrap_or_else(Utc::now);
+        let to = self
+            .to
+            .unwrap_or(from + Duration::days(DEFAULT_SCHEDULE_DAYS));
+        (from, to)
+    }
+}
+
+impl Validate for ScheduleQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        let (from, to) = self.range();
+        if to <= from {
+            errors.add("to", "to must be after from");
+        } else if to - from > Duration::days(MAX_SCHEDULE_DAYS) {
+            errors.add(
+                "to",
+                format!("a query may cover at most {MAX_SCHEDULE_DAYS} days"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// A span of time, half-open: `ends_at` itself is not included.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BookingStatus {
+    Confirmed,
+    Cancelled,
+}
+
+/// A student's one-to-one session with a tutor.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Booking {
+    pub booking_id: Uuid,
+    pub tutor_id: Uuid,
+    pub student_id: Uuid,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub note: String,
+    pub status: BookingStatus,
+    pub created_at: DateTime<Utc>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+impl Booking {
+    pub fn new(
+        tutor_id: Uuid,
+        student_id: Uuid,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        note: String,
+    ) -> Self {
+        Booking {
+            booking_id: Uuid::new_v4(),
+            tutor_id,
+            student_id,
+            starts_at,
+            ends_at,
+            note,
+            status: BookingStatus::Confirmed,
+            created_at: Utc::now(),
+            cancelled_at: None,
+        }
+    }
+
+    pub fn overlaps(&self, starts_at: DateTime<Utc>, ends_at: DateTime<Utc>) -> bool {
+        self.starts_at < ends_at && starts_at < self.ends_at
+    }
+}
+
+/// Shortest and longest bookable session, in minutes.
+pub const MIN_BOOKING_MINUTES: i64 = 15;
+pub const MAX_BOOKING_MINUTES: i64 = 4 * 60;
+/// How long before a session students can still cancel it, in hours. Tutors
+/// and admins may cancel any time before it starts.
+pub const CANCELLATION_NOTICE_HOURS: i64 = 24;
+
+/// Body of `POST /tutors/{tutor_id}/bookings`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewBooking {
+    #[serde(default)]
+    pub student_id: Uuid,
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub note: String,
+}
+
+impl Validate for NewBooking {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.student_id.is_nil() {
+            errors.add("student_id", "student_id is required");
+        }
+        if let Some((starts_at, ends_at)) =
+            check_time_range(&mut errors, self.starts_at, self.ends_at)
+        {
+            if starts_at <= Utc::now() {
+                errors.add("starts_at", "starts_at must be in the future");
+            }
+            let minutes = (ends_at - starts_at).num_minutes();
+            if !(MIN_BOOKING_MINUTES..=MAX_BOOKING_MINUTES).contains(&minutes) {
+                errors.add(
+                    "ends_at",
+                    format!("a booking must last between {MIN_BOOKING_MINUTES} and {MAX_BOOKING_MINUTES} minutes"),
+                );
+            }
+        }
+        if self.note.chars().count() > MAX_NOTE_LEN {
+            errors.add(
+                "note",
+                format!("note must be at most {MAX_NOTE_LEN} characters"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// One scheduled meeting of a course, in person or online.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CourseSession {
+    pub session_id: Uuid,
+    pub course_id: Uuid,
+    pub tutor_id: Uuid,
+    /// Shared by the sessions one recurrence rule created.
+    pub series_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub location: Option<String>,
+    pub meeting_url: Option<String>,
+    /// Seats for this session, when different from the course's
+    /// `enrolled_limit`.
+    pub capacity: Option<i64>,
+}
+
+impl CourseSession {
+    pub fn overlaps(&self, starts_at: DateTime<Utc>, ends_at: DateTime<Utc>) -> bool {
+        self.starts_at < ends_at && starts_at < self.ends_at
+    }
+}
+
+/// Longest course session, in hours.
+pub const MAX_SESSION_HOURS: i64 = 12;
+/// Most weeks one recurrence rule may cover.
+pub const MAX_SESSION_WEEKS: u32 = 52;
+pub const MAX_LOCATION_LEN: usize = 200;
+pub const MAX_URL_LEN: usize = 2000;
+
+/// Repeats a session at the same wall-clock time every week.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recurrence {
+    /// How many weeks, counting the first session.
+    pub weeks: u32,
+    /// Local dates to leave out, such as holidays.
+    #[serde(default)]
+    pub skip: Vec<NaiveDate>,
+    /// IANA name of the wall clock the sessions keep; defaults to the
+    /// timezone of the tutor's availability.
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Body of `POST /courses/{course_id}/sessions`: one session, or the first
+/// of a weekly series when `repeat` is given.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewCourseSession {
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub meeting_url: Option<String>,
+    #[serde(default)]
+    pub capacity: Option<i64>,
+    #[serde(default)]
+    pub repeat: Option<Recurrence>,
+}
+
+impl Validate for NewCourseSession {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some((starts_at, ends_at)) =
+            check_time_range(&mut errors, self.starts_at, self.ends_at)
+        {
+            if starts_at <= Utc::now() {
+                errors.add("starts_at", "starts_at must be in the future");
+            }
+            if ends_at - starts_at > Duration::hours(MAX_SESSION_HOURS) {
+                errors.add(
+                    "ends_at",
+                    format!("a session may last at most {MAX_SESSION_HOURS} hours"),
+                );
+            }
+        }
+
+        if self.location.is_none() && self.meeting_url.is_none() {
+            errors.add("location", "location or meeting_url is required");
+        }
+        if let Some(location) = &self.location {
+            check_required(&mut errors, "location", location);
+            if location.chars().count() > MAX_LOCATION_LEN {
+                errors.add(
+                    "location",
+                    format!("location must be at most {MAX_LOCATION_LEN} characters"),
+                );
+            }
+        }
+        if let Some(url) = &self.meeting_url {
+            let host = url
+                .strip_prefix("https://")
+                .or_else(|| url.strip_prefix("http://"));
+            if host.is_none_or(|host| host.is_empty() || host.chars().any(char::is_whitespace)) {
+                errors.add("meeting_url", "meeting_url must be an http or https URL");
+            } else if url.len() > MAX_URL_LEN {
+                errors.add(
+                    "meeting_url",
+                    format!("meeting_url must be at most {MAX_URL_LEN} characters"),
+                );
+            }
+        }
+        if let Some(capacity) = self.capacity
+            && capacity < 1
+        {
+            errors.add("capacity", "capacity must be at least 1");
+        }
+
+        if let Some(repeat) = &self.repeat {
+            if !(1..=MAX_SESSION_WEEKS).contains(&repeat.weeks) {
+                errors.add(
+                    "repeat.weeks",
+                    format!("weeks must be between 1 and {MAX_SESSION_WEEKS}"),
+                );
+            } else if repeat.skip.len() >= repeat.weeks as usize {
+                errors.add("repeat.skip", "skip must leave at least one session");
+            }
+            if let Some(timezone) = &repeat.timezone
+                && timezone.parse::<chrono_tz::Tz>().is_err()
+            {
+                errors.add(
+                    "repeat.timezone",
+                    format!("`{timezone}` is not an IANA timezone"),
+                );
+            }
+        }
+        errors.into_result()
+    }
+}
+
+/// Query of the upcoming session listings. `from` defaults to now.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpcomingQuery {
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default = "default_page_limit")]
+    pub limit: i64,
+}
+
+impl Validate for UpcomingQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if !(1..=MAX_PAGE_LIMIT).contains(&self.limit) {
+            errors.add(
+                "limit",
+                format!("limit must be between 1 and {MAX_PAGE_LIMIT}"),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AttendanceStatus {
+    Present,
+    Absent,
+    Late,
+    Excused,
+}
+
+/// A student's attendance at one course session.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Attendance {
+    pub session_id: Uuid,
+    pub student_id: Uuid,
+    pub status: AttendanceStatus,
+    /// The student checked in with a code rather than being marked.
+    pub checked_in: bool,
+    pub marked_at: DateTime<Utc>,
+}
+
+/// Most students one attendance request may mark.
+pub const MAX_ATTENDANCE_MARKS: usize = 500;
+/// How long before a session starts its roll may be taken and check-in
+/// opened, in minutes. Students may be excused at any time.
+pub const CHECK_IN_OPENS_MINUTES: i64 = 15;
+/// How long a check-in code stays valid, in minutes.
+pub const CHECK_IN_CODE_MINUTES: i64 = 10;
+/// Students checking in more than this many minutes after the start are
+/// marked late.
+pub const LATE_AFTER_MINUTES: i64 = 10;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttendanceMark {
+    pub student_id: Uuid,
+    pub status: AttendanceStatus,
+}
+
+/// Body of `PUT /courses/{course_id}/sessions/{session_id}/attendance`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttendanceMarks {
+    #[serde(default)]
+    pub marks: Vec<AttendanceMark>,
+}
+
+impl Validate for AttendanceMarks {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.marks.is_empty() {
+            errors.add("marks", "marks is required");
+        } else if self.marks.len() > MAX_ATTENDANCE_MARKS {
+            errors.add(
+                "marks",
+                format!("at most {MAX_ATTENDANCE_MARKS} students may be marked at once"),
+            );
+        }
+        let repeated = self.marks.iter().enumerate().any(|(i, a)| {
+            self.marks[i + 1..]
+                .iter()
+                .any(|b| a.student_id == b.student_id)
+        });
+        if repeated {
+            errors.add("marks", "each student may be marked only once");
+        }
+        errors.into_result()
+    }
+}
+
+/// The code students enter to check in to a session; reply to
+/// `POST /courses/{course_id}/sessions/{session_id}/check-in-code`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckInCode {
+    pub session_id: Uuid,
+    pub code: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Body of `POST /courses/{course_id}/sessions/{session_id}/check-in`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckIn {
+    #[serde(default)]
+    pub student_id: Uuid,
+    #[serde(default)]
+    pub code: String,
+}
+
+impl Validate for CheckIn {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.student_id.is_nil() {
+            errors.add("student_id", "student_id is required");
+        }
+        check_required(&mut errors, "code", &self.code);
+        errors.into_result()
+    }
+}
+
+/// One student's attendance across one course's sessions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AttendanceTally {
+    pub course_id: Uuid,
+    pub student_id: Uuid,
+    /// Sessions of the course that have started.
+    pub sessions_held: i64,
+    pub present: i64,
+    pub absent: i64,
+    pub late: i64,
+    pub excused: i64,
+}
+
+/// A titled group of lessons within a course.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CourseModule {
+    pub module_id: Uuid,
+    pub course_id: Uuid,
+    pub title: String,
+    /// Zero-based place among the course's modules.
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Lesson {
+    pub lesson_id: Uuid,
+    pub module_id: Uuid,
+    pub title: String,
+    /// Markdown, rendered by clients.
+    pub body: String,
+    pub duration_minutes: Option<i32>,
+    /// Zero-based place among the module's lessons.
+    pub position: i32,
+    /// Drafts are only shown to the course's tutor.
+    pub published: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Longest lesson body accepted, in characters.
+pub const MAX_LESSON_BODY_LEN: usize = 100_000;
+/// Longest lesson estimate accepted, in minutes.
+pub const MAX_LESSON_MINUTES: i32 = 24 * 60;
+/// Most modules a course, or lessons a module, may hold.
+pub const MAX_CURRICULUM_ITEMS: usize = 200;
+
+fn check_lesson_body(errors: &mut ValidationErrors, body: &str) {
+    if body.chars().count() > MAX_LESSON_BODY_LEN {
+        errors.add(
+            "body",
+            format!("body must be at most {MAX_LESSON_BODY_LEN} characters"),
+        );
+    }
+}
+
+fn check_lesson_minutes(errors: &mut ValidationErrors, minutes: i32) {
+    if !(1..=MAX_LESSON_MINUTES).contains(&minutes) {
+        errors.add(
+            "duration_minutes",
+            format!("duration_minutes must be between 1 and {MAX_LESSON_MINUTES}"),
+        );
+    }
+}
+
+/// Body of `POST /courses/{course_id}/modules` and
+/// `PATCH /courses/{course_id}/modules/{module_id}`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleTitle {
+    #[serde(default)]
+    pub title: String,
+}
+
+impl Validate for ModuleTitle {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_name(&mut errors, "title", &self.title);
+        errors.into_result()
+    }
+}
+
+/// Body of `POST /courses/{course_id}/modules/{module_id}/lessons`. Lessons
+/// start as drafts unless `published` is set.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewLesson {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    pub duration_minutes: Option<i32>,
+    #[serde(default)]
+    pub published: bool,
+}
+
+impl Validate for NewLesson {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        check_name(&mut errors, "title", &self.title);
+        check_lesson_body(&mut errors, &self.body);
+        if let Some(minutes) = self.duration_minutes {
+            check_lesson_minutes(&mut errors, minutes);
+        }
+        errors.into_result()
+    }
+}
+
+/// Body of `PATCH /courses/{course_id}/modules/{module_id}/lessons/{lesson_id}`;
+/// fields left out are kept. `clear_duration` drops the estimate.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LessonUpdate {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub duration_minutes: Option<i32>,
+    #[serde(default)]
+    pub clear_duration: bool,
+    pub published: Option<bool>,
+}
+
+impl Validate for LessonUpdate {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some(title) = &self.title {
+            check_name(&mut errors, "title", title);
+        }
+        if let Some(body) = &self.body {
+            check_lesson_body(&mut errors, body);
+        }
+        if let Some(minutes) = self.duration_minutes {
+            check_lesson_minutes(&mut errors, minutes);
+            if self.clear_duration {
+                errors.add(
+                    "clear_duration",
+                    "clear_duration cannot be combined with duration_minutes",
+                );
+            }
+        }
+        errors.into_result()
+    }
+}
+
+/// Body of the `.../order` endpoints: every module of the course, or every
+/// lesson of the module, in their new order.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Reorder {
+    #[serde(default)]
+    pub ids: Vec<Uuid>,
+}
+
+impl Validate for Reorder {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.ids.len() > MAX_CURRICULUM_ITEMS {
+            errors.add(
+                "ids",
+                format!("at most {MAX_CURRICULUM_ITEMS} ids may be given"),
+            );
+        }
+        let repeated = self
+            .ids
+            .iter()
+            .enumerate()
+            .any(|(i, id)| self.ids[i + 1..].contains(id));
+        if repeated {
+            errors.add("ids", "each id may appear only once");
+        }
+        errors.into_result()
+    }
+}
+
+/// A module with its lessons, as listed by `GET /courses/{course_id}/curriculum`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurriculumModule {
+    #[serde(flatten)]
+    pub module: CourseModule,
+    pub lessons: Vec<Lesson>,
+    /// Sum of the lessons' estimates.
+    pub duration_minutes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Curriculum {
+    pub course_id: Uuid,
+    pub modules: Vec<CurriculumModule>,
+    pub duration_minutes: i64,
+}
+
+impl Curriculum {
+    /// Groups `lessons`, ordered by module and then position, under their
+    /// modules.
+    pub fn new(course_id: Uuid, modules: Vec<CourseModule>, lessons: Vec<Lesson>) -> Self {
+        let modules: Vec<CurriculumModule> = modules
+            .into_iter()
+            .map(|module| {
+                let lessons: Vec<Lesson> = lessons
+                    .iter()
+                    .filter(|l| l.module_id == module.module_id)
+                    .cloned()
+                    .collect();
+                let duration_minutes = lessons
+                    .iter()
+                    .filter_map(|l| l.duration_minutes)
+                    .map(i64::from)
+                    .sum();
+                CurriculumModule {
+                    module,
+                    lessons,
+                    duration_minutes,
+                }
+            })
+            .collect();
+        Curriculum {
+            course_id,
+            duration_minutes: modules.iter().map(|m| m.duration_minutes).sum(),
+            modules,
+        }
+    }
+}
+
+/// A file attached to a lesson. Its bytes are in the blob store under
+/// `sha256`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Material {
+    pub material_id: Uuid,
+    pub lesson_id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Lowercase hex; also the download's ETag.
+    pub sha256: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+/// Largest material accepted unless the server is configured otherwise.
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 200 * 1024 * 1024;
@@ -1,21 +1,342 @@
+use super::auth::{Role, require_role};
 use super::handlers::*;
-use actix_web::web;
+use actix_web::dev::HttpServiceFactory;
+use actix_web::http::Method;
+use actix_web::middleware::from_fn;
+use actix_web::{FromRequest, Handler, Responder, guard, web};
+
+// Who may call the restricted routes below
+const ADMINS: &[Role] = &[Role::Admin];
+const TUTORS: &[Role] = &[Role::Admin, Role::Tutor];
+const STUDENTS: &[Role] = &[Role::Admin, Role::Student];
+const REVIEWERS: &[Role] = &[Role::Student];
+const MEMBERS: &[Role] = &[Role::Admin, Role::Tutor, Role::Student];
+
+/// `handler` for `method` on `path`, answered only for callers holding one of
+/// `roles`; anyone else gets a 401 or 403 JSON error before it runs.
+fn restricted<F, Args>(
+    path: &str,
+    method: Method,
+    roles: &'static [Role],
+    handler: F,
+) -> impl HttpServiceFactory
+where
+    F: Handler<Args>,
+    Args: FromRequest + 'static,
+    F::Output: Responder + 'static,
+{
+    web::resource(path)
+        .guard(guard::Method(method))
+        .route(web::route().to(handler))
+        .wrap(from_fn(move |req, next| require_role(roles, req, next)))
+}
 
 pub fn general_routes(cfg: &mut web::ServiceConfig) {
     cfg.route("/health", web::get().to(health_check_handler));
 }
 
+pub fn auth_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/auth")
+            .route("/login", web::post().to(login_handler)) // POST /auth/login
+            .route("/refresh", web::post().to(refresh_token_handler)), // POST /auth/refresh
+    );
+}
+
 pub fn course_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/courses")
-            .route("/", web::post().to(new_course_handler)) // POST /courses
-            .route("/{course_id}", web::get().to(get_course_details)), // GET /courses/{id}
+            .service(restricted("/", Method::POST, TUTORS, new_course_handler)) // POST /courses
+            .route("/", web::get().to(list_courses_handler)) // GET /courses (filtered, sorted, paged)
+            // Before `/{course_id}`, which would otherwise claim the path
+            .route("/search", web::get().to(search_courses_handler)) // GET /courses/search?q=
+            .route("/{course_id}", web::get().to(get_course_details)) // GET /courses/{id}
+            .service(restricted(
+                "/{course_id}",
+                Method::PUT,
+                TUTORS,
+                update_course_handler,
+            )) // PUT /courses/{id}
+            .service(restricted(
+                "/{course_id}",
+                Method::PATCH,
+                TUTORS,
+                update_course_handler,
+            )) // PATCH /courses/{id}
+            .service(restricted(
+                "/{course_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_course_handler,
+            )) // DELETE /courses/{id}
+            .service(restricted(
+                "/{course_id}/enrollments",
+                Method::POST,
+                STUDENTS,
+                enroll_student_handler,
+            )) // POST /courses/{id}/enrollments
+            .service(restricted(
+                "/{course_id}/enrollments/{student_id}",
+                Method::DELETE,
+                STUDENTS,
+                withdraw_student_handler,
+            )) // DELETE /courses/{id}/enrollments/{student_id}
+            .route("/{course_id}/waitlist", web::get().to(get_waitlist_handler)) // GET /courses/{id}/waitlist
+            .service(restricted(
+                "/{course_id}/reviews",
+                Method::POST,
+                REVIEWERS,
+                post_review_handler,
+            )) // POST /courses/{id}/reviews
+            .route(
+                "/{course_id}/reviews",
+                web::get().to(get_course_reviews_handler),
+            ) // GET /courses/{id}/reviews
+            .service(restricted(
+                "/{course_id}/sessions",
+                Method::POST,
+                TUTORS,
+                schedule_sessions_handler,
+            )) // POST /courses/{id}/sessions
+            .route(
+                "/{course_id}/sessions",
+                web::get().to(list_course_sessions_handler),
+            ) // GET /courses/{id}/sessions?from=&limit=
+            .service(restricted(
+                "/{course_id}/sessions/{session_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_session_handler,
+            )) // DELETE /courses/{id}/sessions/{session_id}
+            .service(restricted(
+                "/{course_id}/sessions/{session_id}/attendance",
+                Method::PUT,
+                TUTORS,
+                mark_attendance_handler,
+            )) // PUT /courses/{id}/sessions/{session_id}/attendance
+            .service(restricted(
+                "/{course_id}/sessions/{session_id}/attendance",
+                Method::GET,
+                TUTORS,
+                list_attendance_handler,
+            )) // GET /courses/{id}/sessions/{session_id}/attendance
+            .service(restricted(
+                "/{course_id}/sessions/{session_id}/check-in-code",
+                Method::POST,
+                TUTORS,
+                open_check_in_handler,
+            )) // POST /courses/{id}/sessions/{session_id}/check-in-code
+            .service(restricted(
+                "/{course_id}/sessions/{session_id}/check-in",
+                Method::POST,
+                STUDENTS,
+                check_in_handler,
+            )) // POST /courses/{id}/sessions/{session_id}/check-in
+            .service(restricted(
+                "/{course_id}/attendance",
+                Method::GET,
+                TUTORS,
+                course_attendance_handler,
+            )) // GET /courses/{id}/attendance
+            .route(
+                "/{course_id}/curriculum",
+                web::get().to(get_curriculum_handler),
+            ) // GET /courses/{id}/curriculum
+            .service(restricted(
+                "/{course_id}/modules",
+                Method::POST,
+                TUTORS,
+                add_module_handler,
+            )) // POST /courses/{id}/modules
+            // Before the module routes so `order` is not taken for a module id
+            .service(restricted(
+                "/{course_id}/modules/order",
+                Method::PUT,
+                TUTORS,
+                reorder_modules_handler,
+            )) // PUT /courses/{id}/modules/order
+            .service(restricted(
+                "/{course_id}/modules/{module_id}",
+                Method::PATCH,
+                TUTORS,
+                rename_module_handler,
+            )) // PATCH /courses/{id}/modules/{module_id}
+            .service(restricted(
+                "/{course_id}/modules/{module_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_module_handler,
+            )) // DELETE /courses/{id}/modules/{module_id}
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons",
+                Method::POST,
+                TUTORS,
+                add_lesson_handler,
+            )) // POST /courses/{id}/modules/{module_id}/lessons
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/order",
+                Method::PUT,
+                TUTORS,
+                reorder_lessons_handler,
+            )) // PUT /courses/{id}/modules/{module_id}/lessons/order
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/{lesson_id}",
+                Method::PATCH,
+                TUTORS,
+                update_lesson_handler,
+            )) // PATCH /courses/{id}/modules/{module_id}/lessons/{lesson_id}
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/{lesson_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_lesson_handler,
+            )) // DELETE /courses/{id}/modules/{module_id}/lessons/{lesson_id}
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/{lesson_id}/materials",
+                Method::POST,
+                TUTORS,
+                upload_material_handler,
+            )) // POST /courses/{id}/modules/{module_id}/lessons/{lesson_id}/materials (multipart)
+            // The course's tutor and admins, or students enrolled in it
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/{lesson_id}/materials",
+                Method::GET,
+                MEMBERS,
+                list_materials_handler,
+            )) // GET /courses/{id}/modules/{module_id}/lessons/{lesson_id}/materials
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/{lesson_id}/materials/{material_id}",
+                Method::GET,
+                MEMBERS,
+                download_material_handler,
+            )) // GET /courses/{id}/modules/{module_id}/lessons/{lesson_id}/materials/{material_id}
+            .service(restricted(
+                "/{course_id}/modules/{module_id}/lessons/{lesson_id}/materials/{material_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_material_handler,
+            )), // DELETE /courses/{id}/modules/{module_id}/lessons/{lesson_id}/materials/{material_id}
     );
 
     cfg.service(
         web::scope("/tutors")
             .route("/", web::post().to(create_new_tutor)) // POST /tutors
-            .route("/id", web::post().to(get_tutor_id)) // POST /tutors/id (lookup by name/email)
-            .route("/{tutor_id}/courses", web::get().to(get_tutor_courses_handler)), // GET /tutors/{id}/courses
+            // Before `/{tutor_id}`, which would otherwise claim these paths
+            .service(restricted(
+                "/me",
+                Method::GET,
+                TUTORS,
+                get_my_profile_handler,
+            )) // GET /tutors/me
+            .service(restricted(
+                "/search",
+                Method::GET,
+                ADMINS,
+                search_tutors_handler,
+            )) // GET /tutors/search?email=
+            .route("/{tutor_id}", web::get().to(get_tutor_handler)) // GET /tutors/{id}
+            .service(restricted(
+                "/{tutor_id}",
+                Method::PATCH,
+                TUTORS,
+                update_tutor_handler,
+            )) // PATCH /tutors/{id}
+            .service(restricted(
+                "/{tutor_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_tutor_handler,
+            )) // DELETE /tutors/{id}
+            .service(restricted(
+                "/{tutor_id}/role",
+                Method::PUT,
+                ADMINS,
+                set_tutor_role_handler,
+            )) // PUT /tutors/{id}/role
+            .route(
+                "/{tutor_id}/courses",
+                web::get().to(get_tutor_courses_handler),
+            ) // GET /tutors/{id}/courses
+            .route(
+                "/{tutor_id}/availability",
+                web::get().to(get_availability_handler),
+            ) // GET /tutors/{id}/availability
+            .service(restricted(
+                "/{tutor_id}/availability",
+                Method::PUT,
+                TUTORS,
+                set_availability_handler,
+            )) // PUT /tutors/{id}/availability
+            .route(
+                "/{tutor_id}/availability/open",
+                web::get().to(get_open_windows_handler),
+            ) // GET /tutors/{id}/availability/open?from=&to=
+            .service(restricted(
+                "/{tutor_id}/availability/exceptions",
+                Method::POST,
+                TUTORS,
+                add_availability_exception_handler,
+            )) // POST /tutors/{id}/availability/exceptions
+            .service(restricted(
+                "/{tutor_id}/availability/exceptions",
+                Method::GET,
+                TUTORS,
+                list_availability_exceptions_handler,
+            )) // GET /tutors/{id}/availability/exceptions?from=&to=
+            .service(restricted(
+                "/{tutor_id}/availability/exceptions/{exception_id}",
+                Method::DELETE,
+                TUTORS,
+                delete_availability_exception_handler,
+            )) // DELETE /tutors/{id}/availability/exceptions/{exception_id}
+            .service(restricted(
+                "/{tutor_id}/bookings",
+                Method::POST,
+                STUDENTS,
+                book_tutor_handler,
+            )) // POST /tutors/{id}/bookings
+            .service(restricted(
+                "/{tutor_id}/bookings",
+                Method::GET,
+                TUTORS,
+                list_bookings_handler,
+            )) // GET /tutors/{id}/bookings?from=&to=
+            // Students, the tutor and admins, each under their own rules
+            .route(
+                "/{tutor_id}/bookings/{booking_id}/cancel",
+                web::post().to(cancel_booking_handler),
+            ) // POST /tutors/{id}/bookings/{booking_id}/cancel
+            .service(restricted(
+                "/{tutor_id}/calendar.ics",
+                Method::GET,
+                TUTORS,
+                export_calendar_handler,
+            )) // GET /tutors/{id}/calendar.ics
+            .service(restricted(
+                "/{tutor_id}/calendar.ics",
+                Method::POST,
+                TUTORS,
+                import_calendar_handler,
+            )), // POST /tutors/{id}/calendar.ics
+    );
+}
+
+pub fn student_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/students")
+            .route("/", web::post().to(create_new_student)) // POST /students
+            .route("/{student_id}", web::get().to(get_student_handler)) // GET /students/{id}
+            .service(restricted(
+                "/{student_id}/sessions",
+                Method::GET,
+                STUDENTS,
+                list_student_sessions_handler,
+            )) // GET /students/{id}/sessions?from=&limit=
+            .service(restricted(
+                "/{student_id}/attendance",
+                Method::GET,
+                STUDENTS,
+                student_attendance_handler,
+            )), // GET /students/{id}/attendance
     );
 }
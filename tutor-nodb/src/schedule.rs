@@ -0,0 +1,320 @@
+//! Turns a tutor's weekly slots, exceptions and bookings into the concrete
+//! UTC windows in which they can still be booked.
+
+use crate::models::{AvailabilityException, AvailabilityKind, TimeRange, WeeklySlot};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// `local` in `tz`, taking the earlier instant when clocks go back and
+/// skipping forward an hour when `local` falls in a spring-forward gap.
+fn to_utc(tz: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|instant| instant.with_timezone(&Utc))
+}
+
+/// Every occurrence of the weekly slots overlapping `[from, to)`.
+fn expand_slots(
+    tz: Tz,
+    slots: &[WeeklySlot],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<TimeRange> {
+    // A day either side, as local days straddle UTC ones
+    let mut day = from.with_timezone(&tz).date_naive() - Duration::days(1);
+    let last = to.with_timezone(&tz).date_naive() + Duration::days(1);
+
+    let mut ranges = Vec::new();
+    while day <= last {
+        for slot in slots.iter().filter(|slot| slot.weekday == day.weekday()) {
+            let (Some(starts_at), Some(ends_at)) = (
+                to_utc(tz, day.and_time(slot.start)),
+                to_utc(tz, day.and_time(slot.end)),
+            ) else {
+                continue;
+            };
+            if starts_at < ends_at {
+                ranges.push(TimeRange { starts_at, ends_at });
+            }
+        }
+        day += Duration::days(1);
+    }
+    ranges
+}
+
+/// Sorts `ranges` and joins the ones that overlap or touch.
+fn merge(mut ranges: Vec<TimeRange>) -> Vec<TimeRange> {
+    ranges.sort_by_key(|range| range.starts_at);
+    let mut merged: Vec<TimeRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.starts_at <= last.ends_at => {
+                last.ends_at = last.ends_at.max(range.ends_at);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// `windows` with every part covered by `cuts` removed.
+fn subtract(windows: Vec<TimeRange>, cuts: &[TimeRange]) -> Vec<TimeRange> {
+    let mut remaining = windows;
+    for cut in cuts {
+        remaining = remaining
+            .into_iter()
+            .flat_map(|window| {
+                if cut.ends_at <= window.starts_at || window.ends_at <= cut.starts_at {
+                    return vec![window];
+                }
+                let mut pieces = Vec::with_capacity(2);
+                if window.starts_at < cut.starts_at {
+                    pieces.push(TimeRange {
+                        starts_at: window.starts_at,
+                        ends_at: cut.starts_at,
+                    });
+                }
+                if cut.ends_at < window.ends_at {
+                    pieces.push(TimeRange {
+                        starts_at: cut.ends_at,
+                        ends_at: window.ends_at,
+                    });
+                }
+                pieces
+            })
+            .collect();
+    }
+    remaining
+}
+
+/// The bookable windows within `[from, to)`, earliest first: the weekly
+/// slots in `tz` plus `Extra` exceptions, minus `Blocked` exceptions and
+/// `busy` times such as confirmed bookings.
+pub fn open_windows(
+    tz: Tz,
+    slots: &[WeeklySlot],
+    exceptions: &[AvailabilityException],
+    busy: &[TimeRange],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<TimeRange> {
+    let mut ranges = expand_slots(tz, slots, from, to);
+    let mut blocked: Vec<TimeRange> = busy.to_vec();
+    for exception in exceptions {
+        let range = TimeRange {
+            starts_at: exception.starts_at,
+            ends_at: exception.ends_at,
+        };
+        match exception.kind {
+            AvailabilityKind::Extra => ranges.push(range),
+            AvailabilityKind::Blocked => blocked.push(range),
+        }
+    }
+    // Nothing before `from` or from `to` on
+    blocked.push(TimeRange {
+        starts_at: DateTime::<Utc>::MIN_UTC,
+        ends_at: from,
+    });
+    blocked.push(TimeRange {
+        starts_at: to,
+        ends_at: DateTime::<Utc>::MAX_UTC,
+    });
+
+    subtract(merge(ranges), &blocked)
+}
+
+/// Whether one of `windows` holds all of `[starts_at, ends_at)`.
+pub fn covers(windows: &[TimeRange], starts_at: DateTime<Utc>, ends_at: DateTime<Utc>) -> bool {
+    windows
+        .iter()
+        .any(|window| window.starts_at <= starts_at && ends_at <= window.ends_at)
+}
+
+/// `[starts_at, ends_at)` and the same wall-clock times in `tz` each week
+/// after it, `weeks` in all, leaving out those starting on a `skip` date.
+/// Fails with the first `skip` date no occurrence starts on.
+pub fn weekly_occurrences(
+    tz: Tz,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    weeks: u32,
+    skip: &[NaiveDate],
+) -> Result<Vec<TimeRange>, NaiveDate> {
+    let local_start = starts_at.with_timezone(&tz).naive_local();
+    let local_end = ends_at.with_timezone(&tz).naive_local();
+
+    let mut occurrences = Vec::with_capacity(weeks as usize);
+    let mut skipped = Vec::with_capacity(skip.len());
+    for week in 0..weeks as i64 {
+        let start = local_start + Duration::weeks(week);
+        if skip.contains(&start.date()) {
+            skipped.push(start.date());
+            continue;
+        }
+        let (Some(starts_at), Some(ends_at)) = (
+            to_utc(tz, start),
+            to_utc(tz, local_end + Duration::weeks(week)),
+        ) else {
+            continue;
+        };
+        occurrences.push(TimeRange { starts_at, ends_at });
+    }
+    match skip.iter().find(|date| !skipped.contains(date)) {
+        Some(date) => Err(*date),
+        None => Ok(occurrences),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Weekday};
+    use uuid::Uuid;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        text.parse().unwrap()
+    }
+
+    fn range(starts_at: &str, ends_at: &str) -> TimeRange {
+        TimeRange {
+            starts_at: utc(starts_at),
+            ends_at: utc(ends_at),
+        }
+    }
+
+    fn slot(weekday: Weekday, start: u32, end: u32) -> WeeklySlot {
+        WeeklySlot {
+            weekday,
+            start: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+        }
+    }
+
+    fn exception(kind: AvailabilityKind, window: TimeRange) -> AvailabilityException {
+        AvailabilityException {
+            exception_id: Uuid::new_v4(),
+            tutor_id: Uuid::nil(),
+            kind,
+            starts_at: window.starts_at,
+            ends_at: window.ends_at,
+            reason: String::new(),
+            ical_uid: None,
+        }
+    }
+
+    #[test]
+    fn weekly_slots_follow_daylight_saving() {
+        let london: Tz = "Europe/London".parse().unwrap();
+        // Mondays either side of the clocks going forward on 30 March 2025
+        let windows = open_windows(
+            london,
+            &[slot(Weekday::Mon, 9, 11)],
+            &[],
+            &[],
+            utc("2025-03-24T00:00:00Z"),
+            utc("2025-04-01T00:00:00Z"),
+        );
+        assert_eq!(
+            windows,
+            vec![
+                range("2025-03-24T09:00:00Z", "2025-03-24T11:00:00Z"),
+                range("2025-03-31T08:00:00Z", "2025-03-31T10:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn exceptions_and_bookings_reshape_the_week() {
+        let windows = open_windows(
+            Tz::UTC,
+            &[slot(Weekday::Tue, 9, 17)],
+            &[
+                exception(
+                    AvailabilityKind::Blocked,
+                    range("2025-06-03T12:00:00Z", "2025-06-03T13:00:00Z"),
+                ),
+                exception(
+                    AvailabilityKind::Extra,
+                    range("2025-06-03T17:00:00Z", "2025-06-03T18:00:00Z"),
+                ),
+                exception(
+                    AvailabilityKind::Extra,
+                    range("2025-06-07T10:00:00Z", "2025-06-07T11:00:00Z"),
+                ),
+            ],
+            &[range("2025-06-03T09:00:00Z", "2025-06-03T10:00:00Z")],
+            utc("2025-06-02T00:00:00Z"),
+            utc("2025-06-09T00:00:00Z"),
+        );
+        assert_eq!(
+            windows,
+            vec![
+                range("2025-06-03T10:00:00Z", "2025-06-03T12:00:00Z"),
+                // The extra hour joins onto the end of the weekly slot
+                range("2025-06-03T13:00:00Z", "2025-06-03T18:00:00Z"),
+                range("2025-06-07T10:00:00Z", "2025-06-07T11:00:00Z"),
+            ]
+        );
+
+        assert!(covers(
+            &windows,
+            utc("2025-06-03T16:30:00Z"),
+            utc("2025-06-03T17:30:00Z")
+        ));
+        assert!(!covers(
+            &windows,
+            utc("2025-06-03T11:30:00Z"),
+            utc("2025-06-03T12:30:00Z")
+        ));
+    }
+
+    #[test]
+    fn windows_are_clipped_to_the_query() {
+        let windows = open_windows(
+            Tz::UTC,
+            &[slot(Weekday::Wed, 9, 17)],
+            &[],
+            &[],
+            utc("2025-06-04T12:00:00Z"),
+            utc("2025-06-04T15:00:00Z"),
+        );
+        assert_eq!(
+            windows,
+            vec![range("2025-06-04T12:00:00Z", "2025-06-04T15:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn weekly_occurrences_keep_the_wall_clock() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        // 6pm local each Thursday; clocks go forward on 9 March 2025
+        let occurrences = weekly_occurrences(
+            new_york,
+            utc("2025-03-06T23:00:00Z"),
+            utc("2025-03-07T00:30:00Z"),
+            3,
+            &[date(13)],
+        );
+        assert_eq!(
+            occurrences,
+            Ok(vec![
+                range("2025-03-06T23:00:00Z", "2025-03-07T00:30:00Z"),
+                range("2025-03-20T22:00:00Z", "2025-03-20T23:30:00Z"),
+            ])
+        );
+
+        let missed = weekly_occurrences(
+            new_york,
+            utc("2025-03-06T23:00:00Z"),
+            utc("2025-03-07T00:30:00Z"),
+            3,
+            &[date(14)],
+        );
+        assert_eq!(missed, Err(date(14)));
+    }
+}
@@ -0,0 +1,99 @@
+//! Keyword search for the in-memory store, a rough stand-in for the
+//! Postgres full-text index: no stemming or stop words, but name matches
+//! still outrank description matches.
+
+use std::collections::HashSet;
+
+/// Weights of a matched term in the course name and description, mirroring
+/// the `A` and `B` weights of the tsvector.
+const NAME_WEIGHT: f64 = 1.0;
+const DESCRIPTION_WEIGHT: f64 = 0.4;
+
+/// Lowercased alphanumeric words of `text`.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Sum of the weights of every query term found in the name or description,
+/// `None` when nothing matches.
+pub fn score(terms: &HashSet<String>, name: &str, description: &str) -> Option<f64> {
+    let name_words: HashSet<String> = tokenize(name).into_iter().collect();
+    let description_words: HashSet<String> = tokenize(description).into_iter().collect();
+
+    let score: f64 = terms
+        .iter()
+        .map(|term| {
+            let mut weight = 0.0;
+            if name_words.contains(term) {
+                weight += NAME_WEIGHT;
+            }
+            if description_words.contains(term) {
+                weight += DESCRIPTION_WEIGHT;
+            }
+            weight
+        })
+        .sum();
+    (score > 0.0).then_some(score)
+}
+
+/// `text` with every word matching a term wrapped in `<mark>`/`</mark>`.
+pub fn highlight(text: &str, terms: &HashSet<String>) -> String {
+    let mut highlighted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(char::is_alphanumeric) {
+        highlighted.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        if terms.contains(&word.to_lowercase()) {
+            highlighted.push_str("<mark>");
+            highlighted.push_str(word);
+            highlighted.push_str("</mark>");
+        } else {
+            highlighted.push_str(word);
+        }
+        rest = &rest[end..];
+    }
+    highlighted.push_str(rest);
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(query: &str) -> HashSet<String> {
+        tokenize(query).into_iter().collect()
+    }
+
+    #[test]
+    fn tokenizes_on_punctuation() {
+        assert_eq!(
+            tokenize("Rust, WebAssembly & C++!"),
+            ["rust", "webassembly", "c"]
+        );
+    }
+
+    #[test]
+    fn name_matches_outscore_description_matches() {
+        let query = terms("rust");
+        let in_name = score(&query, "Rust basics", "").unwrap();
+        let in_description = score(&query, "Basics", "Learn rust").unwrap();
+        assert!(in_name > in_description);
+        assert_eq!(score(&query, "Go", "Learn go"), None);
+    }
+
+    #[test]
+    fn highlights_whole_words_only() {
+        let query = terms("rust");
+        assert_eq!(
+            highlight("Rust & rusty RUST.", &query),
+            "<mark>Rust</mark> & rusty <mark>RUST</mark>."
+        );
+    }
+}
@@ -1,12 +1,154 @@
-use sqlx::Postgres;
-use std::sync::Mutex;
+use super::AdminSeed;
+use super::auth::{self, JwtKeys, Role};
+use super::blob::{BlobLocks, BlobStore, LocalBlobStore};
+use super::models::{DEFAULT_MAX_UPLOAD_BYTES, NewTutor};
+use super::store::{
+    AttendanceStore, AuditStore, CourseStore, CurriculumStore, InMemoryAttendanceStore,
+    InMemoryAuditStore, InMemoryCourseStore, InMemoryCurriculumStore, InMemoryMaterialStore,
+    InMemoryScheduleStore, InMemoryStudentStore, InMemoryTutorStore, MaterialStore,
+    PgAttendanceStore, PgAuditStore, PgCourseStore, PgCurriculumStore, PgMaterialStore,
+    PgScheduleStore, PgStudentStore, PgTutorStore, ScheduleStore, StudentStore, TutorStore,
+};
+use super::validation::Validate;
 use sqlx::Pool;
-use super::models::{Course,Tutor};
+use sqlx::Postgres;
+use std::io;
+use std::sync::{Arc, Mutex};
 
 pub struct AppState {
     pub health_check_response: String,
     pub visit_count: Mutex<u32>,
-    pub courses: Mutex<Vec<Course>>,
-   pub db_pool:Pool<Postgres>,
-   pub tutors:Mutex<Vec<Tutor>>,
+    pub courses: Arc<dyn CourseStore>,
+    pub tutors: Arc<dyn TutorStore>,
+    pub students: Arc<dyn StudentStore>,
+    pub schedules: Arc<dyn ScheduleStore>,
+    pub audit: Arc<dyn AuditStore>,
+    pub attendance: Arc<dyn AttendanceStore>,
+    pub curriculum: Arc<dyn CurriculumStore>,
+    pub materials: Arc<dyn MaterialStore>,
+    /// Uploaded files, under the system temp directory unless the server is
+    /// configured with an upload directory.
+    pub blobs: Arc<dyn BlobStore>,
+    pub blob_locks: BlobLocks,
+    pub max_upload_bytes: u64,
+    pub jwt: JwtKeys,
+}
+
+impl AppState {
+    // One store per concern, each with an in-memory and a Postgres flavour
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        tutors: Arc<dyn TutorStore>,
+        courses: Arc<dyn CourseStore>,
+        students: Arc<dyn StudentStore>,
+        schedules: Arc<dyn ScheduleStore>,
+        audit: Arc<dyn AuditStore>,
+        attendance: Arc<dyn AttendanceStore>,
+        curriculum: Arc<dyn CurriculumStore>,
+        materials: Arc<dyn MaterialStore>,
+    ) -> Self {
+        AppState {
+            health_check_response: "Tutor Services running fine".to_string(),
+            visit_count: Mutex::new(0u32),
+            courses,
+            tutors,
+            students,
+            schedules,
+            audit,
+            attendance,
+            curriculum,
+            materials,
+            blobs: Arc::new(LocalBlobStore::new(
+                std::env::temp_dir().join("eazytutors-uploads"),
+            )),
+            blob_locks: BlobLocks::default(),
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+            jwt: JwtKeys::from_env(),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        let tutors = Arc::new(InMemoryTutorStore::default());
+        let materials = Arc::new(InMemoryMaterialStore::default());
+        let curriculum = Arc::new(InMemoryCurriculumStore::new(materials.clone()));
+        let courses = Arc::new(InMemoryCourseStore::new(tutors.clone(), curriculum.clone()));
+        Self::new(
+            tutors,
+            courses.clone(),
+            Arc::new(InMemoryStudentStore::default()),
+            Arc::new(InMemoryScheduleStore::default()),
+            Arc::new(InMemoryAuditStore::default()),
+            Arc::new(InMemoryAttendanceStore::new(courses)),
+            curriculum,
+            materials,
+        )
+    }
+
+    pub fn postgres(db_pool: Pool<Postgres>) -> Self {
+        Self::new(
+            Arc::new(PgTutorStore::new(db_pool.clone())),
+            Arc::new(PgCourseStore::new(db_pool.clone())),
+            Arc::new(PgStudentStore::new(db_pool.clone())),
+            Arc::new(PgScheduleStore::new(db_pool.clone())),
+            Arc::new(PgAuditStore::new(db_pool.clone())),
+            Arc::new(PgAttendanceStore::new(db_pool.clone())),
+            Arc::new(PgCurriculumStore::new(db_pool.clone())),
+            Arc::new(PgMaterialStore::new(db_pool)),
+        )
+    }
+
+    /// Creates the admin account unless it already exists. An account with
+    /// the email that is not an admin fails startup instead of being
+    /// promoted, as whoever signed up with the address may own it.
+    pub async fn seed_admin(&self, admin: AdminSeed) -> io::Result<()> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+        let AdminSeed {
+            name,
+            email,
+            password,
+        } = admin;
+        let seed = NewTutor {
+            name,
+            email,
+            password,
+        };
+        if let Err(errors) = seed.validate() {
+            let fields: Vec<String> = errors
+                .errors
+                .into_iter()
+                .map(|e| format!("{}: {}", e.field, e.message))
+                .collect();
+            return Err(invalid(format!(
+                "Invalid admin account ({})",
+                fields.join("; ")
+            )));
+        }
+
+        let storage = |e| io::Error::other(format!("Could not seed the admin account: {e}"));
+        match self
+            .tutors
+            .find_credentials(&seed.email)
+            .await
+            .map_err(storage)?
+        {
+            Some(existing) if existing.role == Role::Admin => Ok(()),
+            Some(_) => Err(invalid(format!(
+                "{} belongs to a tutor who is not an admin; promote them with `tutordb tutor role` instead",
+                seed.email
+            ))),
+            None => {
+                let password_hash = auth::hash_password(&seed.password);
+                let tutor = self
+                    .tutors
+                    .create(seed.name, seed.email, password_hash)
+                    .await
+                    .map_err(storage)?;
+                self.tutors
+                    .set_role(tutor.tutor_id, Role::Admin)
+                    .await
+                    .map_err(storage)?;
+                Ok(())
+            }
+        }
+    }
 }
@@ -0,0 +1,1341 @@
+use super::{
+    AttachOutcome, AttendanceStore, AuditStore, CourseStore, CurriculumStore, EnrollOutcome,
+    MaterialStore, ReviewOutcome, ScheduleStore, StoreError, StudentCredentials, StudentStore,
+    TutorCredentials, TutorStore, already_enrolled, already_waitlisted, booking_overlaps,
+    sessions_overlap, too_many_lessons, too_many_modules,
+};
+use crate::auth::Role;
+use crate::models::{
+    Attendance, AttendanceMark, AttendanceStatus, AttendanceTally, Availability,
+    AvailabilityException, Booking, BookingStatus, CheckInCode, Course, CourseModule, CoursePage,
+    CourseQuery, CourseSession, Enrollment, Highlight, Lesson, MAX_CURRICULUM_ITEMS, Material,
+    Review, SearchHit, Student, Tutor, WaitlistEntry,
+};
+use crate::search;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Tutors and their password hashes and roles; `tutors` is locked before
+/// `credentials`.
+#[derive(Default)]
+pub struct InMemoryTutorStore {
+    tutors: Mutex<Vec<Tutor>>,
+    credentials: Mutex<HashMap<Uuid, TutorCredentials>>,
+}
+
+impl InMemoryTutorStore {
+    fn set_rating(&self, tutor_id: Uuid, rating: f64) {
+        let mut tutors = self.tutors.lock().unwrap();
+        if let Some(tutor) = tutors.iter_mut().find(|t| t.tutor_id == tutor_id) {
+            tutor.rating = rating;
+        }
+    }
+}
+
+#[async_trait]
+impl TutorStore for InMemoryTutorStore {
+    async fn create(
+        &self,
+        name: String,
+        email: String,
+        password_hash: String,
+    ) -> Result<Tutor, StoreError> {
+        let mut tutors = self.tutors.lock().unwrap();
+        let mut credentials = self.credentials.lock().unwrap();
+        if tutors.iter().any(|t| t.email == email) {
+            return Err(email_taken("Tutor", &email));
+        }
+        let new_tutor = Tutor::new(name, email);
+        credentials.insert(
+            new_tutor.tutor_id,
+            TutorCredentials {
+                tutor_id: new_tutor.tutor_id,
+                password_hash: Some(password_hash),
+                role: Role::Tutor,
+            },
+        );
+        tutors.push(new_tutor.clone());
+        Ok(new_tutor)
+    }
+
+    async fn find_credentials(&self, email: &str) -> Result<Option<TutorCredentials>, StoreError> {
+        let tutors = self.tutors.lock().unwrap();
+        let credentials = self.credentials.lock().unwrap();
+        Ok(tutors
+            .iter()
+            .find(|t| t.email == email)
+            .and_then(|t| credentials.get(&t.tutor_id).cloned()))
+    }
+
+    async fn find_role(&self, tutor_id: Uuid) -> Result<Option<Role>, StoreError> {
+        let credentials = self.credentials.lock().unwrap();
+        Ok(credentials.get(&tutor_id).map(|c| c.role))
+    }
+
+    async fn set_role(&self, tutor_id: Uuid, role: Role) -> Result<bool, StoreError> {
+        let mut credentials = self.credentials.lock().unwrap();
+        match credentials.get_mut(&tutor_id) {
+            Some(existing) => {
+                existing.role = role;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn find(&self, tutor_id: Uuid) -> Result<Option<Tutor>, StoreError> {
+        let tutors = self.tutors.lock().unwrap();
+        Ok(tutors.iter().find(|t| t.tutor_id == tutor_id).cloned())
+    }
+
+    async fn search_by_email(&self, fragment: &str, limit: i64) -> Result<Vec<Tutor>, StoreError> {
+        let fragment = fragment.to_lowercase();
+        let tutors = self.tutors.lock().unwrap();
+        let mut found: Vec<Tutor> = tutors
+            .iter()
+            .filter(|t| t.email.to_lowercase().contains(&fragment))
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.email.cmp(&b.email));
+        found.truncate(limit.max(0) as usize);
+        Ok(found)
+    }
+
+    async fn update(&self, tutor: Tutor) -> Result<Option<Tutor>, StoreError> {
+        let mut tutors = self.tutors.lock().unwrap();
+        if tutors
+            .iter()
+            .any(|t| t.email == tutor.email && t.tutor_id != tutor.tutor_id)
+        {
+            return Err(email_taken("Tutor", &tutor.email));
+        }
+        match tutors.iter_mut().find(|t| t.tutor_id == tutor.tutor_id) {
+            Some(existing) => {
+                // The rating is owned by reviews, not by edits
+                let rating = existing.rating;
+                *existing = tutor;
+                existing.rating = rating;
+                Ok(Some(existing.clone()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn delete(&self, tutor_id: Uuid) -> Result<Option<Tutor>, StoreError> {
+        let mut tutors = self.tutors.lock().unwrap();
+        let mut credentials = self.credentials.lock().unwrap();
+        credentials.remove(&tutor_id);
+        Ok(tutors
+            .iter()
+            .position(|t| t.tutor_id == tutor_id)
+            .map(|index| tutors.remove(index)))
+    }
+}
+
+/// Audit entries as `(actor_id, action, detail)`, lost on restart.
+#[derive(Default)]
+pub struct InMemoryAuditStore {
+    entries: Mutex<Vec<(Uuid, String, String)>>,
+}
+
+#[async_trait]
+impl AuditStore for InMemoryAuditStore {
+    async fn record(&self, actor_id: Uuid, action: &str, detail: &str) -> Result<(), StoreError> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push((actor_id, action.to_string(), detail.to_string()));
+        Ok(())
+    }
+}
+
+// Mirrors the `email UNIQUE` constraints on the Postgres tables
+fn email_taken(kind: &str, email: &str) -> StoreError {
+    StoreError::Conflict(format!("{kind} with email `{email}` already exists"))
+}
+
+/// Courses, their enrolments, waitlists and reviews. Methods touching several
+/// always lock `courses`, then `enrollments`, `waitlist`, `reviews` and
+/// finally `tutors`.
+#[derive(Default)]
+pub struct InMemoryCourseStore {
+    courses: Mutex<Vec<Course>>,
+    enrollments: Mutex<Vec<Enrollment>>,
+    /// Kept in arrival order; `position` is filled in when listed.
+    waitlist: Mutex<Vec<WaitlistEntry>>,
+    reviews: Mutex<Vec<Review>>,
+    sessions: Mutex<Vec<CourseSession>>,
+    /// Tutor ratings are rolled up from reviews kept here.
+    tutors: Arc<InMemoryTutorStore>,
+    /// Emptied of a course's modules when the course is deleted.
+    curriculum: Arc<InMemoryCurriculumStore>,
+}
+
+impl InMemoryCourseStore {
+    pub fn new(tutors: Arc<InMemoryTutorStore>, curriculum: Arc<InMemoryCurriculumStore>) -> Self {
+        InMemoryCourseStore {
+            tutors,
+            curriculum,
+            ..Default::default()
+        }
+    }
+
+    fn refresh_tutor_rating(&self, courses: &[Course], reviews: &[Review], tutor_id: Uuid) {
+        let course_ids: Vec<Uuid> = courses
+            .iter()
+            .filter(|c| c.tutor_id == tutor_id)
+            .map(|c| c.course_id)
+            .collect();
+        let rating = average_rating(reviews.iter().filter(|r| course_ids.contains(&r.course_id)));
+        self.tutors.set_rating(tutor_id, rating.unwrap_or(0.0));
+    }
+}
+
+// Rounded to two places like the NUMERIC(3,2) rating columns
+fn average_rating<'a>(reviews: impl Iterator<Item = &'a Review>) -> Option<f64> {
+    let (count, total) = reviews.fold((0, 0.0), |(count, total), review| {
+        (count + 1, total + f64::from(review.rating))
+    });
+    (count > 0).then(|| (total / f64::from(count) * 100.0).round() / 100.0)
+}
+
+#[async_trait]
+impl CourseStore for InMemoryCourseStore {
+    async fn add(&self, course: Course) -> Result<Course, StoreError> {
+        let mut courses = self.courses.lock().unwrap();
+        courses.push(course.clone());
+        Ok(course)
+    }
+
+    async fn find(&self, course_id: Uuid) -> Result<Option<Course>, StoreError> {
+        let courses = self.courses.lock().unwrap();
+        Ok(courses
+            .iter()
+            .find(|course| course.course_id == course_id)
+            .cloned())
+    }
+
+    async fn list_for_tutor(&self, tutor_id: Uuid) -> Result<Vec<Course>, StoreError> {
+        let courses = self.courses.lock().unwrap();
+        Ok(courses
+            .iter()
+            .filter(|course| course.tutor_id == tutor_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn list(&self, query: &CourseQuery) -> Result<CoursePage, StoreError> {
+        let courses = self.courses.lock().unwrap();
+        let mut matching: Vec<&Course> = courses.iter().filter(|c| query.matches(c)).collect();
+        matching.sort_by(|a, b| query.compare(a, b));
+
+        let page = matching
+            .iter()
+            .skip(query.offset as usize)
+            .take(query.limit as usize)
+            .map(|&course| course.clone())
+            .collect();
+        Ok(CoursePage::new(page, matching.len() as i64, query))
+    }
+
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<SearchHit>, StoreError> {
+        let terms: HashSet<String> = search::tokenize(query).into_iter().collect();
+        let courses = self.courses.lock().unwrap();
+
+        let mut hits: Vec<(f64, &Course)> = courses
+            .iter()
+            .filter_map(|c| search::score(&terms, &c.course_name, &c.description).map(|s| (s, c)))
+            .collect();
+        hits.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| a.course_id.cmp(&b.course_id))
+        });
+
+        Ok(hits
+            .into_iter()
+            .take(limit as usize)
+            .map(|(rank, course)| SearchHit {
+                course: course.clone(),
+                rank,
+                highlight: Highlight {
+                    course_name: search::highlight(&course.course_name, &terms),
+                    description: search::highlight(&course.description, &terms),
+                },
+            })
+            .collect())
+    }
+
+    async fn update(&self, course: Course) -> Result<Option<Course>, StoreError> {
+        let mut courses = self.courses.lock().unwrap();
+        match courses.iter_mut().find(|c| c.course_id == course.course_id) {
+            Some(existing) => {
+                // The seat counter and rating are owned by enrolments and reviews
+                let (enrolled, rating) = (existing.enrolled, existing.rating);
+                *existing = course;
+                existing.enrolled = enrolled;
+                existing.rating = rating;
+                Ok(Some(existing.clone()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn delete(&self, course_id: Uuid) -> Result<Option<Course>, StoreError> {
+        let mut courses = self.courses.lock().unwrap();
+        let mut enrollments = self.enrollments.lock().unwrap();
+        let mut waitlist = self.waitlist.lock().unwrap();
+        let mut reviews = self.reviews.lock().unwrap();
+        enrollments.retain(|e| e.course_id != course_id);
+        waitlist.retain(|w| w.course_id != course_id);
+        reviews.retain(|r| r.course_id != course_id);
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|s| s.course_id != course_id);
+        self.curriculum.remove_course(course_id);
+
+        let index = match courses.iter().position(|c| c.course_id == course_id) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let course = courses.remove(index);
+        self.refresh_tutor_rating(&courses, &reviews, course.tutor_id);
+        Ok(Some(course))
+    }
+
+    async fn enroll(&self, course_id: Uuid, student_id: Uuid) -> Result<EnrollOutcome, StoreError> {
+        let mut courses = self.courses.lock().unwrap();
+        let mut enrollments = self.enrollments.lock().unwrap();
+        let mut waitlist = self.waitlist.lock().unwrap();
+
+        let course = courses
+            .iter_mut()
+            .find(|c| c.course_id == course_id)
+            .ok_or_else(|| StoreError::NotFound(format!("Course with ID {course_id} not found")))?;
+        if enrollments
+            .iter()
+            .any(|e| e.course_id == course_id && e.student_id == student_id)
+        {
+            return Err(already_enrolled(course_id, student_id));
+        }
+        if waitlist
+            .iter()
+            .any(|w| w.course_id == course_id && w.student_id == student_id)
+        {
+            return Err(already_waitlisted(course_id, student_id));
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        if course.is_full() {
+            let position = waitlist.iter().filter(|w| w.course_id == course_id).count() as i64 + 1;
+            let entry = WaitlistEntry {
+                course_id,
+                student_id,
+                joined_at: now,
+                position,
+            };
+            waitlist.push(entry.clone());
+            return Ok(EnrollOutcome::Waitlisted(entry));
+        }
+
+        course.enrolled += 1;
+        let enrollment = Enrollment {
+            course_id,
+            student_id,
+            enrolled_at: now,
+        };
+        enrollments.push(enrollment.clone());
+        Ok(EnrollOutcome::Enrolled(enrollment))
+    }
+
+    async fn withdraw(&self, course_id: Uuid, student_id: Uuid) -> Result<bool, StoreError> {
+        let mut courses = self.courses.lock().unwrap();
+        let mut enrollments = self.enrollments.lock().unwrap();
+        let mut waitlist = self.waitlist.lock().unwrap();
+
+        let index = match enrollments
+            .iter()
+            .position(|e| e.course_id == course_id && e.student_id == student_id)
+        {
+            Some(index) => index,
+            None => {
+                let before = waitlist.len();
+                waitlist.retain(|w| !(w.course_id == course_id && w.student_id == student_id));
+                return Ok(waitlist.len() < before);
+            }
+        };
+        enrollments.remove(index);
+
+        // The freed seat goes to the head of the queue, keeping the count as is
+        match waitlist.iter().position(|w| w.course_id == course_id) {
+            Some(head) => {
+                let promoted = waitlist.remove(head);
+                enrollments.push(Enrollment {
+                    course_id,
+                    student_id: promoted.student_id,
+                    enrolled_at: chrono::Utc::now().naive_utc(),
+                });
+            }
+            None => {
+                if let Some(course) = courses.iter_mut().find(|c| c.course_id == course_id) {
+                    course.enrolled -= 1;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    async fn list_enrolled(&self, course_id: Uuid) -> Result<Vec<Enrollment>, StoreError> {
+        let enrollments = self.enrollments.lock().unwrap();
+        // Kept in enrolment order
+        Ok(enrollments
+            .iter()
+            .filter(|e| e.course_id == course_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_waitlist(&self, course_id: Uuid) -> Result<Vec<WaitlistEntry>, StoreError> {
+        let waitlist = self.waitlist.lock().unwrap();
+        Ok(waitlist
+            .iter()
+            .filter(|w| w.course_id == course_id)
+            .enumerate()
+            .map(|(index, entry)| WaitlistEntry {
+                position: index as i64 + 1,
+                ..entry.clone()
+            })
+            .collect())
+    }
+
+    async fn post_review(
+        &self,
+        course_id: Uuid,
+        student_id: Uuid,
+        rating: i16,
+        body: String,
+    ) -> Result<ReviewOutcome, StoreError> {
+        let mut courses = self.courses.lock().unwrap();
+        let enrollments = self.enrollments.lock().unwrap();
+        let mut reviews = self.reviews.lock().unwrap();
+
+        let course = courses
+            .iter_mut()
+            .find(|c| c.course_id == course_id)
+            .ok_or_else(|| StoreError::NotFound(format!("Course with ID {course_id} not found")))?;
+        if !enrollments
+            .iter()
+            .any(|e| e.course_id == course_id && e.student_id == student_id)
+        {
+            return Ok(ReviewOutcome::NotEnrolled);
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let outcome = match reviews
+            .iter_mut()
+            .find(|r| r.course_id == course_id && r.student_id == student_id)
+        {
+            Some(existing) => {
+                existing.rating = rating;
+                existing.body = body;
+                existing.updated_at = now;
+                ReviewOutcome::Updated(existing.clone())
+            }
+            None => {
+                let review = Review {
+                    course_id,
+                    student_id,
+                    rating,
+                    body,
+                    created_at: now,
+                    updated_at: now,
+                };
+                reviews.push(review.clone());
+                ReviewOutcome::Created(review)
+            }
+        };
+
+        course.rating = average_rating(reviews.iter().filter(|r| r.course_id == course_id));
+        let tutor_id = course.tutor_id;
+        self.refresh_tutor_rating(&courses, &reviews, tutor_id);
+        Ok(outcome)
+    }
+
+    async fn list_reviews(&self, course_id: Uuid) -> Result<Vec<Review>, StoreError> {
+        let reviews = self.reviews.lock().unwrap();
+        let mut course_reviews: Vec<Review> = reviews
+            .iter()
+            .filter(|r| r.course_id == course_id)
+            .cloned()
+            .collect();
+        course_reviews.sort_by_key(|r| std::cmp::Reverse(r.updated_at));
+        Ok(course_reviews)
+    }
+
+    async fn schedule_sessions(
+        &self,
+        sessions: Vec<CourseSession>,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let mut stored = self.sessions.lock().unwrap();
+        // Mirrors the `course_session_no_overlap` exclusion constraint
+        for (i, session) in sessions.iter().enumerate() {
+            let clashes = stored.iter().chain(&sessions[..i]).any(|s| {
+                s.tutor_id == session.tutor_id && s.overlaps(session.starts_at, session.ends_at)
+            });
+            if clashes {
+                return Err(sessions_overlap(session.tutor_id));
+            }
+        }
+        stored.extend(sessions.iter().cloned());
+        Ok(sessions)
+    }
+
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<CourseSession>, StoreError> {
+        let sessions = self.sessions.lock().unwrap();
+        Ok(sessions
+            .iter()
+            .find(|s| s.session_id == session_id)
+            .cloned())
+    }
+
+    async fn list_sessions(
+        &self,
+        course_id: Uuid,
+        from: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let sessions = self.sessions.lock().unwrap();
+        Ok(upcoming(
+            sessions.iter().filter(|s| s.course_id == course_id),
+            from,
+            limit,
+        ))
+    }
+
+    async fn list_student_sessions(
+        &self,
+        student_id: Uuid,
+        from: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let course_ids: HashSet<Uuid> = self
+            .enrollments
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.student_id == student_id)
+            .map(|e| e.course_id)
+            .collect();
+        let sessions = self.sessions.lock().unwrap();
+        Ok(upcoming(
+            sessions
+                .iter()
+                .filter(|s| course_ids.contains(&s.course_id)),
+            from,
+            limit,
+        ))
+    }
+
+    async fn list_tutor_sessions(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut listed: Vec<CourseSession> = sessions
+            .iter()
+            .filter(|s| s.tutor_id == tutor_id && s.overlaps(from, to))
+            .cloned()
+            .collect();
+        listed.sort_by_key(|s| s.starts_at);
+        Ok(listed)
+    }
+
+    async fn delete_session(&self, course_id: Uuid, session_id: Uuid) -> Result<bool, StoreError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|s| !(s.session_id == session_id && s.course_id == course_id));
+        Ok(sessions.len() < before)
+    }
+}
+
+// Up to `limit` of `sessions` still running at `from`, earliest first
+fn upcoming<'a>(
+    sessions: impl Iterator<Item = &'a CourseSession>,
+    from: DateTime<Utc>,
+    limit: i64,
+) -> Vec<CourseSession> {
+    let mut listed: Vec<CourseSession> = sessions.filter(|s| s.ends_at > from).cloned().collect();
+    listed.sort_by_key(|s| s.starts_at);
+    listed.truncate(limit.max(0) as usize);
+    listed
+}
+
+/// Students and their password hashes; `students` is locked before
+/// `passwords`.
+#[derive(Default)]
+pub struct InMemoryStudentStore {
+    students: Mutex<Vec<Student>>,
+    passwords: Mutex<HashMap<Uuid, String>>,
+}
+
+#[async_trait]
+impl StudentStore for InMemoryStudentStore {
+    async fn create(
+        &self,
+        name: String,
+        email: String,
+        password_hash: String,
+    ) -> Result<Student, StoreError> {
+        let mut students = self.students.lock().unwrap();
+        let mut passwords = self.passwords.lock().unwrap();
+        if students.iter().any(|s| s.email == email) {
+            return Err(email_taken("Student", &email));
+        }
+        let new_student = Student::new(name, email);
+        passwords.insert(new_student.student_id, password_hash);
+        students.push(new_student.clone());
+        Ok(new_student)
+    }
+
+    async fn find_credentials(
+        &self,
+        email: &str,
+    ) -> Result<Option<StudentCredentials>, StoreError> {
+        let students = self.students.lock().unwrap();
+        let passwords = self.passwords.lock().unwrap();
+        Ok(students
+            .iter()
+            .find(|s| s.email == email)
+            .map(|s| StudentCredentials {
+                student_id: s.student_id,
+                password_hash: passwords.get(&s.student_id).cloned(),
+            }))
+    }
+
+    async fn find(&self, student_id: Uuid) -> Result<Option<Student>, StoreError> {
+        let students = self.students.lock().unwrap();
+        Ok(students
+            .iter()
+            .find(|s| s.student_id == student_id)
+            .cloned())
+    }
+}
+
+/// Availability and bookings per tutor.
+#[derive(Default)]
+pub struct InMemoryScheduleStore {
+    availability: Mutex<HashMap<Uuid, Availability>>,
+    exceptions: Mutex<Vec<AvailabilityException>>,
+    bookings: Mutex<Vec<Booking>>,
+}
+
+#[async_trait]
+impl ScheduleStore for InMemoryScheduleStore {
+    async fn availability(&self, tutor_id: Uuid) -> Result<Availability, StoreError> {
+        let availability = self.availability.lock().unwrap();
+        Ok(availability.get(&tutor_id).cloned().unwrap_or_default())
+    }
+
+    async fn set_availability(
+        &self,
+        tutor_id: Uuid,
+        availability: Availability,
+    ) -> Result<Availability, StoreError> {
+        let mut stored = self.availability.lock().unwrap();
+        stored.insert(tutor_id, availability.clone());
+        Ok(availability)
+    }
+
+    async fn add_exception(
+        &self,
+        exception: AvailabilityException,
+    ) -> Result<AvailabilityException, StoreError> {
+        let mut exceptions = self.exceptions.lock().unwrap();
+        exceptions.push(exception.clone());
+        Ok(exception)
+    }
+
+    async fn list_exceptions(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityException>, StoreError> {
+        let exceptions = self.exceptions.lock().unwrap();
+        let mut listed: Vec<AvailabilityException> = exceptions
+            .iter()
+            .filter(|e| e.tutor_id == tutor_id && e.starts_at < to && from < e.ends_at)
+            .cloned()
+            .collect();
+        listed.sort_by_key(|e| e.starts_at);
+        Ok(listed)
+    }
+
+    async fn import_exceptions(
+        &self,
+        tutor_id: Uuid,
+        exceptions: Vec<AvailabilityException>,
+        cancelled_uids: &[String],
+    ) -> Result<Vec<AvailabilityException>, StoreError> {
+        let mut stored = self.exceptions.lock().unwrap();
+        let mut imported = Vec::with_capacity(exceptions.len());
+        for exception in exceptions {
+            // Mirrors the unique (tutor_id, ical_uid) index
+            let existing = stored.iter_mut().find(|e| {
+                e.tutor_id == tutor_id && e.ical_uid.is_some() && e.ical_uid == exception.ical_uid
+            });
+            match existing {
+                Some(existing) => {
+                    *existing = AvailabilityException {
+                        exception_id: existing.exception_id,
+                        ..exception
+                    };
+                    imported.push(existing.clone());
+                }
+                None => {
+                    stored.push(exception.clone());
+                    imported.push(exception);
+                }
+            }
+        }
+        stored.retain(|e| {
+            !(e.tutor_id == tutor_id
+                && e.ical_uid
+                    .as_ref()
+                    .is_some_and(|uid| cancelled_uids.contains(uid)))
+        });
+        Ok(imported)
+    }
+
+    async fn delete_exception(
+        &self,
+        tutor_id: Uuid,
+        exception_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let mut exceptions = self.exceptions.lock().unwrap();
+        let before = exceptions.len();
+        exceptions.retain(|e| !(e.exception_id == exception_id && e.tutor_id == tutor_id));
+        Ok(exceptions.len() < before)
+    }
+
+    async fn book(&self, booking: Booking) -> Result<Booking, StoreError> {
+        let mut bookings = self.bookings.lock().unwrap();
+        // Mirrors the `booking_no_overlap` exclusion constraint
+        if bookings.iter().any(|b| {
+            b.tutor_id == booking.tutor_id
+                && b.status == BookingStatus::Confirmed
+                && b.overlaps(booking.starts_at, booking.ends_at)
+        }) {
+            return Err(booking_overlaps(&booking));
+        }
+        bookings.push(booking.clone());
+        Ok(booking)
+    }
+
+    async fn find_booking(&self, booking_id: Uuid) -> Result<Option<Booking>, StoreError> {
+        let bookings = self.bookings.lock().unwrap();
+        Ok(bookings
+            .iter()
+            .find(|b| b.booking_id == booking_id)
+            .cloned())
+    }
+
+    async fn list_bookings(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Booking>, StoreError> {
+        let bookings = self.bookings.lock().unwrap();
+        let mut listed: Vec<Booking> = bookings
+            .iter()
+            .filter(|b| b.tutor_id == tutor_id && b.overlaps(from, to))
+            .cloned()
+            .collect();
+        listed.sort_by_key(|b| b.starts_at);
+        Ok(listed)
+    }
+
+    async fn cancel_booking(&self, booking_id: Uuid) -> Result<Option<Booking>, StoreError> {
+        let mut bookings = self.bookings.lock().unwrap();
+        match bookings
+            .iter_mut()
+            .find(|b| b.booking_id == booking_id && b.status == BookingStatus::Confirmed)
+        {
+            Some(booking) => {
+                booking.status = BookingStatus::Cancelled;
+                booking.cancelled_at = Some(Utc::now());
+                Ok(Some(booking.clone()))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Attendance marks and check-in codes. Summaries read the sessions and
+/// enrolments kept by `courses`.
+pub struct InMemoryAttendanceStore {
+    attendance: Mutex<Vec<Attendance>>,
+    codes: Mutex<HashMap<Uuid, CheckInCode>>,
+    courses: Arc<InMemoryCourseStore>,
+}
+
+impl InMemoryAttendanceStore {
+    pub fn new(courses: Arc<InMemoryCourseStore>) -> Self {
+        InMemoryAttendanceStore {
+            attendance: Mutex::default(),
+            codes: Mutex::default(),
+            courses,
+        }
+    }
+
+    // Tallies `student_id`'s marks over the sessions of `course_id`
+    fn tally(
+        &self,
+        sessions: &[CourseSession],
+        attendance: &[Attendance],
+        course_id: Uuid,
+        student_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> AttendanceTally {
+        let course_sessions: Vec<&CourseSession> = sessions
+            .iter()
+            .filter(|s| s.course_id == course_id)
+            .collect();
+        let count = |status: AttendanceStatus| {
+            attendance
+                .iter()
+                .filter(|a| a.student_id == student_id && a.status == status)
+                .filter(|a| course_sessions.iter().any(|s| s.session_id == a.session_id))
+                .count() as i64
+        };
+        AttendanceTally {
+            course_id,
+            student_id,
+            sessions_held: course_sessions
+                .iter()
+                .filter(|s| s.starts_at <= now)
+                .count() as i64,
+            present: count(AttendanceStatus::Present),
+            absent: count(AttendanceStatus::Absent),
+            late: count(AttendanceStatus::Late),
+            excused: count(AttendanceStatus::Excused),
+        }
+    }
+}
+
+#[async_trait]
+impl AttendanceStore for InMemoryAttendanceStore {
+    async fn mark(
+        &self,
+        session_id: Uuid,
+        marks: Vec<AttendanceMark>,
+    ) -> Result<Vec<Attendance>, StoreError> {
+        let mut attendance = self.attendance.lock().unwrap();
+        let mut marked = Vec::with_capacity(marks.len());
+        for mark in marks {
+            let recorded = Attendance {
+                session_id,
+                student_id: mark.student_id,
+                status: mark.status,
+                checked_in: false,
+                marked_at: Utc::now(),
+            };
+            attendance.retain(|a| !(a.session_id == session_id && a.student_id == mark.student_id));
+            attendance.push(recorded.clone());
+            marked.push(recorded);
+        }
+        Ok(marked)
+    }
+
+    async fn list(&self, session_id: Uuid) -> Result<Vec<Attendance>, StoreError> {
+        let attendance = self.attendance.lock().unwrap();
+        let mut listed: Vec<Attendance> = attendance
+            .iter()
+            .filter(|a| a.session_id == session_id)
+            .cloned()
+            .collect();
+        listed.sort_by_key(|a| a.marked_at);
+        Ok(listed)
+    }
+
+    async fn open_check_in(&self, code: CheckInCode) -> Result<CheckInCode, StoreError> {
+        let mut codes = self.codes.lock().unwrap();
+        codes.insert(code.session_id, code.clone());
+        Ok(code)
+    }
+
+    async fn check_in(
+        &self,
+        session_id: Uuid,
+        student_id: Uuid,
+        code: &str,
+        status: AttendanceStatus,
+    ) -> Result<Option<Attendance>, StoreError> {
+        let codes = self.codes.lock().unwrap();
+        let live = codes
+            .get(&session_id)
+            .is_some_and(|c| c.code == code && Utc::now() < c.expires_at);
+        if !live {
+            return Ok(None);
+        }
+
+        let mut attendance = self.attendance.lock().unwrap();
+        if let Some(marked) = attendance
+            .iter()
+            .find(|a| a.session_id == session_id && a.student_id == student_id)
+        {
+            return Ok(Some(marked.clone()));
+        }
+        let checked_in = Attendance {
+            session_id,
+            student_id,
+            status,
+            checked_in: true,
+            marked_at: Utc::now(),
+        };
+        attendance.push(checked_in.clone());
+        Ok(Some(checked_in))
+    }
+
+    async fn course_summary(
+        &self,
+        course_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceTally>, StoreError> {
+        let sessions = self.courses.sessions.lock().unwrap().clone();
+        let attendance = self.attendance.lock().unwrap();
+        let mut student_ids: Vec<Uuid> = self
+            .courses
+            .enrollments
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.course_id == course_id)
+            .map(|e| e.student_id)
+            .chain(
+                attendance
+                    .iter()
+                    .filter(|a| {
+                        sessions
+                            .iter()
+                            .any(|s| s.session_id == a.session_id && s.course_id == course_id)
+                    })
+                    .map(|a| a.student_id),
+            )
+            .collect();
+        // Mirrors `ORDER BY student_id`
+        student_ids.sort();
+        student_ids.dedup();
+        Ok(student_ids
+            .into_iter()
+            .map(|student_id| self.tally(&sessions, &attendance, course_id, student_id, now))
+            .collect())
+    }
+
+    async fn student_summary(
+        &self,
+        student_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceTally>, StoreError> {
+        let sessions = self.courses.sessions.lock().unwrap().clone();
+        let attendance = self.attendance.lock().unwrap();
+        let mut course_ids: Vec<Uuid> = self
+            .courses
+            .enrollments
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.student_id == student_id)
+            .map(|e| e.course_id)
+            .chain(
+                attendance
+                    .iter()
+                    .filter(|a| a.student_id == student_id)
+                    .filter_map(|a| sessions.iter().find(|s| s.session_id == a.session_id))
+                    .map(|s| s.course_id),
+            )
+            .collect();
+        course_ids.sort();
+        course_ids.dedup();
+        Ok(course_ids
+            .into_iter()
+            .map(|course_id| self.tally(&sessions, &attendance, course_id, student_id, now))
+            .collect())
+    }
+}
+
+/// Modules and lessons. Methods touching both lock `modules` first.
+#[derive(Default)]
+pub struct InMemoryCurriculumStore {
+    modules: Mutex<Vec<CourseModule>>,
+    lessons: Mutex<Vec<Lesson>>,
+    materials: Arc<InMemoryMaterialStore>,
+}
+
+impl InMemoryCurriculumStore {
+    pub fn new(materials: Arc<InMemoryMaterialStore>) -> Self {
+        InMemoryCurriculumStore {
+            modules: Mutex::default(),
+            lessons: Mutex::default(),
+            materials,
+        }
+    }
+
+    // Mirrors the cascade from course to module to lesson
+    fn remove_course(&self, course_id: Uuid) {
+        let mut modules = self.modules.lock().unwrap();
+        let module_ids: Vec<Uuid> = modules
+            .iter()
+            .filter(|m| m.course_id == course_id)
+            .map(|m| m.module_id)
+            .collect();
+        modules.retain(|m| m.course_id != course_id);
+        self.remove_lessons(|l| module_ids.contains(&l.module_id));
+    }
+
+    // Mirrors the cascade from lesson to material
+    fn remove_lessons(&self, removed: impl Fn(&Lesson) -> bool) {
+        let mut lessons = self.lessons.lock().unwrap();
+        let lesson_ids: Vec<Uuid> = lessons
+            .iter()
+            .filter(|l| removed(l))
+            .map(|l| l.lesson_id)
+            .collect();
+        lessons.retain(|l| !lesson_ids.contains(&l.lesson_id));
+        self.materials
+            .materials
+            .lock()
+            .unwrap()
+            .retain(|m| !lesson_ids.contains(&m.lesson_id));
+    }
+}
+
+// Mirrors `reorder_*` in the curriculum repository: `ids` must be exactly
+// the ids of `items`, which then take the places `ids` gives them
+fn reorder<T: Clone>(
+    items: &mut [T],
+    ids: &[Uuid],
+    id_of: impl Fn(&T) -> Uuid,
+    position_of: impl Fn(&mut T) -> &mut i32,
+) -> Option<Vec<T>> {
+    if items.len() != ids.len() || !items.iter().all(|item| ids.contains(&id_of(item))) {
+        return None;
+    }
+    for item in items.iter_mut() {
+        let position = ids
+            .iter()
+            .position(|id| *id == id_of(item))
+            .expect("checked above");
+        *position_of(item) = position as i32;
+    }
+    let mut reordered = items.to_vec();
+    reordered.sort_by_key(|item| ids.iter().position(|id| *id == id_of(item)));
+    Some(reordered)
+}
+
+#[async_trait]
+impl CurriculumStore for InMemoryCurriculumStore {
+    async fn add_module(&self, mut module: CourseModule) -> Result<CourseModule, StoreError> {
+        let mut modules = self.modules.lock().unwrap();
+        let course_modules = modules.iter().filter(|m| m.course_id == module.course_id);
+        if course_modules.clone().count() >= MAX_CURRICULUM_ITEMS {
+            return Err(too_many_modules(module.course_id));
+        }
+        module.position = course_modules.map(|m| m.position + 1).max().unwrap_or(0);
+        modules.push(module.clone());
+        Ok(module)
+    }
+
+    async fn list_modules(&self, course_id: Uuid) -> Result<Vec<CourseModule>, StoreError> {
+        let modules = self.modules.lock().unwrap();
+        let mut listed: Vec<CourseModule> = modules
+            .iter()
+            .filter(|m| m.course_id == course_id)
+            .cloned()
+            .collect();
+        listed.sort_by_key(|m| m.position);
+        Ok(listed)
+    }
+
+    async fn rename_module(
+        &self,
+        course_id: Uuid,
+        module_id: Uuid,
+        title: String,
+    ) -> Result<Option<CourseModule>, StoreError> {
+        let mut modules = self.modules.lock().unwrap();
+        let Some(module) = modules
+            .iter_mut()
+            .find(|m| m.module_id == module_id && m.course_id == course_id)
+        else {
+            return Ok(None);
+        };
+        module.title = title;
+        Ok(Some(module.clone()))
+    }
+
+    async fn delete_module(&self, course_id: Uuid, module_id: Uuid) -> Result<bool, StoreError> {
+        let mut modules = self.modules.lock().unwrap();
+        let before = modules.len();
+        modules.retain(|m| !(m.module_id == module_id && m.course_id == course_id));
+        if modules.len() == before {
+            return Ok(false);
+        }
+        // Mirrors ON DELETE CASCADE
+        self.remove_lessons(|l| l.module_id == module_id);
+        Ok(true)
+    }
+
+    async fn reorder_modules(
+        &self,
+        course_id: Uuid,
+        module_ids: Vec<Uuid>,
+    ) -> Result<Option<Vec<CourseModule>>, StoreError> {
+        let mut modules = self.modules.lock().unwrap();
+        let mut course_modules: Vec<CourseModule> = modules
+            .iter()
+            .filter(|m| m.course_id == course_id)
+            .cloned()
+            .collect();
+        let Some(reordered) = reorder(
+            &mut course_modules,
+            &module_ids,
+            |m| m.module_id,
+            |m| &mut m.position,
+        ) else {
+            return Ok(None);
+        };
+        modules.retain(|m| m.course_id != course_id);
+        modules.extend(course_modules);
+        Ok(Some(reordered))
+    }
+
+    async fn add_lesson(&self, mut lesson: Lesson) -> Result<Lesson, StoreError> {
+        let mut lessons = self.lessons.lock().unwrap();
+        let module_lessons = lessons.iter().filter(|l| l.module_id == lesson.module_id);
+        if module_lessons.clone().count() >= MAX_CURRICULUM_ITEMS {
+            return Err(too_many_lessons(lesson.module_id));
+        }
+        lesson.position = module_lessons.map(|l| l.position + 1).max().unwrap_or(0);
+        lesson.updated_at = Utc::now();
+        lessons.push(lesson.clone());
+        Ok(lesson)
+    }
+
+    async fn update_lesson(&self, lesson: Lesson) -> Result<Option<Lesson>, StoreError> {
+        let mut lessons = self.lessons.lock().unwrap();
+        let Some(stored) = lessons
+            .iter_mut()
+            .find(|l| l.lesson_id == lesson.lesson_id && l.module_id == lesson.module_id)
+        else {
+            return Ok(None);
+        };
+        stored.title = lesson.title;
+        stored.body = lesson.body;
+        stored.duration_minutes = lesson.duration_minutes;
+        stored.published = lesson.published;
+        stored.updated_at = Utc::now();
+        Ok(Some(stored.clone()))
+    }
+
+    async fn delete_lesson(&self, module_id: Uuid, lesson_id: Uuid) -> Result<bool, StoreError> {
+        let exists = self
+            .lessons
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|l| l.lesson_id == lesson_id && l.module_id == module_id);
+        if exists {
+            self.remove_lessons(|l| l.lesson_id == lesson_id);
+        }
+        Ok(exists)
+    }
+
+    async fn list_lessons(
+        &self,
+        course_id: Uuid,
+        published_only: bool,
+    ) -> Result<Vec<Lesson>, StoreError> {
+        let modules = self.modules.lock().unwrap();
+        let lessons = self.lessons.lock().unwrap();
+        let module_position = |module_id: Uuid| {
+            modules
+                .iter()
+                .find(|m| m.module_id == module_id && m.course_id == course_id)
+                .map(|m| m.position)
+        };
+        let mut listed: Vec<Lesson> = lessons
+            .iter()
+            .filter(|l| module_position(l.module_id).is_some() && (l.published || !published_only))
+            .cloned()
+            .collect();
+        listed.sort_by_key(|l| (module_position(l.module_id), l.position));
+        Ok(listed)
+    }
+
+    async fn reorder_lessons(
+        &self,
+        module_id: Uuid,
+        lesson_ids: Vec<Uuid>,
+    ) -> Result<Option<Vec<Lesson>>, StoreError> {
+        let mut lessons = self.lessons.lock().unwrap();
+        let mut module_lessons: Vec<Lesson> = lessons
+            .iter()
+            .filter(|l| l.module_id == module_id)
+            .cloned()
+            .collect();
+        let Some(reordered) = reorder(
+            &mut module_lessons,
+            &lesson_ids,
+            |l| l.lesson_id,
+            |l| &mut l.position,
+        ) else {
+            return Ok(None);
+        };
+        lessons.retain(|l| l.module_id != module_id);
+        lessons.extend(module_lessons);
+        Ok(Some(reordered))
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryMaterialStore {
+    materials: Mutex<Vec<Material>>,
+}
+
+#[async_trait]
+impl MaterialStore for InMemoryMaterialStore {
+    async fn attach(&self, material: Material) -> Result<AttachOutcome, StoreError> {
+        let mut materials = self.materials.lock().unwrap();
+        // Mirrors the `lesson_material_content` constraint
+        if let Some(existing) = materials
+            .iter()
+            .find(|m| m.lesson_id == material.lesson_id && m.sha256 == material.sha256)
+        {
+            return Ok(AttachOutcome::AlreadyAttached(existing.clone()));
+        }
+        materials.push(material.clone());
+        Ok(AttachOutcome::Attached(material))
+    }
+
+    async fn list(&self, lesson_id: Uuid) -> Result<Vec<Material>, StoreError> {
+        let materials = self.materials.lock().unwrap();
+        Ok(materials
+            .iter()
+            .filter(|m| m.lesson_id == lesson_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_for_lessons(&self, lesson_ids: &[Uuid]) -> Result<Vec<Material>, StoreError> {
+        let materials = self.materials.lock().unwrap();
+        Ok(materials
+            .iter()
+            .filter(|m| lesson_ids.contains(&m.lesson_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn find(
+        &self,
+        lesson_id: Uuid,
+        material_id: Uuid,
+    ) -> Result<Option<Material>, StoreError> {
+        let materials = self.materials.lock().unwrap();
+        Ok(materials
+            .iter()
+            .find(|m| m.material_id == material_id && m.lesson_id == lesson_id)
+            .cloned())
+    }
+
+    async fn detach(
+        &self,
+        lesson_id: Uuid,
+        material_id: Uuid,
+    ) -> Result<Option<Material>, StoreError> {
+        let mut materials = self.materials.lock().unwrap();
+        let Some(index) = materials
+            .iter()
+            .position(|m| m.material_id == material_id && m.lesson_id == lesson_id)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(materials.remove(index)))
+    }
+
+    async fn content_in_use(&self, sha256: &str) -> Result<bool, StoreError> {
+        let materials = self.materials.lock().unwrap();
+        Ok(materials.iter().any(|m| m.sha256 == sha256))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn deleting_a_course_removes_its_curriculum() {
+        let curriculum = Arc::new(InMemoryCurriculumStore::default());
+        let courses = InMemoryCourseStore::new(Arc::default(), curriculum.clone());
+        let kept = courses
+            .add(Course::with_current_time(
+                Uuid::new_v4(),
+                "Kept".to_string(),
+            ))
+            .await
+            .unwrap();
+        let deleted = courses
+            .add(Course::with_current_time(
+                Uuid::new_v4(),
+                "Deleted".to_string(),
+            ))
+            .await
+            .unwrap();
+        for course in [&kept, &deleted] {
+            let module = CourseModule {
+                module_id: Uuid::new_v4(),
+                course_id: course.course_id,
+                title: "Basics".to_string(),
+                position: 0,
+            };
+            let module = curriculum.add_module(module).await.unwrap();
+            let lesson = Lesson {
+                lesson_id: Uuid::new_v4(),
+                module_id: module.module_id,
+                title: "Welcome".to_string(),
+                body: String::new(),
+                duration_minutes: None,
+                position: 0,
+                published: true,
+                updated_at: Utc::now(),
+            };
+            curriculum.add_lesson(lesson).await.unwrap();
+        }
+
+        courses.delete(deleted.course_id).await.unwrap();
+        assert!(
+            curriculum
+                .list_modules(deleted.course_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            curriculum
+                .list_lessons(deleted.course_id, false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            curriculum.list_modules(kept.course_id).await.unwrap().len(),
+            1
+        );
+        assert_eq!(curriculum.lessons.lock().unwrap().len(), 1);
+    }
+}
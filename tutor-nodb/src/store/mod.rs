@@ -0,0 +1,486 @@
+use super::auth::Role;
+use super::models::{
+    Attendance, AttendanceMark, AttendanceStatus, AttendanceTally, Availability,
+    AvailabilityException, Booking, CheckInCode, Course, CourseModule, CoursePage, CourseQuery,
+    CourseSession, Enrollment, Lesson, MAX_CURRICULUM_ITEMS, Material, Review, SearchHit, Student,
+    Tutor, WaitlistEntry,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use uuid::Uuid;
+
+mod memory;
+mod postgres;
+
+pub use memory::{
+    InMemoryAttendanceStore, InMemoryAuditStore, InMemoryCourseStore, InMemoryCurriculumStore,
+    InMemoryMaterialStore, InMemoryScheduleStore, InMemoryStudentStore, InMemoryTutorStore,
+};
+pub use postgres::{
+    PgAttendanceStore, PgAuditStore, PgCourseStore, PgCurriculumStore, PgMaterialStore,
+    PgScheduleStore, PgStudentStore, PgTutorStore,
+};
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// A uniqueness or referential constraint rejected the write.
+    Conflict(String),
+    /// A row the operation depends on disappeared underneath it.
+    NotFound(String),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Conflict(msg) | StoreError::NotFound(msg) => write!(f, "{msg}"),
+            StoreError::Database(e) => write!(f, "Database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+// What callers are told when a constraint rejects a write. Postgres's own
+// message names tables and constraints, so it is only logged.
+fn constraint_message(constraint: Option<&str>) -> &'static str {
+    match constraint {
+        Some("tutor_email_key") => "A tutor with this email already exists",
+        Some("student_email_key") => "A student with this email already exists",
+        Some(c) if c.ends_with("_fkey") => "A record this change refers to does not exist",
+        _ => "The change conflicts with existing data",
+    }
+}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::Database(db_err)
+                if db_err.is_unique_violation() || db_err.is_foreign_key_violation() =>
+            {
+                eprintln!("Constraint violation: {}", db_err.message());
+                StoreError::Conflict(constraint_message(db_err.constraint()).to_string())
+            }
+            _ => StoreError::Database(e),
+        }
+    }
+}
+
+/// Where an enrolment request put the student.
+#[derive(Debug)]
+pub enum EnrollOutcome {
+    Enrolled(Enrollment),
+    /// The course was full, so the student joined its waitlist.
+    Waitlisted(WaitlistEntry),
+}
+
+/// What attaching a file to a lesson did.
+#[derive(Debug)]
+pub enum AttachOutcome {
+    Attached(Material),
+    /// The lesson already had a file with the same content, returned here.
+    AlreadyAttached(Material),
+}
+
+/// What posting a review did.
+#[derive(Debug)]
+pub enum ReviewOutcome {
+    Created(Review),
+    /// The student's earlier review of the course was replaced.
+    Updated(Review),
+    /// Only enrolled students may review a course.
+    NotEnrolled,
+}
+
+/// What login checks a password against.
+#[derive(Debug, Clone)]
+pub struct TutorCredentials {
+    pub tutor_id: Uuid,
+    /// `None` for tutors created without a password, who cannot log in.
+    pub password_hash: Option<String>,
+    /// `Tutor` or `Admin`.
+    pub role: Role,
+}
+
+#[derive(Debug, Clone)]
+pub struct StudentCredentials {
+    pub student_id: Uuid,
+    pub password_hash: Option<String>,
+}
+
+#[async_trait]
+pub trait TutorStore: Send + Sync {
+    /// Fails with `StoreError::Conflict` if the email is already registered.
+    async fn create(
+        &self,
+        name: String,
+        email: String,
+        password_hash: String,
+    ) -> Result<Tutor, StoreError>;
+
+    async fn find_credentials(&self, email: &str) -> Result<Option<TutorCredentials>, StoreError>;
+
+    async fn find_role(&self, tutor_id: Uuid) -> Result<Option<Role>, StoreError>;
+
+    /// Makes the tutor an admin or back; `false` if the tutor does not exist.
+    async fn set_role(&self, tutor_id: Uuid, role: Role) -> Result<bool, StoreError>;
+
+    async fn find(&self, tutor_id: Uuid) -> Result<Option<Tutor>, StoreError>;
+
+    /// Up to `limit` tutors whose email contains `fragment`, ignoring case,
+    /// ordered by email.
+    async fn search_by_email(&self, fragment: &str, limit: i64) -> Result<Vec<Tutor>, StoreError>;
+
+    /// Overwrites the stored tutor with the same id, `None` if it is gone.
+    async fn update(&self, tutor: Tutor) -> Result<Option<Tutor>, StoreError>;
+
+    /// Removes a tutor, returning it or `None` if it did not exist.
+    async fn delete(&self, tutor_id: Uuid) -> Result<Option<Tutor>, StoreError>;
+}
+
+#[async_trait]
+pub trait CourseStore: Send + Sync {
+    async fn add(&self, course: Course) -> Result<Course, StoreError>;
+
+    async fn find(&self, course_id: Uuid) -> Result<Option<Course>, StoreError>;
+
+    async fn list_for_tutor(&self, tutor_id: Uuid) -> Result<Vec<Course>, StoreError>;
+
+    /// The page of matching courses the query asks for, in its sort order.
+    async fn list(&self, query: &CourseQuery) -> Result<CoursePage, StoreError>;
+
+    /// Up to `limit` courses whose name or description match `query`, best
+    /// match first.
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<SearchHit>, StoreError>;
+
+    /// Overwrites the stored course with the same id, `None` if it is gone.
+    async fn update(&self, course: Course) -> Result<Option<Course>, StoreError>;
+
+    /// Removes a course, returning it or `None` if it did not exist.
+    async fn delete(&self, course_id: Uuid) -> Result<Option<Course>, StoreError>;
+
+    /// Takes a seat atomically, or joins the waitlist when the course is
+    /// full. Fails with `StoreError::Conflict` when the student is already
+    /// enrolled or waiting.
+    async fn enroll(&self, course_id: Uuid, student_id: Uuid) -> Result<EnrollOutcome, StoreError>;
+
+    /// Frees the student's seat, handing it to the head of the waitlist, or
+    /// takes them off the waitlist. `false` if they were in neither.
+    async fn withdraw(&self, course_id: Uuid, student_id: Uuid) -> Result<bool, StoreError>;
+
+    /// The students holding a seat, earliest enrolment first.
+    async fn list_enrolled(&self, course_id: Uuid) -> Result<Vec<Enrollment>, StoreError>;
+
+    /// The course's waitlist, first in line first.
+    async fn list_waitlist(&self, course_id: Uuid) -> Result<Vec<WaitlistEntry>, StoreError>;
+
+    /// Creates or replaces the student's review and recomputes the course and
+    /// tutor ratings together. Fails with `StoreError::NotFound` if the
+    /// course is gone.
+    async fn post_review(
+        &self,
+        course_id: Uuid,
+        student_id: Uuid,
+        rating: i16,
+        body: String,
+    ) -> Result<ReviewOutcome, StoreError>;
+
+    /// The course's reviews, most recently written first.
+    async fn list_reviews(&self, course_id: Uuid) -> Result<Vec<Review>, StoreError>;
+
+    /// Saves every session, or none of them with `StoreError::Conflict` if
+    /// one overlaps another of the tutor's sessions, however requests race.
+    async fn schedule_sessions(
+        &self,
+        sessions: Vec<CourseSession>,
+    ) -> Result<Vec<CourseSession>, StoreError>;
+
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<CourseSession>, StoreError>;
+
+    /// Up to `limit` of the course's sessions still running at `from`,
+    /// earliest first.
+    async fn list_sessions(
+        &self,
+        course_id: Uuid,
+        from: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CourseSession>, StoreError>;
+
+    /// Like `list_sessions`, across the courses the student holds a seat in.
+    async fn list_student_sessions(
+        &self,
+        student_id: Uuid,
+        from: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CourseSession>, StoreError>;
+
+    /// The tutor's sessions overlapping `[from, to)`, earliest first.
+    async fn list_tutor_sessions(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CourseSession>, StoreError>;
+
+    /// `false` if the course has no such session.
+    async fn delete_session(&self, course_id: Uuid, session_id: Uuid) -> Result<bool, StoreError>;
+}
+
+#[async_trait]
+pub trait StudentStore: Send + Sync {
+    /// Fails with `StoreError::Conflict` if the email is already registered.
+    async fn create(
+        &self,
+        name: String,
+        email: String,
+        password_hash: String,
+    ) -> Result<Student, StoreError>;
+
+    async fn find_credentials(&self, email: &str)
+    -> Result<Option<StudentCredentials>, StoreError>;
+
+    async fn find(&self, student_id: Uuid) -> Result<Option<Student>, StoreError>;
+}
+
+/// Tutors' availability and the bookings made against it. Callers check
+/// that tutors and students exist first.
+#[async_trait]
+pub trait ScheduleStore: Send + Sync {
+    /// The tutor's weekly slots, or none in UTC if they never set any.
+    async fn availability(&self, tutor_id: Uuid) -> Result<Availability, StoreError>;
+
+    /// Replaces the tutor's weekly slots.
+    async fn set_availability(
+        &self,
+        tutor_id: Uuid,
+        availability: Availability,
+    ) -> Result<Availability, StoreError>;
+
+    async fn add_exception(
+        &self,
+        exception: AvailabilityException,
+    ) -> Result<AvailabilityException, StoreError>;
+
+    /// The tutor's exceptions overlapping `[from, to)`, earliest first.
+    async fn list_exceptions(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityException>, StoreError>;
+
+    /// Saves exceptions imported from a calendar, replacing earlier imports
+    /// with the same `ical_uid`, and deletes the imports whose event was
+    /// cancelled, all at once.
+    async fn import_exceptions(
+        &self,
+        tutor_id: Uuid,
+        exceptions: Vec<AvailabilityException>,
+        cancelled_uids: &[String],
+    ) -> Result<Vec<AvailabilityException>, StoreError>;
+
+    /// `false` if the tutor has no such exception.
+    async fn delete_exception(
+        &self,
+        tutor_id: Uuid,
+        exception_id: Uuid,
+    ) -> Result<bool, StoreError>;
+
+    /// Fails with `StoreError::Conflict` if one of the tutor's confirmed
+    /// bookings overlaps, however concurrent requests race.
+    async fn book(&self, booking: Booking) -> Result<Booking, StoreError>;
+
+    async fn find_booking(&self, booking_id: Uuid) -> Result<Option<Booking>, StoreError>;
+
+    /// The tutor's bookings overlapping `[from, to)`, cancelled ones
+    /// included, earliest first.
+    async fn list_bookings(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Booking>, StoreError>;
+
+    /// Cancels a confirmed booking; `None` if it is missing or was already
+    /// cancelled.
+    async fn cancel_booking(&self, booking_id: Uuid) -> Result<Option<Booking>, StoreError>;
+}
+
+/// Append-only record of sensitive admin actions.
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    /// `action` names what was done, e.g. `tutor.search`; `detail` says to
+    /// what.
+    async fn record(&self, actor_id: Uuid, action: &str, detail: &str) -> Result<(), StoreError>;
+}
+
+/// Who attended which course session. Callers check that sessions and
+/// students exist first.
+#[async_trait]
+pub trait AttendanceStore: Send + Sync {
+    /// Records or overwrites each student's mark for the session, all or
+    /// none.
+    async fn mark(
+        &self,
+        session_id: Uuid,
+        marks: Vec<AttendanceMark>,
+    ) -> Result<Vec<Attendance>, StoreError>;
+
+    /// Everyone marked or checked in for the session, earliest first.
+    async fn list(&self, session_id: Uuid) -> Result<Vec<Attendance>, StoreError>;
+
+    /// Replaces the session's check-in code.
+    async fn open_check_in(&self, code: CheckInCode) -> Result<CheckInCode, StoreError>;
+
+    /// Checks the student in with `status` if `code` is the session's live
+    /// code, `None` if it is not. A mark the tutor already gave is kept.
+    async fn check_in(
+        &self,
+        session_id: Uuid,
+        student_id: Uuid,
+        code: &str,
+        status: AttendanceStatus,
+    ) -> Result<Option<Attendance>, StoreError>;
+
+    /// A tally per student enrolled in, or marked for, the course, counting
+    /// the sessions started by `now`.
+    async fn course_summary(
+        &self,
+        course_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceTally>, StoreError>;
+
+    /// A tally per course the student is enrolled in or was marked for.
+    async fn student_summary(
+        &self,
+        student_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceTally>, StoreError>;
+}
+
+/// A course's modules and their lessons.
+#[async_trait]
+pub trait CurriculumStore: Send + Sync {
+    /// Adds the module after the course's others, ignoring its `position`.
+    /// Conflicts once the course has `MAX_CURRICULUM_ITEMS` modules.
+    async fn add_module(&self, module: CourseModule) -> Result<CourseModule, StoreError>;
+
+    /// The course's modules in order.
+    async fn list_modules(&self, course_id: Uuid) -> Result<Vec<CourseModule>, StoreError>;
+
+    /// `None` if the course has no such module.
+    async fn rename_module(
+        &self,
+        course_id: Uuid,
+        module_id: Uuid,
+        title: String,
+    ) -> Result<Option<CourseModule>, StoreError>;
+
+    /// Deletes the module and its lessons; false if the course has no such
+    /// module.
+    async fn delete_module(&self, course_id: Uuid, module_id: Uuid) -> Result<bool, StoreError>;
+
+    /// Puts the course's modules in the order of `module_ids`, or returns
+    /// `None` without moving any if they are not exactly the course's
+    /// modules.
+    async fn reorder_modules(
+        &self,
+        course_id: Uuid,
+        module_ids: Vec<Uuid>,
+    ) -> Result<Option<Vec<CourseModule>>, StoreError>;
+
+    /// Adds the lesson after the module's others, ignoring its `position`.
+    /// Conflicts once the module has `MAX_CURRICULUM_ITEMS` lessons.
+    async fn add_lesson(&self, lesson: Lesson) -> Result<Lesson, StoreError>;
+
+    /// Saves the lesson's title, body, duration and published flag; `None`
+    /// if its module has no such lesson.
+    async fn update_lesson(&self, lesson: Lesson) -> Result<Option<Lesson>, StoreError>;
+
+    /// False if the module has no such lesson.
+    async fn delete_lesson(&self, module_id: Uuid, lesson_id: Uuid) -> Result<bool, StoreError>;
+
+    /// The course's lessons, or only its published ones, ordered by module
+    /// and then by lesson.
+    async fn list_lessons(
+        &self,
+        course_id: Uuid,
+        published_only: bool,
+    ) -> Result<Vec<Lesson>, StoreError>;
+
+    /// Like `reorder_modules`, for the module's lessons, drafts included.
+    async fn reorder_lessons(
+        &self,
+        module_id: Uuid,
+        lesson_ids: Vec<Uuid>,
+    ) -> Result<Option<Vec<Lesson>>, StoreError>;
+}
+
+/// Which files are attached to which lessons; the bytes themselves are in
+/// the blob store.
+#[async_trait]
+pub trait MaterialStore: Send + Sync {
+    /// Attaches the file unless its lesson already has the same content.
+    async fn attach(&self, material: Material) -> Result<AttachOutcome, StoreError>;
+
+    /// The lesson's materials, oldest first.
+    async fn list(&self, lesson_id: Uuid) -> Result<Vec<Material>, StoreError>;
+
+    /// The materials of all of the lessons, e.g. before deleting them.
+    async fn list_for_lessons(&self, lesson_ids: &[Uuid]) -> Result<Vec<Material>, StoreError>;
+
+    async fn find(
+        &self,
+        lesson_id: Uuid,
+        material_id: Uuid,
+    ) -> Result<Option<Material>, StoreError>;
+
+    /// Detaches and returns the material, `None` if the lesson has no such
+    /// material.
+    async fn detach(
+        &self,
+        lesson_id: Uuid,
+        material_id: Uuid,
+    ) -> Result<Option<Material>, StoreError>;
+
+    /// Whether any lesson still has a file with this content.
+    async fn content_in_use(&self, sha256: &str) -> Result<bool, StoreError>;
+}
+
+pub(crate) fn already_enrolled(course_id: Uuid, student_id: Uuid) -> StoreError {
+    StoreError::Conflict(format!(
+        "Student {student_id} is already enrolled in course {course_id}"
+    ))
+}
+
+pub(crate) fn already_waitlisted(course_id: Uuid, student_id: Uuid) -> StoreError {
+    StoreError::Conflict(format!(
+        "Student {student_id} is already on the waitlist for course {course_id}"
+    ))
+}
+
+pub(crate) fn too_many_modules(course_id: Uuid) -> StoreError {
+    StoreError::Conflict(format!(
+        "Course {course_id} already has the most modules allowed, {MAX_CURRICULUM_ITEMS}"
+    ))
+}
+
+pub(crate) fn too_many_lessons(module_id: Uuid) -> StoreError {
+    StoreError::Conflict(format!(
+        "Module {module_id} already has the most lessons allowed, {MAX_CURRICULUM_ITEMS}"
+    ))
+}
+
+pub(crate) fn sessions_overlap(tutor_id: Uuid) -> StoreError {
+    StoreError::Conflict(format!(
+        "Tutor {tutor_id} already has a session overlapping one of these"
+    ))
+}
+
+pub(crate) fn booking_overlaps(booking: &Booking) -> StoreError {
+    StoreError::Conflict(format!(
+        "Tutor {} already has a booking overlapping {} to {}",
+        booking.tutor_id, booking.starts_at, booking.ends_at
+    ))
+}
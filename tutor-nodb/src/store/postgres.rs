@@ -0,0 +1,1208 @@
+use super::{
+    AttachOutcome, AttendanceStore, AuditStore, CourseStore, CurriculumStore, EnrollOutcome,
+    MaterialStore, ReviewOutcome, ScheduleStore, StoreError, StudentCredentials, StudentStore,
+    TutorCredentials, TutorStore, already_enrolled, already_waitlisted, booking_overlaps,
+    sessions_overlap, too_many_lessons, too_many_modules,
+};
+use crate::auth::Role;
+use crate::models::{
+    Attendance, AttendanceMark, AttendanceStatus, AttendanceTally, Availability,
+    AvailabilityException, AvailabilityKind, Booking, BookingStatus, CheckInCode, Course,
+    CourseModule, CoursePage, CourseQuery, CourseSession, CourseSort, CourseType, Enrollment,
+    Highlight, Lesson, MAX_CURRICULUM_ITEMS, Material, Review, SearchHit, SortOrder, Student,
+    Tutor, WaitlistEntry, WeeklySlot,
+};
+use async_trait::async_trait;
+use bigdecimal::ToPrimitive;
+use chrono::{DateTime, Utc, Weekday};
+use sqlx::PgPool;
+use tutordb::models::attendance::{
+    Attendance as DbAttendance, AttendanceStatus as DbAttendanceStatus,
+    AttendanceTally as DbAttendanceTally, CheckInCode as DbCheckInCode, CheckInOutcome,
+};
+use tutordb::models::availability::{
+    AvailabilityException as DbAvailabilityException, AvailabilityKind as DbAvailabilityKind,
+    AvailabilityRule,
+};
+use tutordb::models::booking::{
+    Booking as DbBooking, BookingOutcome, BookingStatus as DbBookingStatus,
+};
+use tutordb::models::course_session::{CourseSession as DbCourseSession, ScheduleOutcome};
+use tutordb::models::courses::{
+    Course as DbCourse, CourseFilter, CourseSort as DbCourseSort, CourseType as DbCourseType,
+    PageRequest,
+};
+use tutordb::models::curriculum::{
+    CourseModule as DbCourseModule, Lesson as DbLesson, ReorderOutcome,
+};
+use tutordb::models::enrollment::{EnrollOutcome as DbEnrollOutcome, Enrollment as DbEnrollment};
+use tutordb::models::material::{AttachOutcome as DbAttachOutcome, Material as DbMaterial};
+use tutordb::models::review::{Review as DbReview, ReviewOutcome as DbReviewOutcome};
+use tutordb::models::role::Role as DbRole;
+use tutordb::models::student::Student as DbStudent;
+use tutordb::models::tutor::Tutor as DbTutor;
+use tutordb::models::waitlist::WaitlistEntry as DbWaitlistEntry;
+use tutordb::repositories::{
+    attendance_repository, audit_repository, availability_repository, booking_repository,
+    course_repository, course_session_repository, curriculum_repository, enrollment_repository,
+    material_repository, review_repository, student_repository, tutor_repository,
+};
+use uuid::Uuid;
+
+impl From<DbTutor> for Tutor {
+    fn from(tutor: DbTutor) -> Self {
+        Tutor {
+            name: tutor.name,
+            email: tutor.email,
+            tutor_id: tutor.id,
+            rating: tutor.rating.to_f64().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Tutor> for DbTutor {
+    fn from(tutor: Tutor) -> Self {
+        let mut db_tutor = DbTutor::new(tutor.name, tutor.email);
+        db_tutor.id = tutor.tutor_id;
+        db_tutor
+    }
+}
+
+impl From<DbRole> for Role {
+    fn from(role: DbRole) -> Self {
+        match role {
+            DbRole::Admin => Role::Admin,
+            DbRole::Tutor => Role::Tutor,
+            DbRole::Student => Role::Student,
+        }
+    }
+}
+
+impl From<Role> for DbRole {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Admin => DbRole::Admin,
+            Role::Tutor => DbRole::Tutor,
+            Role::Student => DbRole::Student,
+        }
+    }
+}
+
+impl From<DbCourseType> for CourseType {
+    fn from(course_type: DbCourseType) -> Self {
+        match course_type {
+            DbCourseType::Free => CourseType::Free,
+            DbCourseType::Paid => CourseType::Paid,
+        }
+    }
+}
+
+impl From<CourseType> for DbCourseType {
+    fn from(course_type: CourseType) -> Self {
+        match course_type {
+            CourseType::Free => DbCourseType::Free,
+            CourseType::Paid => DbCourseType::Paid,
+        }
+    }
+}
+
+impl From<&CourseQuery> for CourseFilter {
+    fn from(query: &CourseQuery) -> Self {
+        CourseFilter {
+            tutor_id: query.tutor_id,
+            course_type: query.course_type.map(DbCourseType::from),
+            min_price: query.min_price.clone(),
+            max_price: query.max_price.clone(),
+            posted_after: query.posted_after,
+        }
+    }
+}
+
+impl From<&CourseQuery> for PageRequest {
+    fn from(query: &CourseQuery) -> Self {
+        PageRequest {
+            sort: match query.sort {
+                CourseSort::PostedTime => DbCourseSort::PostedTime,
+                CourseSort::Name => DbCourseSort::Name,
+                CourseSort::Rating => DbCourseSort::Rating,
+                CourseSort::Price => DbCourseSort::Price,
+            },
+            descending: query.order == SortOrder::Desc,
+            limit: Some(query.limit),
+            offset: query.offset,
+        }
+    }
+}
+
+impl From<DbCourse> for Course {
+    fn from(course: DbCourse) -> Self {
+        Course {
+            tutor_id: course.tutor_id,
+            course_id: course.id,
+            course_name: course.name,
+            description: course.description,
+            posted_time: course.posted_time,
+            enrolled: course.enrolled,
+            enrolled_limit: course.enrolled_limit,
+            rating: course.rating.and_then(|rating| rating.to_f64()),
+            course_type: course.course_type.into(),
+            price: course.price.map(|price| price.with_scale(2)),
+            // CHAR(3) comes back padded if a shorter code slipped in
+            currency: course
+                .currency
+                .map(|currency| currency.trim_end().to_string()),
+        }
+    }
+}
+
+impl From<Course> for DbCourse {
+    fn from(course: Course) -> Self {
+        let mut db_course = DbCourse::new(course.tutor_id, course.course_name, course.posted_time);
+        db_course.id = course.course_id;
+        db_course.description = course.description;
+        db_course.enrolled = course.enrolled;
+        db_course.enrolled_limit = course.enrolled_limit;
+        db_course.course_type = course.course_type.into();
+        db_course.price = course.price;
+        db_course.currency = course.currency;
+        db_course
+    }
+}
+
+impl From<DbStudent> for Student {
+    fn from(student: DbStudent) -> Self {
+        Student {
+            name: student.name,
+            email: student.email,
+            student_id: student.id,
+        }
+    }
+}
+
+impl From<DbReview> for Review {
+    fn from(review: DbReview) -> Self {
+        Review {
+            course_id: review.course_id,
+            student_id: review.student_id,
+            rating: review.rating,
+            body: review.body,
+            created_at: review.created_at,
+            updated_at: review.updated_at,
+        }
+    }
+}
+
+impl From<DbWaitlistEntry> for WaitlistEntry {
+    fn from(entry: DbWaitlistEntry) -> Self {
+        WaitlistEntry {
+            course_id: entry.course_id,
+            student_id: entry.student_id,
+            joined_at: entry.joined_at,
+            position: entry.position,
+        }
+    }
+}
+
+impl From<DbEnrollment> for Enrollment {
+    fn from(enrollment: DbEnrollment) -> Self {
+        Enrollment {
+            course_id: enrollment.course_id,
+            student_id: enrollment.student_id,
+            enrolled_at: enrollment.enrolled_at,
+        }
+    }
+}
+
+impl From<AvailabilityRule> for WeeklySlot {
+    fn from(rule: AvailabilityRule) -> Self {
+        WeeklySlot {
+            weekday: Weekday::try_from((rule.weekday - 1) as u8)
+                .expect("weekday is checked to be 1..=7"),
+            start: rule.start_time,
+            end: rule.end_time,
+        }
+    }
+}
+
+impl From<DbAvailabilityKind> for AvailabilityKind {
+    fn from(kind: DbAvailabilityKind) -> Self {
+        match kind {
+            DbAvailabilityKind::Blocked => AvailabilityKind::Blocked,
+            DbAvailabilityKind::Extra => AvailabilityKind::Extra,
+        }
+    }
+}
+
+impl From<AvailabilityKind> for DbAvailabilityKind {
+    fn from(kind: AvailabilityKind) -> Self {
+        match kind {
+            AvailabilityKind::Blocked => DbAvailabilityKind::Blocked,
+            AvailabilityKind::Extra => DbAvailabilityKind::Extra,
+        }
+    }
+}
+
+impl From<DbAvailabilityException> for AvailabilityException {
+    fn from(exception: DbAvailabilityException) -> Self {
+        AvailabilityException {
+            exception_id: exception.id,
+            tutor_id: exception.tutor_id,
+            kind: exception.kind.into(),
+            starts_at: exception.starts_at,
+            ends_at: exception.ends_at,
+            reason: exception.reason,
+            ical_uid: exception.ical_uid,
+        }
+    }
+}
+
+impl From<AvailabilityException> for DbAvailabilityException {
+    fn from(exception: AvailabilityException) -> Self {
+        DbAvailabilityException {
+            id: exception.exception_id,
+            tutor_id: exception.tutor_id,
+            kind: exception.kind.into(),
+            starts_at: exception.starts_at,
+            ends_at: exception.ends_at,
+            reason: exception.reason,
+            ical_uid: exception.ical_uid,
+        }
+    }
+}
+
+impl From<DbCourseSession> for CourseSession {
+    fn from(session: DbCourseSession) -> Self {
+        CourseSession {
+            session_id: session.id,
+            course_id: session.course_id,
+            tutor_id: session.tutor_id,
+            series_id: session.series_id,
+            starts_at: session.starts_at,
+            ends_at: session.ends_at,
+            location: session.location,
+            meeting_url: session.meeting_url,
+            capacity: session.capacity,
+        }
+    }
+}
+
+impl From<CourseSession> for DbCourseSession {
+    fn from(session: CourseSession) -> Self {
+        DbCourseSession {
+            id: session.session_id,
+            course_id: session.course_id,
+            tutor_id: session.tutor_id,
+            series_id: session.series_id,
+            starts_at: session.starts_at,
+            ends_at: session.ends_at,
+            location: session.location,
+            meeting_url: session.meeting_url,
+            capacity: session.capacity,
+        }
+    }
+}
+
+impl From<DbCourseModule> for CourseModule {
+    fn from(module: DbCourseModule) -> Self {
+        CourseModule {
+            module_id: module.id,
+            course_id: module.course_id,
+            title: module.title,
+            position: module.position,
+        }
+    }
+}
+
+impl From<DbLesson> for Lesson {
+    fn from(lesson: DbLesson) -> Self {
+        Lesson {
+            lesson_id: lesson.id,
+            module_id: lesson.module_id,
+            title: lesson.title,
+            body: lesson.body,
+            duration_minutes: lesson.duration_minutes,
+            position: lesson.position,
+            published: lesson.published,
+            updated_at: lesson.updated_at,
+        }
+    }
+}
+
+impl From<Lesson> for DbLesson {
+    fn from(lesson: Lesson) -> Self {
+        DbLesson {
+            id: lesson.lesson_id,
+            module_id: lesson.module_id,
+            title: lesson.title,
+            body: lesson.body,
+            duration_minutes: lesson.duration_minutes,
+            position: lesson.position,
+            published: lesson.published,
+            updated_at: lesson.updated_at,
+        }
+    }
+}
+
+impl From<DbMaterial> for Material {
+    fn from(material: DbMaterial) -> Self {
+        Material {
+            material_id: material.id,
+            lesson_id: material.lesson_id,
+            filename: material.filename,
+            content_type: material.content_type,
+            size_bytes: material.size_bytes,
+            sha256: material.sha256,
+            uploaded_at: material.uploaded_at,
+        }
+    }
+}
+
+impl From<Material> for DbMaterial {
+    fn from(material: Material) -> Self {
+        DbMaterial {
+            id: material.material_id,
+            lesson_id: material.lesson_id,
+            filename: material.filename,
+            content_type: material.content_type,
+            size_bytes: material.size_bytes,
+            sha256: material.sha256,
+            uploaded_at: material.uploaded_at,
+        }
+    }
+}
+
+impl From<DbAttendanceStatus> for AttendanceStatus {
+    fn from(status: DbAttendanceStatus) -> Self {
+        match status {
+            DbAttendanceStatus::Present => AttendanceStatus::Present,
+            DbAttendanceStatus::Absent => AttendanceStatus::Absent,
+            DbAttendanceStatus::Late => AttendanceStatus::Late,
+            DbAttendanceStatus::Excused => AttendanceStatus::Excused,
+        }
+    }
+}
+
+impl From<AttendanceStatus> for DbAttendanceStatus {
+    fn from(status: AttendanceStatus) -> Self {
+        match status {
+            AttendanceStatus::Present => DbAttendanceStatus::Present,
+            AttendanceStatus::Absent => DbAttendanceStatus::Absent,
+            AttendanceStatus::Late => DbAttendanceStatus::Late,
+            AttendanceStatus::Excused => DbAttendanceStatus::Excused,
+        }
+    }
+}
+
+impl From<DbAttendance> for Attendance {
+    fn from(attendance: DbAttendance) -> Self {
+        Attendance {
+            session_id: attendance.session_id,
+            student_id: attendance.student_id,
+            status: attendance.status.into(),
+            checked_in: attendance.checked_in,
+            marked_at: attendance.marked_at,
+        }
+    }
+}
+
+impl From<DbCheckInCode> for CheckInCode {
+    fn from(code: DbCheckInCode) -> Self {
+        CheckInCode {
+            session_id: code.session_id,
+            code: code.code,
+            expires_at: code.expires_at,
+        }
+    }
+}
+
+impl From<CheckInCode> for DbCheckInCode {
+    fn from(code: CheckInCode) -> Self {
+        DbCheckInCode {
+            session_id: code.session_id,
+            code: code.code,
+            expires_at: code.expires_at,
+        }
+    }
+}
+
+impl From<DbAttendanceTally> for AttendanceTally {
+    fn from(tally: DbAttendanceTally) -> Self {
+        AttendanceTally {
+            course_id: tally.course_id,
+            student_id: tally.student_id,
+            sessions_held: tally.sessions_held,
+            present: tally.present,
+            absent: tally.absent,
+            late: tally.late,
+            excused: tally.excused,
+        }
+    }
+}
+
+impl From<DbBookingStatus> for BookingStatus {
+    fn from(status: DbBookingStatus) -> Self {
+        match status {
+            DbBookingStatus::Confirmed => BookingStatus::Confirmed,
+            DbBookingStatus::Cancelled => BookingStatus::Cancelled,
+        }
+    }
+}
+
+impl From<BookingStatus> for DbBookingStatus {
+    fn from(status: BookingStatus) -> Self {
+        match status {
+            BookingStatus::Confirmed => DbBookingStatus::Confirmed,
+            BookingStatus::Cancelled => DbBookingStatus::Cancelled,
+        }
+    }
+}
+
+impl From<DbBooking> for Booking {
+    fn from(booking: DbBooking) -> Self {
+        Booking {
+            booking_id: booking.id,
+            tutor_id: booking.tutor_id,
+            student_id: booking.student_id,
+            starts_at: booking.starts_at,
+            ends_at: booking.ends_at,
+            note: booking.note,
+            status: booking.status.into(),
+            created_at: booking.created_at,
+            cancelled_at: booking.cancelled_at,
+        }
+    }
+}
+
+impl From<Booking> for DbBooking {
+    fn from(booking: Booking) -> Self {
+        DbBooking {
+            id: booking.booking_id,
+            tutor_id: booking.tutor_id,
+            student_id: booking.student_id,
+            starts_at: booking.starts_at,
+            ends_at: booking.ends_at,
+            note: booking.note,
+            status: booking.status.into(),
+            created_at: booking.created_at,
+            cancelled_at: booking.cancelled_at,
+        }
+    }
+}
+
+/// Maps `RowNotFound` to `None` so the repository's `fetch_one` lookups
+/// behave like the in-memory `find`.
+fn optional<T>(result: Result<T, sqlx::Error>) -> Result<Option<T>, StoreError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(sqlx::Error::RowNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub struct PgTutorStore {
+    pool: PgPool,
+}
+
+impl PgTutorStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgTutorStore { pool }
+    }
+}
+
+#[async_trait]
+impl TutorStore for PgTutorStore {
+    async fn create(
+        &self,
+        name: String,
+        email: String,
+        password_hash: String,
+    ) -> Result<Tutor, StoreError> {
+        let tutor =
+            tutor_repository::create_tutor_with_password(&self.pool, name, email, password_hash)
+                .await?;
+        Ok(tutor.into())
+    }
+
+    async fn find_credentials(&self, email: &str) -> Result<Option<TutorCredentials>, StoreError> {
+        let credentials = tutor_repository::find_tutor_credentials(&self.pool, email).await;
+        Ok(optional(credentials)?.map(|credentials| TutorCredentials {
+            tutor_id: credentials.id,
+            password_hash: credentials.password_hash,
+            role: credentials.role.into(),
+        }))
+    }
+
+    async fn find_role(&self, tutor_id: Uuid) -> Result<Option<Role>, StoreError> {
+        let role = tutor_repository::find_tutor_role(&self.pool, tutor_id).await;
+        Ok(optional(role)?.map(Role::from))
+    }
+
+    async fn set_role(&self, tutor_id: Uuid, role: Role) -> Result<bool, StoreError> {
+        let updated = tutor_repository::set_tutor_role(&self.pool, tutor_id, role.into()).await;
+        Ok(optional(updated)?.is_some())
+    }
+
+    async fn find(&self, tutor_id: Uuid) -> Result<Option<Tutor>, StoreError> {
+        let tutor = tutor_repository::find_tutor(tutor_id, &self.pool).await;
+        Ok(optional(tutor)?.map(Tutor::from))
+    }
+
+    async fn search_by_email(&self, fragment: &str, limit: i64) -> Result<Vec<Tutor>, StoreError> {
+        let tutors = tutor_repository::search_tutors_by_email(&self.pool, fragment, limit).await?;
+        Ok(tutors.into_iter().map(Tutor::from).collect())
+    }
+
+    async fn update(&self, tutor: Tutor) -> Result<Option<Tutor>, StoreError> {
+        let tutor = tutor_repository::update_tutor(&self.pool, tutor.into()).await;
+        Ok(optional(tutor)?.map(Tutor::from))
+    }
+
+    async fn delete(&self, tutor_id: Uuid) -> Result<Option<Tutor>, StoreError> {
+        let tutor = tutor_repository::delete_tutor(&self.pool, tutor_id).await;
+        Ok(optional(tutor)?.map(Tutor::from))
+    }
+}
+
+pub struct PgCourseStore {
+    pool: PgPool,
+}
+
+impl PgCourseStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgCourseStore { pool }
+    }
+}
+
+#[async_trait]
+impl CourseStore for PgCourseStore {
+    async fn add(&self, course: Course) -> Result<Course, StoreError> {
+        let course = course_repository::create_course(&self.pool, course.into()).await?;
+        Ok(course.into())
+    }
+
+    async fn find(&self, course_id: Uuid) -> Result<Option<Course>, StoreError> {
+        let course = course_repository::find_course(&self.pool, course_id).await;
+        Ok(optional(course)?.map(Course::from))
+    }
+
+    async fn list_for_tutor(&self, tutor_id: Uuid) -> Result<Vec<Course>, StoreError> {
+        let courses = course_repository::list_tutor_courses(&self.pool, tutor_id).await?;
+        Ok(courses.into_iter().map(Course::from).collect())
+    }
+
+    async fn list(&self, query: &CourseQuery) -> Result<CoursePage, StoreError> {
+        let page =
+            course_repository::list_courses(&self.pool, &query.into(), &query.into()).await?;
+        let courses = page.courses.into_iter().map(Course::from).collect();
+        Ok(CoursePage::new(courses, page.total, query))
+    }
+
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<SearchHit>, StoreError> {
+        let hits = course_repository::search_courses(&self.pool, query, limit).await?;
+        Ok(hits
+            .into_iter()
+            .map(|hit| SearchHit {
+                course: hit.course.into(),
+                rank: f64::from(hit.rank),
+                highlight: Highlight {
+                    course_name: hit.name_highlight,
+                    description: hit.description_highlight,
+                },
+            })
+            .collect())
+    }
+
+    async fn update(&self, course: Course) -> Result<Option<Course>, StoreError> {
+        // Start from the stored row so columns the HTTP model lacks are kept
+        let existing = course_repository::find_course(&self.pool, course.course_id).await;
+        let mut db_course = match optional(existing)? {
+            Some(db_course) => db_course,
+            None => return Ok(None),
+        };
+        db_course.name = course.course_name;
+        db_course.description = course.description;
+        db_course.posted_time = course.posted_time;
+        db_course.course_type = course.course_type.into();
+        db_course.price = course.price;
+        db_course.currency = course.currency;
+
+        let course = course_repository::update_course(&self.pool, db_course).await;
+        Ok(optional(course)?.map(Course::from))
+    }
+
+    async fn delete(&self, course_id: Uuid) -> Result<Option<Course>, StoreError> {
+        let course = course_repository::delete_course(&self.pool, course_id).await;
+        Ok(optional(course)?.map(Course::from))
+    }
+
+    async fn enroll(&self, course_id: Uuid, student_id: Uuid) -> Result<EnrollOutcome, StoreError> {
+        let outcome =
+            enrollment_repository::enroll_student(&self.pool, course_id, student_id).await;
+        match outcome {
+            Ok(DbEnrollOutcome::Enrolled(enrollment)) => {
+                Ok(EnrollOutcome::Enrolled(enrollment.into()))
+            }
+            Ok(DbEnrollOutcome::Waitlisted(entry)) => Ok(EnrollOutcome::Waitlisted(entry.into())),
+            Ok(DbEnrollOutcome::AlreadyEnrolled) => Err(already_enrolled(course_id, student_id)),
+            Ok(DbEnrollOutcome::AlreadyWaitlisted) => {
+                Err(already_waitlisted(course_id, student_id))
+            }
+            Err(sqlx::Error::RowNotFound) => Err(StoreError::NotFound(format!(
+                "Course with ID {course_id} not found"
+            ))),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn withdraw(&self, course_id: Uuid, student_id: Uuid) -> Result<bool, StoreError> {
+        let outcome =
+            enrollment_repository::withdraw_student(&self.pool, course_id, student_id).await;
+        Ok(optional(outcome)?.is_some())
+    }
+
+    async fn list_enrolled(&self, course_id: Uuid) -> Result<Vec<Enrollment>, StoreError> {
+        let enrollments =
+            enrollment_repository::list_course_enrollments(&self.pool, course_id).await?;
+        Ok(enrollments.into_iter().map(Enrollment::from).collect())
+    }
+
+    async fn list_waitlist(&self, course_id: Uuid) -> Result<Vec<WaitlistEntry>, StoreError> {
+        let entries = enrollment_repository::list_waitlist(&self.pool, course_id).await?;
+        Ok(entries.into_iter().map(WaitlistEntry::from).collect())
+    }
+
+    async fn post_review(
+        &self,
+        course_id: Uuid,
+        student_id: Uuid,
+        rating: i16,
+        body: String,
+    ) -> Result<ReviewOutcome, StoreError> {
+        let outcome =
+            review_repository::post_review(&self.pool, course_id, student_id, rating, body).await;
+        match outcome {
+            Ok(DbReviewOutcome::Created(review)) => Ok(ReviewOutcome::Created(review.into())),
+            Ok(DbReviewOutcome::Updated(review)) => Ok(ReviewOutcome::Updated(review.into())),
+            Ok(DbReviewOutcome::NotEnrolled) => Ok(ReviewOutcome::NotEnrolled),
+            Err(sqlx::Error::RowNotFound) => Err(StoreError::NotFound(format!(
+                "Course with ID {course_id} not found"
+            ))),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list_reviews(&self, course_id: Uuid) -> Result<Vec<Review>, StoreError> {
+        let reviews = review_repository::list_course_reviews(&self.pool, course_id).await?;
+        Ok(reviews.into_iter().map(Review::from).collect())
+    }
+
+    async fn schedule_sessions(
+        &self,
+        sessions: Vec<CourseSession>,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let Some(tutor_id) = sessions.first().map(|s| s.tutor_id) else {
+            return Ok(Vec::new());
+        };
+        let sessions = sessions.into_iter().map(DbCourseSession::from).collect();
+        match course_session_repository::schedule_course_sessions(&self.pool, sessions).await? {
+            ScheduleOutcome::Scheduled(sessions) => {
+                Ok(sessions.into_iter().map(CourseSession::from).collect())
+            }
+            ScheduleOutcome::Overlaps => Err(sessions_overlap(tutor_id)),
+        }
+    }
+
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<CourseSession>, StoreError> {
+        let session = course_session_repository::find_course_session(&self.pool, session_id).await;
+        Ok(optional(session)?.map(CourseSession::from))
+    }
+
+    async fn list_sessions(
+        &self,
+        course_id: Uuid,
+        from: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let sessions =
+            course_session_repository::list_course_sessions(&self.pool, course_id, from, limit)
+                .await?;
+        Ok(sessions.into_iter().map(CourseSession::from).collect())
+    }
+
+    async fn list_student_sessions(
+        &self,
+        student_id: Uuid,
+        from: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let sessions =
+            course_session_repository::list_student_sessions(&self.pool, student_id, from, limit)
+                .await?;
+        Ok(sessions.into_iter().map(CourseSession::from).collect())
+    }
+
+    async fn list_tutor_sessions(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CourseSession>, StoreError> {
+        let sessions =
+            course_session_repository::list_tutor_sessions(&self.pool, tutor_id, from, to).await?;
+        Ok(sessions.into_iter().map(CourseSession::from).collect())
+    }
+
+    async fn delete_session(&self, course_id: Uuid, session_id: Uuid) -> Result<bool, StoreError> {
+        let deleted =
+            course_session_repository::delete_course_session(&self.pool, course_id, session_id)
+                .await;
+        Ok(optional(deleted)?.is_some())
+    }
+}
+
+pub struct PgStudentStore {
+    pool: PgPool,
+}
+
+impl PgStudentStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgStudentStore { pool }
+    }
+}
+
+#[async_trait]
+impl StudentStore for PgStudentStore {
+    async fn create(
+        &self,
+        name: String,
+        email: String,
+        password_hash: String,
+    ) -> Result<Student, StoreError> {
+        let student = student_repository::create_student_with_password(
+            &self.pool,
+            name,
+            email,
+            password_hash,
+        )
+        .await?;
+        Ok(student.into())
+    }
+
+    async fn find_credentials(
+        &self,
+        email: &str,
+    ) -> Result<Option<StudentCredentials>, StoreError> {
+        let credentials = student_repository::find_student_credentials(&self.pool, email).await;
+        Ok(
+            optional(credentials)?.map(|credentials| StudentCredentials {
+                student_id: credentials.id,
+                password_hash: credentials.password_hash,
+            }),
+        )
+    }
+
+    async fn find(&self, student_id: Uuid) -> Result<Option<Student>, StoreError> {
+        let student = student_repository::find_student(&self.pool, student_id).await;
+        Ok(optional(student)?.map(Student::from))
+    }
+}
+
+pub struct PgAuditStore {
+    pool: PgPool,
+}
+
+impl PgAuditStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgAuditStore { pool }
+    }
+}
+
+#[async_trait]
+impl AuditStore for PgAuditStore {
+    async fn record(&self, actor_id: Uuid, action: &str, detail: &str) -> Result<(), StoreError> {
+        audit_repository::record_audit(&self.pool, actor_id, action, detail).await?;
+        Ok(())
+    }
+}
+
+pub struct PgScheduleStore {
+    pool: PgPool,
+}
+
+impl PgScheduleStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgScheduleStore { pool }
+    }
+}
+
+#[async_trait]
+impl ScheduleStore for PgScheduleStore {
+    async fn availability(&self, tutor_id: Uuid) -> Result<Availability, StoreError> {
+        let rules = availability_repository::list_availability_rules(&self.pool, tutor_id).await?;
+        // Every rule of a tutor is written with the same timezone
+        let Some(timezone) = rules.first().map(|rule| rule.timezone.clone()) else {
+            return Ok(Availability::default());
+        };
+        Ok(Availability {
+            timezone,
+            slots: rules.into_iter().map(WeeklySlot::from).collect(),
+        })
+    }
+
+    async fn set_availability(
+        &self,
+        tutor_id: Uuid,
+        availability: Availability,
+    ) -> Result<Availability, StoreError> {
+        let rules: Vec<AvailabilityRule> = availability
+            .slots
+            .iter()
+            .map(|slot| AvailabilityRule {
+                weekday: slot.weekday.number_from_monday() as i16,
+                start_time: slot.start,
+                end_time: slot.end,
+                timezone: availability.timezone.clone(),
+            })
+            .collect();
+        match availability_repository::replace_availability_rules(&self.pool, tutor_id, &rules)
+            .await
+        {
+            Ok(_) => Ok(availability),
+            Err(sqlx::Error::RowNotFound) => Err(StoreError::NotFound(format!(
+                "Tutor with ID {tutor_id} not found"
+            ))),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn add_exception(
+        &self,
+        exception: AvailabilityException,
+    ) -> Result<AvailabilityException, StoreError> {
+        let exception =
+            availability_repository::create_availability_exception(&self.pool, exception.into())
+                .await?;
+        Ok(exception.into())
+    }
+
+    async fn list_exceptions(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityException>, StoreError> {
+        let exceptions =
+            availability_repository::list_availability_exceptions(&self.pool, tutor_id, from, to)
+                .await?;
+        Ok(exceptions
+            .into_iter()
+            .map(AvailabilityException::from)
+            .collect())
+    }
+
+    async fn import_exceptions(
+        &self,
+        tutor_id: Uuid,
+        exceptions: Vec<AvailabilityException>,
+        cancelled_uids: &[String],
+    ) -> Result<Vec<AvailabilityException>, StoreError> {
+        let exceptions = exceptions
+            .into_iter()
+            .map(DbAvailabilityException::from)
+            .collect();
+        let imported = availability_repository::import_availability_exceptions(
+            &self.pool,
+            tutor_id,
+            exceptions,
+            cancelled_uids,
+        )
+        .await?;
+        Ok(imported
+            .into_iter()
+            .map(AvailabilityException::from)
+            .collect())
+    }
+
+    async fn delete_exception(
+        &self,
+        tutor_id: Uuid,
+        exception_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let deleted = availability_repository::delete_availability_exception(
+            &self.pool,
+            tutor_id,
+            exception_id,
+        )
+        .await;
+        Ok(optional(deleted)?.is_some())
+    }
+
+    async fn book(&self, booking: Booking) -> Result<Booking, StoreError> {
+        match booking_repository::create_booking(&self.pool, booking.clone().into()).await? {
+            BookingOutcome::Booked(booking) => Ok(booking.into()),
+            BookingOutcome::Overlaps => Err(booking_overlaps(&booking)),
+        }
+    }
+
+    async fn find_booking(&self, booking_id: Uuid) -> Result<Option<Booking>, StoreError> {
+        let booking = booking_repository::find_booking(&self.pool, booking_id).await;
+        Ok(optional(booking)?.map(Booking::from))
+    }
+
+    async fn list_bookings(
+        &self,
+        tutor_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Booking>, StoreError> {
+        let bookings =
+            booking_repository::list_tutor_bookings(&self.pool, tutor_id, from, to).await?;
+        Ok(bookings.into_iter().map(Booking::from).collect())
+    }
+
+    async fn cancel_booking(&self, booking_id: Uuid) -> Result<Option<Booking>, StoreError> {
+        let booking = booking_repository::cancel_booking(&self.pool, booking_id).await;
+        Ok(optional(booking)?.map(Booking::from))
+    }
+}
+
+pub struct PgAttendanceStore {
+    pool: PgPool,
+}
+
+impl PgAttendanceStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgAttendanceStore { pool }
+    }
+}
+
+#[async_trait]
+impl AttendanceStore for PgAttendanceStore {
+    async fn mark(
+        &self,
+        session_id: Uuid,
+        marks: Vec<AttendanceMark>,
+    ) -> Result<Vec<Attendance>, StoreError> {
+        let marks: Vec<(Uuid, DbAttendanceStatus)> = marks
+            .into_iter()
+            .map(|m| (m.student_id, m.status.into()))
+            .collect();
+        let marked = attendance_repository::mark_attendance(&self.pool, session_id, &marks).await?;
+        Ok(marked.into_iter().map(Attendance::from).collect())
+    }
+
+    async fn list(&self, session_id: Uuid) -> Result<Vec<Attendance>, StoreError> {
+        let attendance =
+            attendance_repository::list_session_attendance(&self.pool, session_id).await?;
+        Ok(attendance.into_iter().map(Attendance::from).collect())
+    }
+
+    async fn open_check_in(&self, code: CheckInCode) -> Result<CheckInCode, StoreError> {
+        let code = attendance_repository::open_check_in(&self.pool, code.into()).await?;
+        Ok(code.into())
+    }
+
+    async fn check_in(
+        &self,
+        session_id: Uuid,
+        student_id: Uuid,
+        code: &str,
+        status: AttendanceStatus,
+    ) -> Result<Option<Attendance>, StoreError> {
+        let outcome = attendance_repository::check_in(
+            &self.pool,
+            session_id,
+            student_id,
+            code,
+            status.into(),
+        )
+        .await?;
+        match outcome {
+            CheckInOutcome::CheckedIn(attendance) => Ok(Some(attendance.into())),
+            CheckInOutcome::InvalidCode => Ok(None),
+        }
+    }
+
+    async fn course_summary(
+        &self,
+        course_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceTally>, StoreError> {
+        let tallies = attendance_repository::course_attendance(&self.pool, course_id, now).await?;
+        Ok(tallies.into_iter().map(AttendanceTally::from).collect())
+    }
+
+    async fn student_summary(
+        &self,
+        student_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceTally>, StoreError> {
+        let tallies =
+            attendance_repository::student_attendance(&self.pool, student_id, now).await?;
+        Ok(tallies.into_iter().map(AttendanceTally::from).collect())
+    }
+}
+
+pub struct PgCurriculumStore {
+    pool: PgPool,
+}
+
+impl PgCurriculumStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgCurriculumStore { pool }
+    }
+}
+
+#[async_trait]
+impl CurriculumStore for PgCurriculumStore {
+    async fn add_module(&self, module: CourseModule) -> Result<CourseModule, StoreError> {
+        let added = curriculum_repository::append_module(
+            &self.pool,
+            module.module_id,
+            module.course_id,
+            &module.title,
+            MAX_CURRICULUM_ITEMS as i64,
+        )
+        .await;
+        optional(added)?
+            .map(CourseModule::from)
+            .ok_or_else(|| too_many_modules(module.course_id))
+    }
+
+    async fn list_modules(&self, course_id: Uuid) -> Result<Vec<CourseModule>, StoreError> {
+        let modules = curriculum_repository::list_modules(&self.pool, course_id).await?;
+        Ok(modules.into_iter().map(CourseModule::from).collect())
+    }
+
+    async fn rename_module(
+        &self,
+        course_id: Uuid,
+        module_id: Uuid,
+        title: String,
+    ) -> Result<Option<CourseModule>, StoreError> {
+        let module =
+            curriculum_repository::rename_module(&self.pool, course_id, module_id, &title).await;
+        Ok(optional(module)?.map(CourseModule::from))
+    }
+
+    async fn delete_module(&self, course_id: Uuid, module_id: Uuid) -> Result<bool, StoreError> {
+        let deleted = curriculum_repository::delete_module(&self.pool, course_id, module_id).await;
+        Ok(optional(deleted)?.is_some())
+    }
+
+    async fn reorder_modules(
+        &self,
+        course_id: Uuid,
+        module_ids: Vec<Uuid>,
+    ) -> Result<Option<Vec<CourseModule>>, StoreError> {
+        match curriculum_repository::reorder_modules(&self.pool, course_id, &module_ids).await? {
+            ReorderOutcome::Reordered(modules) => {
+                Ok(Some(modules.into_iter().map(CourseModule::from).collect()))
+            }
+            ReorderOutcome::Mismatch => Ok(None),
+        }
+    }
+
+    async fn add_lesson(&self, lesson: Lesson) -> Result<Lesson, StoreError> {
+        let module_id = lesson.module_id;
+        let added = curriculum_repository::append_lesson(
+            &self.pool,
+            lesson.into(),
+            MAX_CURRICULUM_ITEMS as i64,
+        )
+        .await;
+        optional(added)?
+            .map(Lesson::from)
+            .ok_or_else(|| too_many_lessons(module_id))
+    }
+
+    async fn update_lesson(&self, lesson: Lesson) -> Result<Option<Lesson>, StoreError> {
+        let lesson = curriculum_repository::update_lesson(&self.pool, lesson.into()).await;
+        Ok(optional(lesson)?.map(Lesson::from))
+    }
+
+    async fn delete_lesson(&self, module_id: Uuid, lesson_id: Uuid) -> Result<bool, StoreError> {
+        let deleted = curriculum_repository::delete_lesson(&self.pool, module_id, lesson_id).await;
+        Ok(optional(deleted)?.is_some())
+    }
+
+    async fn list_lessons(
+        &self,
+        course_id: Uuid,
+        published_only: bool,
+    ) -> Result<Vec<Lesson>, StoreError> {
+        let lessons =
+            curriculum_repository::list_course_lessons(&self.pool, course_id, published_only)
+                .await?;
+        Ok(lessons.into_iter().map(Lesson::from).collect())
+    }
+
+    async fn reorder_lessons(
+        &self,
+        module_id: Uuid,
+        lesson_ids: Vec<Uuid>,
+    ) -> Result<Option<Vec<Lesson>>, StoreError> {
+        match curriculum_repository::reorder_lessons(&self.pool, module_id, &lesson_ids).await? {
+            ReorderOutcome::Reordered(lessons) => {
+                Ok(Some(lessons.into_iter().map(Lesson::from).collect()))
+            }
+            ReorderOutcome::Mismatch => Ok(None),
+        }
+    }
+}
+
+pub struct PgMaterialStore {
+    pool: PgPool,
+}
+
+impl PgMaterialStore {
+    pub fn new(pool: PgPool) -> Self {
+        PgMaterialStore { pool }
+    }
+}
+
+#[async_trait]
+impl MaterialStore for PgMaterialStore {
+    async fn attach(&self, material: Material) -> Result<AttachOutcome, StoreError> {
+        match material_repository::attach_material(&self.pool, material.into()).await? {
+            DbAttachOutcome::Attached(material) => Ok(AttachOutcome::Attached(material.into())),
+            DbAttachOutcome::AlreadyAttached(material) => {
+                Ok(AttachOutcome::AlreadyAttached(material.into()))
+            }
+        }
+    }
+
+    async fn list(&self, lesson_id: Uuid) -> Result<Vec<Material>, StoreError> {
+        let materials = material_repository::list_lesson_materials(&self.pool, lesson_id).await?;
+        Ok(materials.into_iter().map(Material::from).collect())
+    }
+
+    async fn list_for_lessons(&self, lesson_ids: &[Uuid]) -> Result<Vec<Material>, StoreError> {
+        let materials =
+            material_repository::list_materials_for_lessons(&self.pool, lesson_ids).await?;
+        Ok(materials.into_iter().map(Material::from).collect())
+    }
+
+    async fn find(
+        &self,
+        lesson_id: Uuid,
+        material_id: Uuid,
+    ) -> Result<Option<Material>, StoreError> {
+        let material = material_repository::find_material(&self.pool, lesson_id, material_id).await;
+        Ok(optional(material)?.map(Material::from))
+    }
+
+    async fn detach(
+        &self,
+        lesson_id: Uuid,
+        material_id: Uuid,
+    ) -> Result<Option<Material>, StoreError> {
+        let material =
+            material_repository::delete_material(&self.pool, lesson_id, material_id).await;
+        Ok(optional(material)?.map(Material::from))
+    }
+
+    async fn content_in_use(&self, sha256: &str) -> Result<bool, StoreError> {
+        Ok(material_repository::content_in_use(&self.pool, sha256).await?)
+    }
+}
@@ -0,0 +1,127 @@
+//! Checks on files uploaded as lesson materials: which types are accepted,
+//! whether the bytes match the declared type, and what name to keep.
+
+const PPTX: &str = "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+const ODP: &str = "application/vnd.oasis.opendocument.presentation";
+
+/// Content types a lesson material may have: documents, slides, videos and
+/// images.
+pub const ALLOWED_CONTENT_TYPES: &[&str] = &[
+    "application/pdf",
+    "application/vnd.ms-powerpoint",
+    PPTX,
+    ODP,
+    "video/mp4",
+    "video/webm",
+    "video/quicktime",
+    "image/png",
+    "image/jpeg",
+];
+
+/// How many leading bytes [`matches_content_type`] looks at.
+pub const SNIFF_LEN: usize = 12;
+
+/// Longest file name kept, in characters.
+pub const MAX_FILENAME_LEN: usize = 255;
+
+/// Whether `head`, the first bytes of a file, fit `content_type`. Only the
+/// file signature is checked, not the whole format.
+pub fn matches_content_type(content_type: &str, head: &[u8]) -> bool {
+    match content_type {
+        "application/pdf" => head.starts_with(b"%PDF-"),
+        // Legacy Office files are OLE compound documents
+        "application/vnd.ms-powerpoint" => {
+            head.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1])
+        }
+        // OOXML and OpenDocument files are zip archives
+        PPTX | ODP => head.starts_with(b"PK\x03\x04"),
+        // ISO base media files open with a box whose type follows its size
+        "video/mp4" | "video/quicktime" => matches!(
+            head.get(4..8),
+            Some(b"ftyp" | b"moov" | b"mdat" | b"wide" | b"free")
+        ),
+        "video/webm" => head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]),
+        "image/png" => head.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/jpeg" => head.starts_with(&[0xFF, 0xD8, 0xFF]),
+        _ => false,
+    }
+}
+
+/// The last path component of an uploaded file's name, without control
+/// characters and cut to [`MAX_FILENAME_LEN`]. `None` if nothing usable is
+/// left.
+pub fn clean_filename(name: &str) -> Option<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILENAME_LEN)
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() || cleaned == "." || cleaned == ".." {
+        return None;
+    }
+    Some(cleaned.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_file_signatures() {
+        assert!(matches_content_type(
+            "application/pdf",
+            b"%PDF-1.7\n%\xE2\xE3"
+        ));
+        assert!(matches_content_type(PPTX, b"PK\x03\x04\x14\x00\x06\x00"));
+        assert!(matches_content_type(
+            "video/mp4",
+            b"\x00\x00\x00\x20ftypisom"
+        ));
+        assert!(matches_content_type(
+            "video/webm",
+            &[0x1A, 0x45, 0xDF, 0xA3, 0x9F]
+        ));
+        assert!(matches_content_type(
+            "image/jpeg",
+            &[0xFF, 0xD8, 0xFF, 0xE0]
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_and_unknown_types() {
+        assert!(!matches_content_type("application/pdf", b"MZ\x90\x00"));
+        assert!(!matches_content_type("video/mp4", b"%PDF-1.7"));
+        assert!(!matches_content_type("video/mp4", b"\x00\x00"));
+        assert!(!matches_content_type("text/html", b"<html>"));
+        assert!(
+            ALLOWED_CONTENT_TYPES
+                .iter()
+                .all(|t| !matches_content_type(t, b""))
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_file_name() {
+        assert_eq!(clean_filename("slides.pdf").as_deref(), Some("slides.pdf"));
+        assert_eq!(
+            clean_filename("../../etc/passwd").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(
+            clean_filename("C:\\Users\\me\\notes\r\n.pdf").as_deref(),
+            Some("notes.pdf")
+        );
+        assert_eq!(
+            clean_filename(" week 1 .mp4 ").as_deref(),
+            Some("week 1 .mp4")
+        );
+        assert_eq!(clean_filename("dir/"), None);
+        assert_eq!(clean_filename(".."), None);
+        assert_eq!(
+            clean_filename(&"a".repeat(300)).map(|n| n.len()),
+            Some(MAX_FILENAME_LEN)
+        );
+    }
+}
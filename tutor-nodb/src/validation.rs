@@ -0,0 +1,133 @@
+use serde::Serialize;
+
+pub const MAX_NAME_LEN: usize = 100;
+pub const MIN_PASSWORD_LEN: usize = 8;
+pub const MAX_PASSWORD_LEN: usize = 128;
+
+#[derive(Debug, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// Every invalid field of a request body, collected in one pass so the
+/// client can fix them all at once.
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field,
+            message: message.into(),
+        });
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+pub fn check_required(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    if value.trim().is_empty() {
+        errors.add(field, format!("{field} is required"));
+    }
+}
+
+pub fn check_name(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    let len = value.trim().chars().count();
+    if len == 0 {
+        errors.add(field, format!("{field} is required"));
+    } else if len > MAX_NAME_LEN {
+        errors.add(
+            field,
+            format!("{field} must be at most {MAX_NAME_LEN} characters"),
+        );
+    }
+}
+
+pub fn check_email(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    if value.trim().is_empty() {
+        errors.add(field, format!("{field} is required"));
+    } else if !is_valid_email(value) {
+        errors.add(field, format!("`{value}` is not a valid email address"));
+    }
+}
+
+pub fn check_password(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    let len = value.chars().count();
+    if len < MIN_PASSWORD_LEN {
+        errors.add(
+            field,
+            format!("{field} must be at least {MIN_PASSWORD_LEN} characters"),
+        );
+    } else if len > MAX_PASSWORD_LEN {
+        errors.add(
+            field,
+            format!("{field} must be at most {MAX_PASSWORD_LEN} characters"),
+        );
+    }
+}
+
+// Deliberately loose: one `@`, something before it and a dotted domain after
+fn is_valid_email(email: &str) -> bool {
+    if email.chars().any(char::is_whitespace) {
+        return false;
+    }
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() >= 2
+                && domain.split('.').all(|part| !part.is_empty())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plain_emails() {
+        assert!(is_valid_email("kendrick@gmail.com"));
+        assert!(is_valid_email("first.last@mail.example.org"));
+    }
+
+    #[test]
+    fn rejects_malformed_emails() {
+        for email in [
+            "",
+            "no-at-sign",
+            "@example.com",
+            "a@b",
+            "a@@b.com",
+            "a b@c.com",
+            "a@b.",
+        ] {
+            assert!(!is_valid_email(email), "{email} should be invalid");
+        }
+    }
+
+    #[test]
+    fn name_length_is_bounded() {
+        let mut errors = ValidationErrors::default();
+        check_name(&mut errors, "name", &"x".repeat(MAX_NAME_LEN));
+        assert!(errors.errors.is_empty());
+
+        check_name(&mut errors, "name", &"x".repeat(MAX_NAME_LEN + 1));
+        check_name(&mut errors, "name", "   ");
+        assert_eq!(errors.errors.len(), 2);
+    }
+}
@@ -1,39 +1,148 @@
 use std::net::TcpListener;
-use tutor_nodb::{run, connect_db};
-use serde_json;
+use std::path::PathBuf;
+use tutor_nodb::{AdminSeed, Config, connect_db, run_with};
 use uuid::Uuid;
 
 async fn spawn_app() -> String {
+    spawn_app_with_uploads().await.0
+}
+
+/// Also returns the directory the server keeps uploaded files in.
+async fn spawn_app_with_uploads() -> (String, PathBuf) {
+    // In-memory storage, so the tests need no running Postgres
+    let config = Config {
+        admin: Some(AdminSeed {
+            name: "admin".to_string(),
+            email: ADMIN_EMAIL.to_string(),
+            password: PASSWORD.to_string(),
+        }),
+        ..Config::in_memory()
+    };
+    serve(config).await
+}
+
+/// A server on the database at `DATABASE_URL`, or `None` when it is unset so
+/// the test can be skipped.
+async fn spawn_postgres_app() -> Option<(String, PathBuf)> {
+    if std::env::var("DATABASE_URL").is_err() {
+        eprintln!("DATABASE_URL is unset, skipping the Postgres run");
+        return None;
+    }
+    let pool = connect_db().await.expect("Failed to connect to Postgres");
+    Some(serve(Config::postgres(pool)).await)
+}
+
+async fn serve(config: Config) -> (String, PathBuf) {
     // Bind to a random available port
     let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind port");
     let port = listener.local_addr().unwrap().port();
-    let pool = connect_db().await.expect("Failed to connect to DB");
 
-    let server = run(listener, pool).expect("Failed to start server");
+    let upload_dir = std::env::temp_dir().join(format!("eazytutors-test-{}", Uuid::new_v4()));
+    let config = Config {
+        upload_dir: Some(upload_dir.clone()),
+        max_upload_bytes: Some(MAX_UPLOAD_BYTES),
+        ..config
+    };
+    let server = run_with(listener, config)
+        .await
+        .expect("Failed to start server");
     // Spawn the server on a background task
     tokio::spawn(server);
-    
-    format!("http://127.0.0.1:{}", port)
+
+    (format!("http://127.0.0.1:{}", port), upload_dir)
+}
+
+// Emails differ between runs, as the Postgres runs share a database
+fn unique_email(name: &str) -> String {
+    format!("{name}-{}@example.com", Uuid::new_v4().simple())
+}
+
+const PASSWORD: &str = "correct horse battery";
+// The admin account every test server starts with
+const ADMIN_EMAIL: &str = "admin@example.com";
+// Small, so the size limit is quick to hit
+const MAX_UPLOAD_BYTES: u64 = 64 * 1024;
+
+async fn log_in(client: &reqwest::Client, address: &str, body: serde_json::Value) -> String {
+    let tokens: serde_json::Value = client
+        .post(format!("{}/auth/login", address))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to log in")
+        .json()
+        .await
+        .expect("Failed to parse tokens");
+    tokens["access_token"].as_str().unwrap().to_string()
+}
+
+async fn log_in_admin(client: &reqwest::Client, address: &str) -> String {
+    let body = serde_json::json!({ "email": ADMIN_EMAIL, "password": PASSWORD });
+    log_in(client, address, body).await
+}
+
+// Signs a tutor up and logs them in, returning (tutor_id, access_token)
+async fn sign_up_tutor(client: &reqwest::Client, address: &str, email: &str) -> (Uuid, String) {
+    let tutor_id: Uuid = client
+        .post(format!("{}/tutors/", address))
+        .json(&serde_json::json!({ "name": "owner", "email": email, "password": PASSWORD }))
+        .send()
+        .await
+        .expect("Failed to create tutor")
+        .json()
+        .await
+        .expect("Failed to parse tutor_id");
+
+    let token = log_in(
+        client,
+        address,
+        serde_json::json!({ "email": email, "password": PASSWORD }),
+    )
+    .await;
+    (tutor_id, token)
+}
+
+// Creates a tutor and one course, returning (tutor_id, course_id, access_token)
+async fn create_tutor_with_course(
+    client: &reqwest::Client,
+    address: &str,
+    email: &str,
+) -> (Uuid, Uuid, String) {
+    let (tutor_id, token) = sign_up_tutor(client, address, email).await;
+
+    let course: serde_json::Value = client
+        .post(format!("{}/courses/", address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "course_name": "Owned Course" }))
+        .send()
+        .await
+        .expect("Failed to create course")
+        .json()
+        .await
+        .expect("Failed to parse course");
+
+    let course_id = course["course_id"].as_str().unwrap().parse().unwrap();
+    (tutor_id, course_id, token)
 }
 
 #[tokio::test]
 async fn health_check_works() {
     // Spawn app
     let address = spawn_app().await;
-    
+
     // Give the server a moment to start up
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    
+
     let client = reqwest::Client::new();
     let response = client
-        .get(&format!("{}/health", &address))
+        .get(format!("{}/health", &address))
         .send()
         .await
         .expect("Failed to execute request.");
-    
+
     assert!(response.status().is_success());
     assert_eq!(200, response.status().as_u16());
-    
+
     // Check response body contains expected message
     let body = response.text().await.expect("Failed to read response body");
     assert!(body.contains("Tutor Services running fine"));
@@ -42,119 +151,129 @@ async fn health_check_works() {
 #[tokio::test]
 async fn test_course_creation() {
     let address = spawn_app().await;
-    
+
     // Give the server a moment to start up
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    
+
     let client = reqwest::Client::new();
-    
+
     // First create a tutor
     let tutor = serde_json::json!({
         "name": "kendrick",
-        "email": "kendrick@gmail.com"
+        "email": "kendrick@gmail.com",
+        "password": PASSWORD
     });
-    
+
     let tutor_response = client
-        .post(&format!("{}/tutors/", &address))
+        .post(format!("{}/tutors/", &address))
         .header("Content-Type", "application/json")
         .json(&tutor)
         .send()
         .await
         .expect("Failed to create tutor");
-    
+
     assert!(tutor_response.status().is_success());
-    
+
     // Get the tutor_id from the response
     let tutor_id: Uuid = tutor_response
         .json()
         .await
         .expect("Failed to parse tutor_id");
-    
-    // Test POST to create a course with correct structure
+
+    // Log in to act as the tutor
+    let tokens: serde_json::Value = client
+        .post(format!("{}/auth/login", &address))
+        .json(&serde_json::json!({ "email": "kendrick@gmail.com", "password": PASSWORD }))
+        .send()
+        .await
+        .expect("Failed to log in")
+        .json()
+        .await
+        .expect("Failed to parse tokens");
+    assert_eq!("Bearer", tokens["token_type"]);
+    let access_token = tokens["access_token"].as_str().unwrap();
+
+    // Test POST to create a course with correct structure; the owner is
+    // whoever the token was issued to
     let new_course = serde_json::json!({
-        "tutor_id": tutor_id.to_string(),
         "course_name": "Test Course for Integration Testing"
     });
-    
+
     let response = client
-        .post(&format!("{}/courses/", &address))
+        .post(format!("{}/courses/", &address))
         .header("Content-Type", "application/json")
+        .bearer_auth(access_token)
         .json(&new_course)
         .send()
         .await
         .expect("Failed to execute request.");
-    
+
     assert!(response.status().is_success());
-    assert_eq!(200, response.status().as_u16());
-    
-    // Check response body
-    let body = response.text().await.expect("Failed to read response body");
-    assert!(body.contains(&format!("Added course for tutor {}", tutor_id)));
+    assert_eq!(201, response.status().as_u16());
+
+    // The created course is returned along with its location
+    let location = response
+        .headers()
+        .get("Location")
+        .expect("Missing Location header")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let course: serde_json::Value = response.json().await.expect("Failed to parse course");
+    let course_id = course["course_id"].as_str().unwrap();
+    assert_eq!(format!("/courses/{}", course_id), location);
+    assert_eq!(tutor_id.to_string(), course["tutor_id"].as_str().unwrap());
+    assert_eq!("Test Course for Integration Testing", course["course_name"]);
+    assert!(course["posted_time"].is_string());
 }
 
 #[tokio::test]
 async fn test_get_tutor_courses() {
     let address = spawn_app().await;
-    
+
     // Give the server a moment to start up
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    
+
     let client = reqwest::Client::new();
-    
+
     // First create a tutor
-    let tutor = serde_json::json!({
-        "name": "test_tutor",
-        "email": "test@example.com"
-    });
-    
-    let tutor_response = client
-        .post(&format!("{}/tutors/", &address))
-        .header("Content-Type", "application/json")
-        .json(&tutor)
-        .send()
-        .await
-        .expect("Failed to create tutor");
-    
-    let tutor_id: Uuid = tutor_response
-        .json()
-        .await
-        .expect("Failed to parse tutor_id");
-    
+    let (tutor_id, token) = sign_up_tutor(&client, &address, "test@example.com").await;
+
     // Create a course for this tutor
     let new_course = serde_json::json!({
-        "tutor_id": tutor_id.to_string(),
         "course_name": "Test Course"
     });
-    
+
     let _create_response = client
-        .post(&format!("{}/courses/", &address))
+        .post(format!("{}/courses/", &address))
         .header("Content-Type", "application/json")
+        .bearer_auth(&token)
         .json(&new_course)
         .send()
         .await
         .expect("Failed to create course.");
-    
+
     // Then, retrieve courses for this tutor using the correct endpoint
     let response = client
-        .get(&format!("{}/tutors/{}/courses", &address, tutor_id))
+        .get(format!("{}/tutors/{}/courses", &address, tutor_id))
         .send()
         .await
         .expect("Failed to execute request.");
-    
+
     assert!(response.status().is_success());
     assert_eq!(200, response.status().as_u16());
-    
+
     // Parse JSON response
     let courses: serde_json::Value = response
         .json()
         .await
         .expect("Failed to parse JSON response");
-    
+
     // Should be an array with one course
     assert!(courses.is_array());
     let courses_array = courses.as_array().unwrap();
     assert_eq!(1, courses_array.len());
-    
+
     // Check course details
     let course = &courses_array[0];
     assert_eq!(tutor_id.to_string(), course["tutor_id"].as_str().unwrap());
@@ -166,76 +285,52 @@ async fn test_get_tutor_courses() {
 #[tokio::test]
 async fn test_get_course_details() {
     let address = spawn_app().await;
-    
+
     // Give the server a moment to start up
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    
+
     let client = reqwest::Client::new();
-    
+
     // First create a tutor
-    let tutor = serde_json::json!({
-        "name": "detail_tutor",
-        "email": "detail@example.com"
-    });
-    
-    let tutor_response = client
-        .post(&format!("{}/tutors/", &address))
-        .header("Content-Type", "application/json")
-        .json(&tutor)
-        .send()
-        .await
-        .expect("Failed to create tutor");
-    
-    let tutor_id: Uuid = tutor_response
-        .json()
-        .await
-        .expect("Failed to parse tutor_id");
-    
+    let (tutor_id, token) = sign_up_tutor(&client, &address, "detail@example.com").await;
+
     // Create a course
     let new_course = serde_json::json!({
-        "tutor_id": tutor_id.to_string(),
         "course_name": "Detailed Test Course"
     });
-    
+
     let create_response = client
-        .post(&format!("{}/courses/", &address))
+        .post(format!("{}/courses/", &address))
         .header("Content-Type", "application/json")
+        .bearer_auth(&token)
         .json(&new_course)
         .send()
         .await
         .expect("Failed to create course.");
-    
+
     assert!(create_response.status().is_success());
-    
-    // Get the courses to find the course_id
-    let courses_response = client
-        .get(&format!("{}/tutors/{}/courses", &address, tutor_id))
-        .send()
-        .await
-        .expect("Failed to get courses");
-    
-    let courses: serde_json::Value = courses_response
+
+    let created: serde_json::Value = create_response
         .json()
         .await
-        .expect("Failed to parse courses");
-    
-    let course_id = courses[0]["course_id"].as_str().unwrap();
-    
+        .expect("Failed to parse course");
+    let course_id = created["course_id"].as_str().unwrap();
+
     // Then, get specific course details
     let response = client
-        .get(&format!("{}/courses/{}", &address, course_id))
+        .get(format!("{}/courses/{}", &address, course_id))
         .send()
         .await
         .expect("Failed to execute request.");
-    
+
     assert!(response.status().is_success());
-    
+
     // Parse JSON response
     let course: serde_json::Value = response
         .json()
         .await
         .expect("Failed to parse JSON response");
-    
+
     assert_eq!(tutor_id.to_string(), course["tutor_id"].as_str().unwrap());
     assert_eq!(course_id, course["course_id"].as_str().unwrap());
     assert_eq!("Detailed Test Course", course["course_name"]);
@@ -245,76 +340,3059 @@ async fn test_get_course_details() {
 #[tokio::test]
 async fn test_course_not_found() {
     let address = spawn_app().await;
-    
+
     // Give the server a moment to start up
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    
+
     let client = reqwest::Client::new();
-    
+
     // Generate a random UUID that definitely doesn't exist
     let non_existent_id = Uuid::new_v4();
-    
+
     // Try to get a non-existent course
     let response = client
-        .get(&format!("{}/courses/{}", &address, non_existent_id))
+        .get(format!("{}/courses/{}", &address, non_existent_id))
         .send()
         .await
         .expect("Failed to execute request.");
-    
+
     assert_eq!(404, response.status().as_u16());
-    
+
     let body = response.text().await.expect("Failed to read response body");
     assert!(body.contains(&format!("Course with ID {} not found", non_existent_id)));
 }
 
 #[tokio::test]
-async fn test_get_tutor_id_by_details() {
+async fn test_get_my_profile() {
     let address = spawn_app().await;
-    
-    // Give the server a moment to start up
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    
     let client = reqwest::Client::new();
-    
-    // First create a tutor
-    let tutor = serde_json::json!({
-        "name": "lookup_tutor",
-        "email": "lookup@example.com"
-    });
-    
-    let tutor_response = client
-        .post(&format!("{}/tutors/", &address))
-        .header("Content-Type", "application/json")
-        .json(&tutor)
+
+    let (tutor_id, course_id, token) =
+        create_tutor_with_course(&client, &address, "me@example.com").await;
+    let (_, student_token) = create_student(&client, &address, "not-me@example.com").await;
+
+    let response = client
+        .get(format!("{}/tutors/me", &address))
         .send()
         .await
-        .expect("Failed to create tutor");
-    
-    let expected_tutor_id: Uuid = tutor_response
-        .json()
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+
+    let response = client
+        .get(format!("{}/tutors/me", &address))
+        .bearer_auth(&student_token)
+        .send()
         .await
-        .expect("Failed to parse tutor_id");
-    
-    // Now lookup the tutor by name and email
-    let lookup_data = serde_json::json!({
-        "name": "lookup_tutor",
-        "email": "lookup@example.com"
-    });
-    
-    let lookup_response = client
-        .post(&format!("{}/tutors/id", &address))
-        .header("Content-Type", "application/json")
-        .json(&lookup_data)
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+
+    let response = client
+        .get(format!("{}/tutors/me", &address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+    let profile: serde_json::Value = response.json().await.expect("Failed to parse profile");
+    assert_eq!(tutor_id.to_string(), profile["tutor_id"].as_str().unwrap());
+    assert_eq!("me@example.com", profile["email"]);
+    assert_eq!("tutor", profile["role"]);
+    let courses = profile["courses"].as_array().unwrap();
+    assert_eq!(1, courses.len());
+    assert_eq!(
+        course_id.to_string(),
+        courses[0]["course_id"].as_str().unwrap()
+    );
+
+    // The anonymous name/email lookup is gone
+    let response = client
+        .post(format!("{}/tutors/id", &address))
+        .json(&serde_json::json!({ "name": "Test Tutor", "email": "me@example.com" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn test_search_tutors_by_email_is_admin_only() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, tutor_token) = sign_up_tutor(&client, &address, "Sought@Example.com").await;
+    sign_up_tutor(&client, &address, "other@example.com").await;
+    let admin_token = log_in_admin(&client, &address).await;
+
+    let search = |token: &str, query: &str| {
+        client
+            .get(format!("{}/tutors/search?{}", &address, query))
+            .bearer_auth(token)
+            .send()
+    };
+
+    let response = search(&tutor_token, "email=sought").await.unwrap();
+    assert_eq!(403, response.status().as_u16());
+
+    let response = search(&admin_token, "email=SOUGHT").await.unwrap();
+    assert_eq!(200, response.status().as_u16());
+    let tutors: Vec<serde_json::Value> = response.json().await.expect("Failed to parse tutors");
+    assert_eq!(1, tutors.len());
+    assert_eq!(
+        tutor_id.to_string(),
+        tutors[0]["tutor_id"].as_str().unwrap()
+    );
+
+    let response = search(&admin_token, "email=example.com&limit=2")
+        .await
+        .unwrap();
+    let tutors: Vec<serde_json::Value> = response.json().await.expect("Failed to parse tutors");
+    assert_eq!(2, tutors.len());
+
+    let response = search(&admin_token, "email=").await.unwrap();
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn postgres_backend_requires_pool() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind port");
+    let config = Config {
+        backend: tutor_nodb::StorageBackend::Postgres,
+        db_pool: None,
+        admin: None,
+        upload_dir: None,
+        max_upload_bytes: None,
+    };
+
+    let result = run_with(listener, config).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_update_course() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, token) =
+        create_tutor_with_course(&client, &address, "update@example.com").await;
+
+    let response = client
+        .patch(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "course_name": "Renamed Course",
+            "refresh_posted_time": true
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    let course: serde_json::Value = response.json().await.expect("Failed to parse course");
+    assert_eq!("Renamed Course", course["course_name"]);
+
+    // The change is visible on subsequent reads
+    let course: serde_json::Value = client
+        .get(format!("{}/courses/{}", &address, course_id))
         .send()
         .await
-        .expect("Failed to lookup tutor");
-    
-    assert!(lookup_response.status().is_success());
-    
-    let found_tutor_id: Uuid = lookup_response
+        .expect("Failed to execute request.")
         .json()
         .await
-        .expect("Failed to parse found tutor_id");
-    
-    assert_eq!(expected_tutor_id, found_tutor_id);
-}
\ No newline at end of file
+        .expect("Failed to parse course");
+    assert_eq!("Renamed Course", course["course_name"]);
+
+    let response = client
+        .put(format!("{}/courses/{}", &address, Uuid::new_v4()))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "course_name": "x" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_update_course_by_other_tutor_is_forbidden() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, _) = create_tutor_with_course(&client, &address, "owner@example.com").await;
+    let (_, other_token) = sign_up_tutor(&client, &address, "intruder@example.com").await;
+
+    let response = client
+        .put(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&other_token)
+        .json(&serde_json::json!({ "course_name": "Hijacked" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+
+    let response = client
+        .delete(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&other_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_delete_course() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, token) =
+        create_tutor_with_course(&client, &address, "delete@example.com").await;
+
+    let response = client
+        .delete(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+
+    let response = client
+        .get(format!("{}/courses/{}", &address, course_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+
+    let response = client
+        .delete(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_duplicate_tutor_email_conflicts() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let tutor =
+        serde_json::json!({ "name": "first", "email": "taken@example.com", "password": PASSWORD });
+    let response = client
+        .post(format!("{}/tutors/", &address))
+        .json(&tutor)
+        .send()
+        .await
+        .expect("Failed to create tutor");
+    assert!(response.status().is_success());
+
+    let tutor =
+        serde_json::json!({ "name": "second", "email": "taken@example.com", "password": PASSWORD });
+    let response = client
+        .post(format!("{}/tutors/", &address))
+        .json(&tutor)
+        .send()
+        .await
+        .expect("Failed to create tutor");
+    assert_eq!(409, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_tutor_crud() {
+    check_tutor_crud(spawn_app().await).await;
+}
+
+#[tokio::test]
+async fn test_tutor_crud_on_postgres() {
+    if let Some((address, _)) = spawn_postgres_app().await {
+        check_tutor_crud(address).await;
+    }
+}
+
+async fn check_tutor_crud(address: String) {
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let email = unique_email("crud");
+    let (tutor_id, token) = sign_up_tutor(&client, &address, &email).await;
+
+    let response = client
+        .get(format!("{}/tutors/{}", &address, tutor_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+    let tutor: serde_json::Value = response.json().await.expect("Failed to parse tutor");
+    assert_eq!("owner", tutor["name"]);
+    assert!(tutor.get("email").is_none());
+
+    // Only the tutor themselves may edit their profile, or see its email
+    let (_, other_token) = sign_up_tutor(&client, &address, &unique_email("nosy")).await;
+    let get_tutor = |token: &str| {
+        client
+            .get(format!("{}/tutors/{}", &address, tutor_id))
+            .bearer_auth(token)
+            .send()
+    };
+    let tutor: serde_json::Value = get_tutor(&other_token).await.unwrap().json().await.unwrap();
+    assert!(tutor.get("email").is_none());
+    let tutor: serde_json::Value = get_tutor(&token).await.unwrap().json().await.unwrap();
+    assert_eq!(email.as_str(), tutor["email"]);
+    let response = client
+        .patch(format!("{}/tutors/{}", &address, tutor_id))
+        .bearer_auth(&other_token)
+        .json(&serde_json::json!({ "name": "hijacked" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+
+    let new_email = unique_email("crud-new");
+    let response = client
+        .patch(format!("{}/tutors/{}", &address, tutor_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "email": new_email }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+    let tutor: serde_json::Value = response.json().await.expect("Failed to parse tutor");
+    assert_eq!("owner", tutor["name"]);
+    assert_eq!(new_email.as_str(), tutor["email"]);
+
+    let response = client
+        .delete(format!("{}/tutors/{}", &address, tutor_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+
+    let response = client
+        .get(format!("{}/tutors/{}", &address, tutor_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_delete_tutor_with_courses_conflicts() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, _, token) =
+        create_tutor_with_course(&client, &address, "busy@example.com").await;
+
+    let response = client
+        .delete(format!("{}/tutors/{}", &address, tutor_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(409, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_invalid_tutor_reports_every_field() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/tutors/", &address))
+        .json(&serde_json::json!({ "name": "", "email": "not-an-email" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.expect("Failed to parse errors");
+    assert_eq!("validation_failed", body["code"]);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(vec!["name", "email", "password"], fields);
+}
+
+#[tokio::test]
+async fn test_invalid_course_body_is_rejected() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, token) = sign_up_tutor(&client, &address, "invalid@example.com").await;
+
+    // A blank course_name and a bad enrolled_limit are reported together
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "course_name": " ", "enrolled_limit": 0 }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.expect("Failed to parse errors");
+    assert_eq!(2, body["details"].as_array().unwrap().len());
+
+    // Unknown fields are rejected as JSON too
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "course_name": "Course",
+            "discount": 10
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.expect("Failed to parse errors");
+    assert_eq!("body", body["details"][0]["field"]);
+}
+
+#[tokio::test]
+async fn test_errors_are_json() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let non_existent_id = Uuid::new_v4();
+    let response = client
+        .get(format!("{}/courses/{}", &address, non_existent_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.expect("Failed to parse error");
+    assert_eq!("not_found", body["code"]);
+    assert_eq!(
+        format!("Course with ID {} not found", non_existent_id),
+        body["message"]
+    );
+
+    // Malformed path parameters and unknown routes share the same shape
+    for path in ["/courses/not-a-uuid", "/no/such/route"] {
+        let response = client
+            .get(format!("{}{}", &address, path))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(404, response.status().as_u16());
+        let body: serde_json::Value = response.json().await.expect("Failed to parse error");
+        assert_eq!("not_found", body["code"]);
+    }
+}
+
+#[tokio::test]
+async fn test_course_creation_requires_authentication() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .json(&serde_json::json!({ "course_name": "Orphan Course" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.expect("Failed to parse error");
+    assert_eq!("unauthorized", body["code"]);
+
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth("not-a-token")
+        .json(&serde_json::json!({ "course_name": "Orphan Course" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+
+    // A token outliving its tutor no longer creates courses
+    let (tutor_id, token) = sign_up_tutor(&client, &address, "gone@example.com").await;
+    client
+        .delete(format!("{}/tutors/{}", &address, tutor_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to delete tutor");
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "course_name": "Orphan Course" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_login_and_refresh() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, _) = sign_up_tutor(&client, &address, "login@example.com").await;
+
+    // Wrong passwords and unknown emails get the same answer
+    for (email, password) in [
+        ("login@example.com", "wrong password"),
+        ("nobody@example.com", PASSWORD),
+    ] {
+        let response = client
+            .post(format!("{}/auth/login", &address))
+            .json(&serde_json::json!({ "email": email, "password": password }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(401, response.status().as_u16());
+        let body: serde_json::Value = response.json().await.expect("Failed to parse error");
+        assert_eq!("Invalid email or password", body["message"]);
+    }
+
+    let tokens: serde_json::Value = client
+        .post(format!("{}/auth/login", &address))
+        .json(&serde_json::json!({ "email": "login@example.com", "password": PASSWORD }))
+        .send()
+        .await
+        .expect("Failed to log in")
+        .json()
+        .await
+        .expect("Failed to parse tokens");
+    let refresh_token = tokens["refresh_token"].as_str().unwrap();
+
+    // Refresh tokens are not accepted in place of access tokens
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(refresh_token)
+        .json(&serde_json::json!({ "course_name": "Sneaky" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+
+    let response = client
+        .post(format!("{}/auth/refresh", &address))
+        .json(&serde_json::json!({ "refresh_token": refresh_token }))
+        .send()
+        .await
+        .expect("Failed to refresh");
+    assert_eq!(200, response.status().as_u16());
+    let refreshed: serde_json::Value = response.json().await.expect("Failed to parse tokens");
+
+    let course: serde_json::Value = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(refreshed["access_token"].as_str().unwrap())
+        .json(&serde_json::json!({ "course_name": "Refreshed" }))
+        .send()
+        .await
+        .expect("Failed to create course")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    assert_eq!(tutor_id.to_string(), course["tutor_id"].as_str().unwrap());
+
+    let response = client
+        .post(format!("{}/auth/refresh", &address))
+        .json(&serde_json::json!({ "refresh_token": tokens["access_token"] }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_roles_are_enforced() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, course_id, _) =
+        create_tutor_with_course(&client, &address, "moderated@example.com").await;
+    let admin_token = log_in_admin(&client, &address).await;
+    let (other_tutor, other_token) = sign_up_tutor(&client, &address, "peer@example.com").await;
+
+    // Signing up with the admin's address, in any case, grants nothing
+    let response = client
+        .post(format!("{}/tutors/", &address))
+        .json(
+            &serde_json::json!({ "name": "impostor", "email": ADMIN_EMAIL, "password": PASSWORD }),
+        )
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(409, response.status().as_u16());
+    let (_, impostor_token) = sign_up_tutor(&client, &address, &ADMIN_EMAIL.to_uppercase()).await;
+    let response = client
+        .get(format!("{}/tutors/search?email=example", &address))
+        .bearer_auth(&impostor_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+    let (student_id, student_token) = create_student(&client, &address, "pupil@example.com").await;
+
+    // Students cannot post courses and tutors cannot enroll
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&student_token)
+        .json(&serde_json::json!({ "course_name": "Student Course" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.expect("Failed to parse error");
+    assert_eq!("forbidden", body["code"]);
+
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&other_token)
+        .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+
+    // Only admins hand out roles
+    let promote = |token: &str, role: &str| {
+        client
+            .put(format!("{}/tutors/{}/role", &address, other_tutor))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "role": role }))
+            .send()
+    };
+    assert_eq!(
+        403,
+        promote(&other_token, "admin")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        400,
+        promote(&admin_token, "student")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+
+    // Admins moderate any tutor's courses
+    let response = client
+        .patch(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({ "course_name": "Moderated" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+    let course: serde_json::Value = response.json().await.expect("Failed to parse course");
+    assert_eq!(tutor_id.to_string(), course["tutor_id"].as_str().unwrap());
+
+    // A promotion shows up in the next token
+    assert_eq!(
+        204,
+        promote(&admin_token, "admin")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let body = serde_json::json!({ "email": "peer@example.com", "password": PASSWORD });
+    let promoted_token = log_in(&client, &address, body).await;
+    let response = client
+        .delete(format!("{}/courses/{}", &address, course_id))
+        .bearer_auth(&promoted_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+}
+
+// Signs a student up and logs them in, returning (student_id, access_token)
+async fn create_student(client: &reqwest::Client, address: &str, email: &str) -> (Uuid, String) {
+    let student: serde_json::Value = client
+        .post(format!("{}/students/", address))
+        .json(&serde_json::json!({ "name": "student", "email": email, "password": PASSWORD }))
+        .send()
+        .await
+        .expect("Failed to create student")
+        .json()
+        .await
+        .expect("Failed to parse student");
+
+    let body = serde_json::json!({ "email": email, "password": PASSWORD, "account": "student" });
+    let token = log_in(client, address, body).await;
+    (
+        student["student_id"].as_str().unwrap().parse().unwrap(),
+        token,
+    )
+}
+
+#[tokio::test]
+async fn test_student_profile_hides_email() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (student_id, token) = create_student(&client, &address, "private@example.com").await;
+    let (_, other_token) = create_student(&client, &address, "curious@example.com").await;
+    let (_, _, tutor_token) =
+        create_tutor_with_course(&client, &address, "teacher@example.com").await;
+    let profile = format!("{}/students/{}", &address, student_id);
+
+    let student: serde_json::Value = client
+        .get(&profile)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("student", student["name"]);
+    assert!(student.get("email").is_none());
+    for other in [&other_token, &tutor_token] {
+        let student: serde_json::Value = client
+            .get(&profile)
+            .bearer_auth(other)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(student.get("email").is_none());
+    }
+    let student: serde_json::Value = client
+        .get(&profile)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("private@example.com", student["email"]);
+
+    let response = client
+        .get(format!("{}/students/{}", &address, Uuid::new_v4()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_enroll_and_withdraw_student() {
+    check_enroll_and_withdraw_student(spawn_app().await).await;
+}
+
+#[tokio::test]
+async fn test_enroll_and_withdraw_student_on_postgres() {
+    if let Some((address, _)) = spawn_postgres_app().await {
+        check_enroll_and_withdraw_student(address).await;
+    }
+}
+
+async fn check_enroll_and_withdraw_student(address: String) {
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, _) =
+        create_tutor_with_course(&client, &address, &unique_email("seats")).await;
+    let (student_id, token) = create_student(&client, &address, &unique_email("learner")).await;
+
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+
+    // Enrolling twice conflicts
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(409, response.status().as_u16());
+
+    let course: serde_json::Value = client
+        .get(format!("{}/courses/{}", &address, course_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    assert_eq!(1, course["enrolled"]);
+
+    let response = client
+        .delete(format!(
+            "{}/courses/{}/enrollments/{}",
+            &address, course_id, student_id
+        ))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+
+    let response = client
+        .delete(format!(
+            "{}/courses/{}/enrollments/{}",
+            &address, course_id, student_id
+        ))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_enrollment_respects_limit() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, token) = sign_up_tutor(&client, &address, "limited@example.com").await;
+
+    let course: serde_json::Value = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "course_name": "One Seat",
+            "enrolled_limit": 1
+        }))
+        .send()
+        .await
+        .expect("Failed to create course")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    let course_id = course["course_id"].as_str().unwrap();
+
+    let (first, first_token) = create_student(&client, &address, "first@example.com").await;
+    let (second, second_token) = create_student(&client, &address, "second@example.com").await;
+
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&first_token)
+        .json(&serde_json::json!({ "student_id": first.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&second_token)
+        .json(&serde_json::json!({ "student_id": second.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    // A full course queues the student instead of refusing them
+    assert_eq!(202, response.status().as_u16());
+    let entry: serde_json::Value = response.json().await.expect("Failed to parse entry");
+    assert_eq!(1, entry["position"]);
+
+    let course: serde_json::Value = client
+        .get(format!("{}/courses/{}", &address, course_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    assert_eq!(1, course["enrolled"]);
+}
+
+#[tokio::test]
+async fn test_withdrawal_promotes_waitlist() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, token) = sign_up_tutor(&client, &address, "queue@example.com").await;
+
+    let course: serde_json::Value = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "course_name": "Popular",
+            "enrolled_limit": 1
+        }))
+        .send()
+        .await
+        .expect("Failed to create course")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    let course_id = course["course_id"].as_str().unwrap();
+
+    let seated = create_student(&client, &address, "seated@example.com").await;
+    let next = create_student(&client, &address, "next@example.com").await;
+    let last = create_student(&client, &address, "last@example.com").await;
+    for ((student_id, token), expected) in
+        [(&seated, 201), (&next, 202), (&last, 202), (&next, 409)]
+    {
+        let response = client
+            .post(format!("{}/courses/{}/enrollments", &address, course_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(expected, response.status().as_u16());
+    }
+
+    let response = client
+        .delete(format!(
+            "{}/courses/{}/enrollments/{}",
+            &address, course_id, seated.0
+        ))
+        .bearer_auth(&seated.1)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+
+    // `next` took the freed seat, so enrolling again is a duplicate
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&next.1)
+        .json(&serde_json::json!({ "student_id": next.0.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(409, response.status().as_u16());
+
+    let waitlist: serde_json::Value = client
+        .get(format!("{}/courses/{}/waitlist", &address, course_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse waitlist");
+    assert_eq!(1, waitlist.as_array().unwrap().len());
+    assert_eq!(last.0.to_string(), waitlist[0]["student_id"]);
+    assert_eq!(1, waitlist[0]["position"]);
+
+    let response = client
+        .delete(format!(
+            "{}/courses/{}/enrollments/{}",
+            &address, course_id, last.0
+        ))
+        .bearer_auth(&last.1)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+
+    let response = client
+        .get(format!("{}/courses/{}/waitlist", &address, Uuid::new_v4()))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_reviews_update_ratings() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, course_id, _) =
+        create_tutor_with_course(&client, &address, "reviewed@example.com").await;
+    let fan = create_student(&client, &address, "fan@example.com").await;
+    let critic = create_student(&client, &address, "critic@example.com").await;
+    let outsider = create_student(&client, &address, "outsider@example.com").await;
+    for (student_id, token) in [&fan, &critic] {
+        client
+            .post(format!("{}/courses/{}/enrollments", &address, course_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+            .send()
+            .await
+            .expect("Failed to enroll");
+    }
+
+    let review = |(student_id, token): &(Uuid, String), rating: i64| {
+        client
+            .post(format!("{}/courses/{}/reviews", &address, course_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({
+                "student_id": student_id.to_string(),
+                "rating": rating,
+                "body": "Thoughts"
+            }))
+            .send()
+    };
+    assert_eq!(201, review(&fan, 5).await.unwrap().status().as_u16());
+    assert_eq!(201, review(&critic, 2).await.unwrap().status().as_u16());
+    // A second review replaces the first
+    assert_eq!(200, review(&critic, 4).await.unwrap().status().as_u16());
+    assert_eq!(403, review(&outsider, 1).await.unwrap().status().as_u16());
+    assert_eq!(400, review(&fan, 6).await.unwrap().status().as_u16());
+    // Students review only as themselves
+    let impostor = (fan.0, critic.1.clone());
+    assert_eq!(403, review(&impostor, 1).await.unwrap().status().as_u16());
+
+    let course: serde_json::Value = client
+        .get(format!("{}/courses/{}", &address, course_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    assert_eq!(4.5, course["rating"]);
+
+    let tutor: serde_json::Value = client
+        .get(format!("{}/tutors/{}", &address, tutor_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse tutor");
+    assert_eq!(4.5, tutor["rating"]);
+
+    let reviews: serde_json::Value = client
+        .get(format!("{}/courses/{}/reviews", &address, course_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse reviews");
+    assert_eq!(2, reviews.as_array().unwrap().len());
+    assert_eq!(critic.0.to_string(), reviews[0]["student_id"]);
+}
+
+#[tokio::test]
+async fn test_paid_courses_and_price_filter() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, free_course, token) =
+        create_tutor_with_course(&client, &address, "pricing@example.com").await;
+
+    // PAID needs a price and currency; FREE must not have them
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "course_name": "Unpriced",
+            "course_type": "PAID",
+            "currency": "usd"
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.expect("Failed to parse error");
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(vec!["price", "currency"], fields);
+
+    let response = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "course_name": "Gratis",
+            "price": "5"
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+
+    let mut paid_courses = Vec::new();
+    for price in ["9.99", "49.00"] {
+        let course: serde_json::Value = client
+            .post(format!("{}/courses/", &address))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "course_name": format!("Paid {price}"),
+                "course_type": "PAID",
+                "price": price,
+                "currency": "EUR"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .expect("Failed to parse course");
+        assert_eq!("PAID", course["course_type"]);
+        assert_eq!(price, course["price"]);
+        paid_courses.push(course["course_id"].as_str().unwrap().to_string());
+    }
+
+    let ids = |url: String| {
+        let client = client.clone();
+        async move {
+            let courses: serde_json::Value = client
+                .get(url)
+                .send()
+                .await
+                .expect("Failed to execute request.")
+                .json()
+                .await
+                .expect("Failed to parse courses");
+            courses["courses"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|c| c["course_id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        vec![paid_courses[1].clone()],
+        ids(format!(
+            "{}/courses/?course_type=PAID&min_price=10",
+            &address
+        ))
+        .await
+    );
+    assert_eq!(
+        vec![free_course.to_string(), paid_courses[0].clone()],
+        ids(format!("{}/courses/?max_price=10", &address)).await
+    );
+
+    let response = client
+        .get(format!("{}/courses/?min_price=20&max_price=10", &address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+
+    // Switching to FREE drops the price
+    let course: serde_json::Value = client
+        .patch(format!("{}/courses/{}", &address, paid_courses[0]))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "course_type": "FREE" }))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    assert_eq!("FREE", course["course_type"]);
+    assert!(course["price"].is_null());
+}
+
+#[tokio::test]
+async fn test_course_catalogue_pages_and_sorts() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, _, token) =
+        create_tutor_with_course(&client, &address, "catalogue@example.com").await;
+    let (other_tutor, _, _) =
+        create_tutor_with_course(&client, &address, "other@example.com").await;
+    for course_name in ["zebra", "Apple", "mango"] {
+        client
+            .post(format!("{}/courses/", &address))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "course_name": course_name
+            }))
+            .send()
+            .await
+            .expect("Failed to create course");
+    }
+
+    let page = |query: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}/courses/?{}", address, query))
+                .send()
+                .await
+                .expect("Failed to execute request.")
+        }
+    };
+    let names = |page: &serde_json::Value| -> Vec<String> {
+        page["courses"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["course_name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let first: serde_json::Value =
+        page(format!("tutor_id={tutor_id}&sort=name&order=desc&limit=3"))
+            .await
+            .json()
+            .await
+            .expect("Failed to parse page");
+    assert_eq!(4, first["total"]);
+    assert_eq!(vec!["zebra", "Owned Course", "mango"], names(&first));
+    assert_eq!(3, first["next_offset"]);
+
+    let last: serde_json::Value = page(format!(
+        "tutor_id={tutor_id}&sort=name&order=desc&limit=3&offset=3"
+    ))
+    .await
+    .json()
+    .await
+    .expect("Failed to parse page");
+    assert_eq!(vec!["Apple"], names(&last));
+    assert!(last["next_offset"].is_null());
+
+    // Newest first across every tutor
+    let newest: serde_json::Value = page("order=desc&limit=1".to_string())
+        .await
+        .json()
+        .await
+        .expect("Failed to parse page");
+    assert_eq!(5, newest["total"]);
+    assert_eq!(vec!["mango"], names(&newest));
+
+    let only_other: serde_json::Value = page(format!("tutor_id={other_tutor}"))
+        .await
+        .json()
+        .await
+        .expect("Failed to parse page");
+    assert_eq!(1, only_other["total"]);
+
+    for bad_query in ["sort=popularity", "limit=0", "limit=101", "offset=-1"] {
+        assert_eq!(
+            400,
+            page(bad_query.to_string()).await.status().as_u16(),
+            "{bad_query}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_search_courses() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, _, token) = create_tutor_with_course(&client, &address, "search@example.com").await;
+    for (course_name, description) in [
+        ("Evening Pottery", "Throwing bowls on the wheel"),
+        ("Wheel Basics", "Start here"),
+        ("Knitting", "Scarves and hats"),
+    ] {
+        client
+            .post(format!("{}/courses/", &address))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "course_name": course_name,
+                "description": description
+            }))
+            .send()
+            .await
+            .expect("Failed to create course");
+    }
+
+    let hits: serde_json::Value = client
+        .get(format!("{}/courses/search?q=wheel", &address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse hits");
+    let hits = hits.as_array().unwrap();
+    // The name match ranks above the description match
+    assert_eq!(2, hits.len());
+    assert_eq!("Wheel Basics", hits[0]["course"]["course_name"]);
+    assert_eq!(
+        "<mark>Wheel</mark> Basics",
+        hits[0]["highlight"]["course_name"]
+    );
+    assert_eq!(
+        "Throwing bowls on the <mark>wheel</mark>",
+        hits[1]["highlight"]["description"]
+    );
+
+    let response = client
+        .get(format!("{}/courses/search?q=%20", &address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, response.status().as_u16());
+}
+
+// `hour:minute` UTC on the day `days` from now, in RFC 3339
+fn day_at(days: i64, hour: u32, minute: u32) -> String {
+    let day = (chrono::Utc::now() + chrono::Duration::days(days)).date_naive();
+    day.and_hms_opt(hour, minute, 0)
+        .unwrap()
+        .and_utc()
+        .to_rfc3339()
+}
+
+#[tokio::test]
+async fn test_availability_and_bookings() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, tutor_token) = sign_up_tutor(&client, &address, "calendar@example.com").await;
+    let (_, other_tutor_token) = sign_up_tutor(&client, &address, "rival@example.com").await;
+    let (student_id, student_token) = create_student(&client, &address, "booker@example.com").await;
+    let (other_student, other_token) =
+        create_student(&client, &address, "latecomer@example.com").await;
+
+    let every_day: Vec<serde_json::Value> = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+        .iter()
+        .map(|day| serde_json::json!({ "weekday": day, "start": "09:00", "end": "17:00" }))
+        .collect();
+    let set_availability = |token: &str, timezone: &str| {
+        client
+            .put(format!("{}/tutors/{}/availability", &address, tutor_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "timezone": timezone, "slots": every_day }))
+            .send()
+    };
+    assert_eq!(
+        400,
+        set_availability(&tutor_token, "Mars/Olympus")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        403,
+        set_availability(&other_tutor_token, "UTC")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        200,
+        set_availability(&tutor_token, "UTC")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+
+    let availability: serde_json::Value = client
+        .get(format!("{}/tutors/{}/availability", &address, tutor_id))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse availability");
+    assert_eq!("UTC", availability["timezone"]);
+    assert_eq!(7, availability["slots"].as_array().unwrap().len());
+
+    let book = |token: &str, student_id: Uuid, starts_at: String, ends_at: String| {
+        client
+            .post(format!("{}/tutors/{}/bookings", &address, tutor_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({
+                "student_id": student_id.to_string(),
+                "starts_at": starts_at,
+                "ends_at": ends_at,
+            }))
+            .send()
+    };
+
+    let response = book(
+        &student_token,
+        student_id,
+        day_at(3, 10, 0),
+        day_at(3, 11, 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    let booking: serde_json::Value = response.json().await.expect("Failed to parse booking");
+    assert_eq!("confirmed", booking["status"]);
+    let booking_id = booking["booking_id"].as_str().unwrap().to_string();
+
+    // Overlapping the first booking, outside the weekly slots, and for
+    // another student
+    let response = book(
+        &other_token,
+        other_student,
+        day_at(3, 10, 30),
+        day_at(3, 11, 30),
+    )
+    .await
+    .unwrap();
+    assert_eq!(409, response.status().as_u16());
+    let response = book(
+        &other_token,
+        other_student,
+        day_at(3, 18, 0),
+        day_at(3, 19, 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(409, response.status().as_u16());
+    let response = book(&other_token, student_id, day_at(3, 12, 0), day_at(3, 13, 0))
+        .await
+        .unwrap();
+    assert_eq!(403, response.status().as_u16());
+
+    // Time off removes the hour from the open windows
+    let response = client
+        .post(format!(
+            "{}/tutors/{}/availability/exceptions",
+            &address, tutor_id
+        ))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({
+            "kind": "blocked",
+            "starts_at": day_at(3, 13, 0),
+            "ends_at": day_at(3, 14, 0),
+            "reason": "Lunch",
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+    let open: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/tutors/{}/availability/open",
+            &address, tutor_id
+        ))
+        .query(&[("from", day_at(3, 0, 0)), ("to", day_at(4, 0, 0))])
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse open windows");
+    let starts: Vec<String> = open
+        .iter()
+        .map(|window| {
+            window["starts_at"]
+                .as_str()
+                .unwrap()
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+                .to_rfc3339()
+        })
+        .collect();
+    assert_eq!(
+        vec![day_at(3, 9, 0), day_at(3, 11, 0), day_at(3, 14, 0)],
+        starts
+    );
+    let response = book(
+        &other_token,
+        other_student,
+        day_at(3, 13, 30),
+        day_at(3, 14, 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(409, response.status().as_u16());
+
+    let bookings: Vec<serde_json::Value> = client
+        .get(format!("{}/tutors/{}/bookings", &address, tutor_id))
+        .bearer_auth(&tutor_token)
+        .query(&[("from", day_at(3, 0, 0))])
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse bookings");
+    assert_eq!(1, bookings.len());
+
+    // Students may cancel well ahead, once
+    let cancel = |token: &str, booking_id: &str| {
+        client
+            .post(format!(
+                "{}/tutors/{}/bookings/{}/cancel",
+                &address, tutor_id, booking_id
+            ))
+            .bearer_auth(token)
+            .send()
+    };
+    assert_eq!(
+        403,
+        cancel(&other_token, &booking_id)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let response = cancel(&student_token, &booking_id).await.unwrap();
+    assert_eq!(200, response.status().as_u16());
+    let cancelled: serde_json::Value = response.json().await.expect("Failed to parse booking");
+    assert_eq!("cancelled", cancelled["status"]);
+    assert_eq!(
+        409,
+        cancel(&student_token, &booking_id)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+
+    // The freed slot can be booked again
+    let response = book(
+        &other_token,
+        other_student,
+        day_at(3, 10, 30),
+        day_at(3, 11, 30),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+
+    // Inside the notice period only the tutor can cancel
+    let soon = chrono::Utc::now() + chrono::Duration::hours(2);
+    client
+        .post(format!(
+            "{}/tutors/{}/availability/exceptions",
+            &address, tutor_id
+        ))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({
+            "kind": "extra",
+            "starts_at": soon.to_rfc3339(),
+            "ends_at": (soon + chrono::Duration::hours(1)).to_rfc3339(),
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = book(
+        &student_token,
+        student_id,
+        soon.to_rfc3339(),
+        (soon + chrono::Duration::minutes(30)).to_rfc3339(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    let booking: serde_json::Value = response.json().await.expect("Failed to parse booking");
+    let soon_id = booking["booking_id"].as_str().unwrap().to_string();
+    assert_eq!(
+        403,
+        cancel(&student_token, &soon_id)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        200,
+        cancel(&tutor_token, &soon_id)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+}
+
+#[tokio::test]
+async fn test_calendar_export_and_import() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, "exporter@example.com").await;
+    let (other_id, other_token) = sign_up_tutor(&client, &address, "importer@example.com").await;
+    let (student_id, student_token) =
+        create_student(&client, &address, "subscriber@example.com").await;
+
+    let set_availability = |tutor_id: Uuid, token: &str, timezone: &str| {
+        let every_day: Vec<serde_json::Value> = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+            .iter()
+            .map(|day| serde_json::json!({ "weekday": day, "start": "08:00", "end": "17:00" }))
+            .collect();
+        client
+            .put(format!("{}/tutors/{}/availability", &address, tutor_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "timezone": timezone, "slots": every_day }))
+            .send()
+    };
+    assert_eq!(
+        200,
+        set_availability(tutor_id, &tutor_token, "Europe/London")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        200,
+        set_availability(other_id, &other_token, "UTC")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+
+    let book = |starts_at: String, ends_at: String| {
+        client
+            .post(format!("{}/tutors/{}/bookings", &address, tutor_id))
+            .bearer_auth(&student_token)
+            .json(&serde_json::json!({
+                "student_id": student_id.to_string(),
+                "starts_at": starts_at,
+                "ends_at": ends_at,
+                "note": "Fractions, decimals; percentages",
+            }))
+            .send()
+    };
+    let booking: serde_json::Value = book(day_at(3, 10, 0), day_at(3, 11, 0))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let booking_id = booking["booking_id"].as_str().unwrap().to_string();
+    let dropped: serde_json::Value = book(day_at(4, 10, 0), day_at(4, 11, 0))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let dropped_id = dropped["booking_id"].as_str().unwrap().to_string();
+    let cancel = |booking_id: &str| {
+        client
+            .post(format!(
+                "{}/tutors/{}/bookings/{}/cancel",
+                &address, tutor_id, booking_id
+            ))
+            .bearer_auth(&tutor_token)
+            .send()
+    };
+    assert_eq!(200, cancel(&dropped_id).await.unwrap().status().as_u16());
+
+    let export = || async {
+        let response = client
+            .get(format!("{}/tutors/{}/calendar.ics", &address, tutor_id))
+            .bearer_auth(&tutor_token)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            "text/calendar; charset=utf-8",
+            response.headers()["content-type"]
+        );
+        response.text().await.expect("Failed to read calendar")
+    };
+    let calendar = export().await;
+    assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(calendar.contains("BEGIN:VTIMEZONE\r\nTZID:Europe/London\r\n"));
+    assert!(calendar.contains(&format!("UID:booking-{booking_id}@eazytutors\r\n")));
+    assert!(calendar.contains(&format!("UID:booking-{dropped_id}@eazytutors\r\n")));
+    assert!(calendar.contains(&format!("UID:course-{course_id}@eazytutors\r\n")));
+    assert!(calendar.contains("DTSTART;TZID=Europe/London:"));
+    assert!(calendar.contains("STATUS:CANCELLED\r\n"));
+    // Long lines are folded
+    assert!(
+        calendar
+            .replace("\r\n ", "")
+            .contains(r"Fractions\, decimals\; percentages")
+    );
+
+    let response = client
+        .get(format!("{}/tutors/{}/calendar.ics", &address, tutor_id))
+        .bearer_auth(&other_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(403, response.status().as_u16());
+
+    // Importing one tutor's export blocks the same hours for another
+    let import = |calendar: String| {
+        client
+            .post(format!("{}/tutors/{}/calendar.ics", &address, other_id))
+            .bearer_auth(&other_token)
+            .header("content-type", "text/calendar")
+            .body(calendar)
+            .send()
+    };
+    let response = import(calendar.clone()).await.unwrap();
+    assert_eq!(200, response.status().as_u16());
+    let report: serde_json::Value = response.json().await.expect("Failed to parse import");
+    let blocked = report["blocked"].as_array().unwrap();
+    assert_eq!(1, blocked.len());
+    assert_eq!(
+        format!("booking-{booking_id}@eazytutors"),
+        blocked[0]["ical_uid"]
+    );
+    assert_eq!("Tutoring session", blocked[0]["reason"]);
+    assert_eq!(
+        serde_json::json!([format!("booking-{dropped_id}@eazytutors")]),
+        report["released"]
+    );
+    assert_eq!(0, report["skipped"].as_array().unwrap().len());
+
+    let open_starts = || async {
+        let open: Vec<serde_json::Value> = client
+            .get(format!(
+                "{}/tutors/{}/availability/open",
+                &address, other_id
+            ))
+            .query(&[("from", day_at(3, 0, 0)), ("to", day_at(4, 0, 0))])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .expect("Failed to parse open windows");
+        open.iter()
+            .map(|window| {
+                window["starts_at"]
+                    .as_str()
+                    .unwrap()
+                    .parse::<chrono::DateTime<chrono::Utc>>()
+                    .unwrap()
+                    .to_rfc3339()
+            })
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(vec![day_at(3, 8, 0), day_at(3, 11, 0)], open_starts().await);
+
+    // Re-importing updates the earlier import rather than adding to it
+    let report: serde_json::Value = import(calendar).await.unwrap().json().await.unwrap();
+    assert_eq!(
+        blocked[0]["exception_id"],
+        report["blocked"][0]["exception_id"]
+    );
+    let exceptions: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/tutors/{}/availability/exceptions",
+            &address, other_id
+        ))
+        .bearer_auth(&other_token)
+        .query(&[("from", day_at(0, 0, 0))])
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse exceptions");
+    assert_eq!(1, exceptions.len());
+
+    // Once cancelled at the source, the time is free again
+    assert_eq!(200, cancel(&booking_id).await.unwrap().status().as_u16());
+    let report: serde_json::Value = import(export().await).await.unwrap().json().await.unwrap();
+    assert_eq!(0, report["blocked"].as_array().unwrap().len());
+    assert_eq!(vec![day_at(3, 8, 0)], open_starts().await);
+
+    let response = import("not a calendar".to_string()).await.unwrap();
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_course_sessions() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (tutor_id, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, "lecturer@example.com").await;
+    let (_, _, other_tutor_token) =
+        create_tutor_with_course(&client, &address, "substitute@example.com").await;
+    let (student_id, student_token) =
+        create_student(&client, &address, "attendee@example.com").await;
+    let (_, other_student_token) = create_student(&client, &address, "onlooker@example.com").await;
+    let second_course: serde_json::Value = client
+        .post(format!("{}/courses/", &address))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "course_name": "Second Course" }))
+        .send()
+        .await
+        .expect("Failed to create course")
+        .json()
+        .await
+        .expect("Failed to parse course");
+    let second_course_id = second_course["course_id"].as_str().unwrap().to_string();
+
+    let schedule = |course_id: String, token: &str, body: serde_json::Value| {
+        client
+            .post(format!("{}/courses/{}/sessions", &address, course_id))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+    };
+
+    let response = schedule(
+        course_id.to_string(),
+        &tutor_token,
+        serde_json::json!({ "starts_at": day_at(2, 9, 0), "ends_at": day_at(2, 10, 0), "location": "Room 101" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+
+    // Weekly for four weeks, skipping the second
+    let skipped = (chrono::Utc::now() + chrono::Duration::days(10))
+        .date_naive()
+        .to_string();
+    let weekly = serde_json::json!({
+        "starts_at": day_at(3, 14, 0),
+        "ends_at": day_at(3, 15, 30),
+        "meeting_url": "https://meet.example.com/algebra",
+        "capacity": 10,
+        "repeat": { "weeks": 4, "skip": [skipped] },
+    });
+    assert_eq!(
+        403,
+        schedule(course_id.to_string(), &other_tutor_token, weekly.clone())
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let response = schedule(course_id.to_string(), &tutor_token, weekly)
+        .await
+        .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    let series: Vec<serde_json::Value> = response.json().await.expect("Failed to parse sessions");
+    let starts: Vec<&str> = series
+        .iter()
+        .map(|s| s["starts_at"].as_str().unwrap())
+        .collect();
+    assert_eq!(3, starts.len());
+    assert!(
+        series
+            .iter()
+            .all(|s| s["series_id"] == series[0]["series_id"] && !s["series_id"].is_null())
+    );
+    assert_eq!(10, series[0]["capacity"]);
+
+    // Overlapping sessions clash even across the tutor's courses
+    let response = schedule(
+        second_course_id.clone(),
+        &tutor_token,
+        serde_json::json!({ "starts_at": day_at(17, 15, 0), "ends_at": day_at(17, 16, 0), "location": "Lab" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(409, response.status().as_u16());
+    for invalid in [
+        serde_json::json!({ "starts_at": day_at(5, 9, 0), "ends_at": day_at(5, 10, 0) }),
+        serde_json::json!({ "starts_at": day_at(5, 9, 0), "ends_at": day_at(5, 10, 0), "meeting_url": "ftp://files" }),
+        serde_json::json!({ "starts_at": day_at(5, 9, 0), "ends_at": day_at(5, 10, 0), "location": "Lab",
+            "repeat": { "weeks": 2, "skip": [(chrono::Utc::now() + chrono::Duration::days(6)).date_naive().to_string()] } }),
+    ] {
+        let response = schedule(second_course_id.clone(), &tutor_token, invalid)
+            .await
+            .unwrap();
+        assert_eq!(400, response.status().as_u16());
+    }
+
+    let list = |path: String, token: Option<String>| {
+        let request = client.get(format!("{}{}", &address, path));
+        match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+        .send()
+    };
+    let sessions: Vec<serde_json::Value> = list(format!("/courses/{course_id}/sessions"), None)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(4, sessions.len());
+    assert_eq!("Room 101", sessions[0]["location"]);
+    let page: Vec<serde_json::Value> = list(format!("/courses/{course_id}/sessions?limit=2"), None)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(2, page.len());
+
+    // Students see the sessions of courses they hold a seat in
+    let student_sessions = format!("/students/{student_id}/sessions");
+    let mine: Vec<serde_json::Value> = list(student_sessions.clone(), Some(student_token.clone()))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(mine.is_empty());
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&student_token)
+        .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+    let mine: Vec<serde_json::Value> = list(student_sessions.clone(), Some(student_token.clone()))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(4, mine.len());
+    let response = list(student_sessions, Some(other_student_token))
+        .await
+        .unwrap();
+    assert_eq!(403, response.status().as_u16());
+
+    // Sessions are not open for booking
+    let every_day: Vec<serde_json::Value> = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+        .iter()
+        .map(|day| serde_json::json!({ "weekday": day, "start": "08:00", "end": "17:00" }))
+        .collect();
+    client
+        .put(format!("{}/tutors/{}/availability", &address, tutor_id))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "timezone": "UTC", "slots": every_day }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let open: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/tutors/{}/availability/open",
+            &address, tutor_id
+        ))
+        .query(&[("from", day_at(2, 0, 0)), ("to", day_at(3, 0, 0))])
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse open windows");
+    let open_starts: Vec<String> = open
+        .iter()
+        .map(|window| {
+            window["starts_at"]
+                .as_str()
+                .unwrap()
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+                .to_rfc3339()
+        })
+        .collect();
+    assert_eq!(vec![day_at(2, 8, 0), day_at(2, 10, 0)], open_starts);
+
+    let session_id = sessions[0]["session_id"].as_str().unwrap();
+    let delete = || {
+        client
+            .delete(format!(
+                "{}/courses/{}/sessions/{}",
+                &address, course_id, session_id
+            ))
+            .bearer_auth(&tutor_token)
+            .send()
+    };
+    assert_eq!(204, delete().await.unwrap().status().as_u16());
+    assert_eq!(404, delete().await.unwrap().status().as_u16());
+}
+
+#[tokio::test]
+async fn test_session_attendance() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, "register@example.com").await;
+    let (_, _, other_tutor_token) =
+        create_tutor_with_course(&client, &address, "cover@example.com").await;
+    let (ada_id, ada_token) = create_student(&client, &address, "ada@example.com").await;
+    let (bo_id, bo_token) = create_student(&client, &address, "bo@example.com").await;
+    let (cy_id, cy_token) = create_student(&client, &address, "cy@example.com").await;
+    let (outsider_id, outsider_token) =
+        create_student(&client, &address, "outsider@example.com").await;
+    for (student_id, token) in [(ada_id, &ada_token), (bo_id, &bo_token), (cy_id, &cy_token)] {
+        let response = client
+            .post(format!("{}/courses/{}/enrollments", &address, course_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(201, response.status().as_u16());
+    }
+
+    // One session about to start, one next week
+    let soon = chrono::Utc::now() + chrono::Duration::minutes(5);
+    let mut session_ids = Vec::new();
+    for (starts_at, ends_at) in [
+        (
+            soon.to_rfc3339(),
+            (soon + chrono::Duration::hours(1)).to_rfc3339(),
+        ),
+        (day_at(7, 9, 0), day_at(7, 10, 0)),
+    ] {
+        let sessions: Vec<serde_json::Value> = client
+            .post(format!("{}/courses/{}/sessions", &address, course_id))
+            .bearer_auth(&tutor_token)
+            .json(&serde_json::json!({ "starts_at": starts_at, "ends_at": ends_at, "location": "Hall" }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .expect("Failed to parse sessions");
+        session_ids.push(sessions[0]["session_id"].as_str().unwrap().to_string());
+    }
+    let session = |session_id: &str, rest: &str| {
+        format!(
+            "{}/courses/{}/sessions/{}/{}",
+            &address, course_id, session_id, rest
+        )
+    };
+    let mark = |session_id: &str, token: &str, body: serde_json::Value| {
+        client
+            .put(session(session_id, "attendance"))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+    };
+
+    // Tutors mark the roll in bulk, but only for enrolled students
+    let roll = serde_json::json!({ "marks": [
+        { "student_id": ada_id, "status": "absent" },
+        { "student_id": bo_id, "status": "late" },
+    ] });
+    assert_eq!(
+        403,
+        mark(&session_ids[0], &other_tutor_token, roll.clone())
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let response = mark(&session_ids[0], &tutor_token, roll).await.unwrap();
+    assert_eq!(200, response.status().as_u16());
+    let marked: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(2, marked.len());
+    for invalid in [
+        serde_json::json!({ "marks": [] }),
+        serde_json::json!({ "marks": [{ "student_id": outsider_id, "status": "present" }] }),
+        serde_json::json!({ "marks": [{ "student_id": ada_id, "status": "present" }, { "student_id": ada_id, "status": "late" }] }),
+        serde_json::json!({ "marks": [{ "student_id": ada_id, "status": "asleep" }] }),
+    ] {
+        let response = mark(&session_ids[0], &tutor_token, invalid).await.unwrap();
+        assert_eq!(400, response.status().as_u16());
+    }
+    // Next week's roll is not open yet, though students may be excused ahead
+    let early = serde_json::json!({ "marks": [{ "student_id": ada_id, "status": "present" }] });
+    assert_eq!(
+        409,
+        mark(&session_ids[1], &tutor_token, early)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let excused = serde_json::json!({ "marks": [{ "student_id": ada_id, "status": "excused" }] });
+    assert_eq!(
+        200,
+        mark(&session_ids[1], &tutor_token, excused)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+
+    // Students check in with the tutor's code, keeping any mark already given
+    let response = client
+        .post(session(&session_ids[1], "check-in-code"))
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(409, response.status().as_u16());
+    let response = client
+        .post(session(&session_ids[0], "check-in-code"))
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    let code: serde_json::Value = response.json().await.unwrap();
+    let code = code["code"].as_str().unwrap().to_string();
+    assert_eq!(6, code.len());
+    let check_in = |token: &str, student_id: Uuid, code: &str| {
+        client
+            .post(session(&session_ids[0], "check-in"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "student_id": student_id, "code": code }))
+            .send()
+    };
+    assert_eq!(
+        403,
+        check_in(&ada_token, ada_id, "WRONG1")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        403,
+        check_in(&bo_token, ada_id, &code)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        403,
+        check_in(&outsider_token, outsider_id, &code)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        403,
+        check_in(&tutor_token, ada_id, &code)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let checked_in: serde_json::Value = check_in(&cy_token, cy_id, &code.to_lowercase())
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        ("present", true),
+        (
+            checked_in["status"].as_str().unwrap(),
+            checked_in["checked_in"].as_bool().unwrap()
+        )
+    );
+    let kept: serde_json::Value = check_in(&ada_token, ada_id, &code)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        ("absent", false),
+        (
+            kept["status"].as_str().unwrap(),
+            kept["checked_in"].as_bool().unwrap()
+        )
+    );
+    let excused = serde_json::json!({ "marks": [{ "student_id": ada_id, "status": "excused" }] });
+    mark(&session_ids[0], &tutor_token, excused).await.unwrap();
+
+    let listed: Vec<serde_json::Value> = client
+        .get(session(&session_ids[0], "attendance"))
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(3, listed.len());
+    assert_eq!(1, listed.iter().filter(|a| a["checked_in"] == true).count());
+
+    // Neither session has started, so none count as held yet
+    let tallies: Vec<serde_json::Value> = client
+        .get(format!("{}/courses/{}/attendance", &address, course_id))
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(3, tallies.len());
+    let ada = tallies
+        .iter()
+        .find(|t| t["student_id"] == ada_id.to_string())
+        .unwrap();
+    assert_eq!(
+        (0, 2),
+        (
+            ada["sessions_held"].as_i64().unwrap(),
+            ada["excused"].as_i64().unwrap()
+        )
+    );
+    let response = client
+        .get(format!("{}/courses/{}/attendance", &address, course_id))
+        .bearer_auth(&other_tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(403, response.status().as_u16());
+
+    let student_attendance = format!("{}/students/{}/attendance", &address, bo_id);
+    let mine: Vec<serde_json::Value> = client
+        .get(&student_attendance)
+        .bearer_auth(&bo_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, mine.len());
+    assert_eq!(1, mine[0]["late"]);
+    let response = client
+        .get(&student_attendance)
+        .bearer_auth(&ada_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_course_curriculum() {
+    check_course_curriculum(spawn_app().await).await;
+}
+
+#[tokio::test]
+async fn test_course_curriculum_on_postgres() {
+    if let Some((address, _)) = spawn_postgres_app().await {
+        check_course_curriculum(address).await;
+    }
+}
+
+async fn check_course_curriculum(address: String) {
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, &unique_email("author")).await;
+    let (_, _, other_tutor_token) =
+        create_tutor_with_course(&client, &address, &unique_email("editor")).await;
+    let (_, student_token) = create_student(&client, &address, &unique_email("reader")).await;
+    let course = format!("{}/courses/{}", &address, course_id);
+
+    let add_module = |token: &str, title: &str| {
+        client
+            .post(format!("{course}/modules"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "title": title }))
+            .send()
+    };
+    assert_eq!(
+        403,
+        add_module(&other_tutor_token, "Stolen")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        400,
+        add_module(&tutor_token, " ")
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let mut module_ids = Vec::new();
+    for title in ["Basics", "Practice", "Extras"] {
+        let response = add_module(&tutor_token, title).await.unwrap();
+        assert_eq!(201, response.status().as_u16());
+        let module: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(
+            module_ids.len() as i64,
+            module["position"].as_i64().unwrap()
+        );
+        module_ids.push(module["module_id"].as_str().unwrap().to_string());
+    }
+
+    let add_lesson = |module_id: &str, body: serde_json::Value| {
+        client
+            .post(format!("{course}/modules/{module_id}/lessons"))
+            .bearer_auth(&tutor_token)
+            .json(&body)
+            .send()
+    };
+    let mut lesson_ids = Vec::new();
+    for body in [
+        serde_json::json!({ "title": "Welcome", "body": "# Hello\n\nRead *this* first.", "duration_minutes": 10, "published": true }),
+        serde_json::json!({ "title": "Setup", "body": "Install the tools.", "duration_minutes": 20, "published": true }),
+        serde_json::json!({ "title": "Work in progress" }),
+    ] {
+        let response = add_lesson(&module_ids[0], body).await.unwrap();
+        assert_eq!(201, response.status().as_u16());
+        let lesson: serde_json::Value = response.json().await.unwrap();
+        lesson_ids.push(lesson["lesson_id"].as_str().unwrap().to_string());
+    }
+    let response = add_lesson(
+        &module_ids[1],
+        serde_json::json!({ "title": "Drill", "duration_minutes": 45, "published": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    for invalid in [
+        serde_json::json!({ "title": "" }),
+        serde_json::json!({ "title": "Forever", "duration_minutes": 0 }),
+        serde_json::json!({ "title": "Huge", "body": "x".repeat(100_001) }),
+    ] {
+        assert_eq!(
+            400,
+            add_lesson(&module_ids[0], invalid)
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        );
+    }
+    let response = add_lesson(
+        &Uuid::new_v4().to_string(),
+        serde_json::json!({ "title": "Lost" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(404, response.status().as_u16());
+
+    // Reordering takes every id exactly once
+    let reorder = |path: String, ids: &[&String]| {
+        client
+            .put(format!("{course}/{path}"))
+            .bearer_auth(&tutor_token)
+            .json(&serde_json::json!({ "ids": ids }))
+            .send()
+    };
+    let response = reorder(
+        "modules/order".to_string(),
+        &[&module_ids[1], &module_ids[0]],
+    )
+    .await
+    .unwrap();
+    assert_eq!(400, response.status().as_u16());
+    let response = reorder(
+        "modules/order".to_string(),
+        &[&module_ids[1], &module_ids[0], &module_ids[1]],
+    )
+    .await
+    .unwrap();
+    assert_eq!(400, response.status().as_u16());
+    let response = reorder(
+        "modules/order".to_string(),
+        &[&module_ids[1], &module_ids[0], &module_ids[2]],
+    )
+    .await
+    .unwrap();
+    assert_eq!(200, response.status().as_u16());
+    let modules: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(
+        vec!["Practice", "Basics", "Extras"],
+        modules
+            .iter()
+            .map(|m| m["title"].as_str().unwrap())
+            .collect::<Vec<_>>()
+    );
+    let lessons_order = format!("modules/{}/lessons/order", module_ids[0]);
+    let response = reorder(
+        lessons_order,
+        &[&lesson_ids[2], &lesson_ids[1], &lesson_ids[0]],
+    )
+    .await
+    .unwrap();
+    assert_eq!(200, response.status().as_u16());
+
+    let curriculum = |token: Option<&str>| {
+        let request = client.get(format!("{course}/curriculum"));
+        match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+        .send()
+    };
+    // Students see published lessons only, and no empty modules
+    for token in [
+        None,
+        Some(student_token.as_str()),
+        Some(other_tutor_token.as_str()),
+    ] {
+        let published: serde_json::Value = curriculum(token).await.unwrap().json().await.unwrap();
+        let modules = published["modules"].as_array().unwrap();
+        assert_eq!(2, modules.len());
+        assert_eq!("Practice", modules[0]["title"]);
+        let titles: Vec<&str> = modules[1]["lessons"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(vec!["Setup", "Welcome"], titles);
+        assert_eq!(75, published["duration_minutes"]);
+    }
+    let everything: serde_json::Value = curriculum(Some(&tutor_token))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(3, everything["modules"].as_array().unwrap().len());
+    assert_eq!(
+        "Work in progress",
+        everything["modules"][1]["lessons"][0]["title"]
+    );
+    assert_eq!(false, everything["modules"][1]["lessons"][0]["published"]);
+    assert_eq!(30, everything["modules"][1]["duration_minutes"]);
+
+    // Publishing the draft shows it to students
+    let draft = format!(
+        "{course}/modules/{}/lessons/{}",
+        module_ids[0], lesson_ids[2]
+    );
+    let response = client
+        .patch(&draft)
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "published": true, "duration_minutes": 5 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status().as_u16());
+    let lesson: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        ("Work in progress", 5),
+        (
+            lesson["title"].as_str().unwrap(),
+            lesson["duration_minutes"].as_i64().unwrap()
+        )
+    );
+    let published: serde_json::Value = curriculum(None).await.unwrap().json().await.unwrap();
+    assert_eq!(80, published["duration_minutes"]);
+    let response = client
+        .patch(format!("{course}/modules/{}", module_ids[2]))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "title": "Appendix" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        "Appendix",
+        response.json::<serde_json::Value>().await.unwrap()["title"]
+    );
+
+    let response = client
+        .delete(&draft)
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(204, response.status().as_u16());
+    let response = client
+        .delete(&draft)
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status().as_u16());
+    let response = client
+        .delete(format!("{course}/modules/{}", module_ids[1]))
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(204, response.status().as_u16());
+    let everything: serde_json::Value = curriculum(Some(&tutor_token))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(2, everything["modules"].as_array().unwrap().len());
+    assert_eq!(30, everything["duration_minutes"]);
+
+    let response = client
+        .get(format!(
+            "{}/courses/{}/curriculum",
+            &address,
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status().as_u16());
+}
+
+// A multipart/form-data body with one file field, and its content type
+#[tokio::test]
+async fn test_curriculum_size_is_capped() {
+    // MAX_CURRICULUM_ITEMS, also the most ids a reorder accepts
+    const MAX_ITEMS: usize = 200;
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, "prolific@example.com").await;
+    let course = format!("{}/courses/{}", &address, course_id);
+    let add = |url: String, title: String| {
+        client
+            .post(url)
+            .bearer_auth(&tutor_token)
+            .json(&serde_json::json!({ "title": title }))
+            .send()
+    };
+
+    let mut module_ids = Vec::new();
+    for i in 0..MAX_ITEMS {
+        let module: serde_json::Value = add(format!("{course}/modules"), format!("Week {i}"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        module_ids.push(module["module_id"].as_str().unwrap().to_string());
+    }
+    let response = add(format!("{course}/modules"), "One too many".to_string())
+        .await
+        .unwrap();
+    assert_eq!(409, response.status().as_u16());
+    // A full course can still be reordered
+    module_ids.reverse();
+    let response = client
+        .put(format!("{course}/modules/order"))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "ids": module_ids }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status().as_u16());
+
+    let lessons = format!("{course}/modules/{}/lessons", module_ids[0]);
+    for i in 0..MAX_ITEMS {
+        let response = add(lessons.clone(), format!("Lesson {i}")).await.unwrap();
+        assert_eq!(201, response.status().as_u16());
+    }
+    let response = add(lessons.clone(), "One too many".to_string())
+        .await
+        .unwrap();
+    assert_eq!(409, response.status().as_u16());
+    // Other modules are not affected
+    let response = add(
+        format!("{course}/modules/{}/lessons", module_ids[1]),
+        "Elsewhere".to_string(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+}
+
+fn multipart_file(
+    field: &str,
+    filename: &str,
+    content_type: &str,
+    data: &[u8],
+) -> (String, Vec<u8>) {
+    let boundary = "eazytutors-test-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"{filename}\"\r\n\
+         Content-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+#[tokio::test]
+async fn test_lesson_materials() {
+    let address = spawn_app().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, "uploader@example.com").await;
+    let (_, _, other_tutor_token) =
+        create_tutor_with_course(&client, &address, "onlooker@example.com").await;
+    let (student_id, student_token) =
+        create_student(&client, &address, "learner@example.com").await;
+    let (_, outsider_token) = create_student(&client, &address, "outsider@example.com").await;
+    let response = client
+        .post(format!("{}/courses/{}/enrollments", &address, course_id))
+        .bearer_auth(&student_token)
+        .json(&serde_json::json!({ "student_id": student_id.to_string() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(201, response.status().as_u16());
+
+    let course = format!("{}/courses/{}", &address, course_id);
+    let module: serde_json::Value = client
+        .post(format!("{course}/modules"))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "title": "Readings" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let mut lessons = Vec::new();
+    for published in [true, false] {
+        let lesson: serde_json::Value = client
+            .post(format!(
+                "{course}/modules/{}/lessons",
+                module["module_id"].as_str().unwrap()
+            ))
+            .bearer_auth(&tutor_token)
+            .json(&serde_json::json!({ "title": "Chapter", "published": published }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        lessons.push(format!(
+            "{course}/modules/{}/lessons/{}/materials",
+            module["module_id"].as_str().unwrap(),
+            lesson["lesson_id"].as_str().unwrap()
+        ));
+    }
+    let (materials, draft_materials) = (&lessons[0], &lessons[1]);
+
+    let upload = |url: &str, token: &str, (content_type, body): (String, Vec<u8>)| {
+        client
+            .post(url)
+            .bearer_auth(token)
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+    };
+    let pdf: Vec<u8> = b"%PDF-1.7\n"
+        .iter()
+        .copied()
+        .chain((0..1000u32).map(|i| (i % 251) as u8))
+        .collect();
+    let response = upload(
+        materials,
+        &tutor_token,
+        multipart_file("file", "../notes.pdf", "application/pdf", &pdf),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let material: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        ("notes.pdf", 1009),
+        (
+            material["filename"].as_str().unwrap(),
+            material["size_bytes"].as_i64().unwrap()
+        )
+    );
+    assert_eq!(
+        format!("{}{}", &address, location),
+        format!("{materials}/{}", material["material_id"].as_str().unwrap())
+    );
+
+    // The same bytes again are the same material
+    let response = upload(
+        materials,
+        &tutor_token,
+        multipart_file("file", "copy.pdf", "application/pdf", &pdf),
+    )
+    .await
+    .unwrap();
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        material["material_id"],
+        response.json::<serde_json::Value>().await.unwrap()["material_id"]
+    );
+    let response = upload(
+        draft_materials,
+        &tutor_token,
+        multipart_file("file", "draft.pdf", "application/pdf", &pdf),
+    )
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    let draft_material: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(material["sha256"], draft_material["sha256"]);
+
+    let response = upload(
+        materials,
+        &other_tutor_token,
+        multipart_file("file", "notes.pdf", "application/pdf", &pdf),
+    )
+    .await
+    .unwrap();
+    assert_eq!(403, response.status().as_u16());
+    let response = upload(
+        materials,
+        &tutor_token,
+        multipart_file("file", "page.html", "text/html", b"<html></html>"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(415, response.status().as_u16());
+    let response = upload(
+        materials,
+        &tutor_token,
+        multipart_file(
+            "file",
+            "fake.pdf",
+            "application/pdf",
+            b"MZ\x90\x00 not a pdf",
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(415, response.status().as_u16());
+    let response = upload(
+        materials,
+        &tutor_token,
+        multipart_file("attachment", "notes.pdf", "application/pdf", &pdf),
+    )
+    .await
+    .unwrap();
+    assert_eq!(400, response.status().as_u16());
+    let huge = [
+        b"%PDF-1.7\n".as_slice(),
+        &vec![b'x'; MAX_UPLOAD_BYTES as usize],
+    ]
+    .concat();
+    let response = upload(
+        materials,
+        &tutor_token,
+        multipart_file("file", "huge.pdf", "application/pdf", &huge),
+    )
+    .await
+    .unwrap();
+    assert_eq!(413, response.status().as_u16());
+    let response = client
+        .post(materials.as_str())
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(415, response.status().as_u16());
+
+    let list = |url: &str, token: &str| client.get(url).bearer_auth(token).send();
+    let listed: Vec<serde_json::Value> = list(materials, &student_token)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, listed.len());
+    assert_eq!(
+        403,
+        list(materials, &outsider_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        403,
+        list(materials, &other_tutor_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        404,
+        list(draft_materials, &student_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        200,
+        list(draft_materials, &tutor_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+
+    let download = format!("{materials}/{}", material["material_id"].as_str().unwrap());
+    let response = list(&download, &student_token).await.unwrap();
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!("application/pdf", response.headers()["content-type"]);
+    assert_eq!("bytes", response.headers()["accept-ranges"]);
+    assert!(
+        response.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .contains("notes.pdf")
+    );
+    assert_eq!(pdf, response.bytes().await.unwrap().to_vec());
+
+    let ranged = |range: &str| {
+        client
+            .get(&download)
+            .bearer_auth(&student_token)
+            .header("Range", range)
+            .send()
+    };
+    let response = ranged("bytes=0-4").await.unwrap();
+    assert_eq!(206, response.status().as_u16());
+    assert_eq!("bytes 0-4/1009", response.headers()["content-range"]);
+    assert_eq!(b"%PDF-", response.bytes().await.unwrap().as_ref());
+    let response = ranged("bytes=-9").await.unwrap();
+    assert_eq!(206, response.status().as_u16());
+    assert_eq!(&pdf[1000..], response.bytes().await.unwrap().as_ref());
+    let response = ranged("bytes=5000-").await.unwrap();
+    assert_eq!(416, response.status().as_u16());
+    assert_eq!("bytes */1009", response.headers()["content-range"]);
+    // Several ranges at once are answered with the whole file
+    let response = ranged("bytes=0-1,5-6").await.unwrap();
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(1009, response.bytes().await.unwrap().len());
+    assert_eq!(
+        403,
+        list(&download, &outsider_token)
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    );
+    let response = list(&format!("{materials}/{}", Uuid::new_v4()), &tutor_token)
+        .await
+        .unwrap();
+    assert_eq!(404, response.status().as_u16());
+
+    // Shared content outlives one of the lessons letting go of it
+    let response = client
+        .delete(&download)
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(204, response.status().as_u16());
+    let response = client
+        .delete(&download)
+        .bearer_auth(&tutor_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(404, response.status().as_u16());
+    let draft_download = format!(
+        "{draft_materials}/{}",
+        draft_material["material_id"].as_str().unwrap()
+    );
+    let response = list(&draft_download, &tutor_token).await.unwrap();
+    assert_eq!(pdf, response.bytes().await.unwrap().to_vec());
+}
+
+// Every file kept under `dir`, staged or stored
+fn files_under(dir: &std::path::Path) -> usize {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|entry| entry.unwrap().path())
+            .map(|path| if path.is_dir() { files_under(&path) } else { 1 })
+            .sum(),
+        Err(_) => 0,
+    }
+}
+
+#[tokio::test]
+async fn test_rejected_uploads_leave_no_files() {
+    let (address, upload_dir) = spawn_app_with_uploads().await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, "careful@example.com").await;
+    let module: serde_json::Value = client
+        .post(format!("{}/courses/{}/modules", &address, course_id))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "title": "Week 1" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let module = format!(
+        "{}/courses/{}/modules/{}",
+        &address,
+        course_id,
+        module["module_id"].as_str().unwrap()
+    );
+    let lesson: serde_json::Value = client
+        .post(format!("{module}/lessons"))
+        .bearer_auth(&tutor_token)
+        .json(&serde_json::json!({ "title": "Reading", "published": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let materials = format!(
+        "{module}/lessons/{}/materials",
+        lesson["lesson_id"].as_str().unwrap()
+    );
+    let upload = |(content_type, body): (String, Vec<u8>)| {
+        client
+            .post(&materials)
+            .bearer_auth(&tutor_token)
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+    };
+
+    // A second field after the file
+    let (content_type, mut body) =
+        multipart_file("file", "notes.pdf", "application/pdf", b"%PDF-1.7\n");
+    let (_, second) = multipart_file("note", "note.txt", "text/plain", b"hi");
+    body.truncate(body.len() - "--\r\n".len());
+    body.extend_from_slice(&second[second.iter().position(|&b| b == b'\r').unwrap()..]);
+    let response = upload((content_type, body)).await.unwrap();
+    assert_eq!(400, response.status().as_u16());
+    let error: serde_json::Value = response.json().await.unwrap();
+    assert!(error.to_string().contains("only form field"), "{error}");
+    let response = upload(multipart_file(
+        "file",
+        "fake.pdf",
+        "application/pdf",
+        b"MZ\x90\x00 not a pdf",
+    ))
+    .await
+    .unwrap();
+    assert_eq!(415, response.status().as_u16());
+    assert_eq!(0, files_under(&upload_dir));
+
+    let response = upload(multipart_file(
+        "file",
+        "notes.pdf",
+        "application/pdf",
+        b"%PDF-1.7\n",
+    ))
+    .await
+    .unwrap();
+    assert_eq!(201, response.status().as_u16());
+    assert_eq!(1, files_under(&upload_dir));
+}
+
+#[tokio::test]
+async fn test_deleting_lessons_releases_materials() {
+    let (address, upload_dir) = spawn_app_with_uploads().await;
+    check_deleting_lessons_releases_materials(address, upload_dir).await;
+}
+
+#[tokio::test]
+async fn test_deleting_lessons_releases_materials_on_postgres() {
+    if let Some((address, upload_dir)) = spawn_postgres_app().await {
+        check_deleting_lessons_releases_materials(address, upload_dir).await;
+    }
+}
+
+async fn check_deleting_lessons_releases_materials(address: String, upload_dir: PathBuf) {
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let client = reqwest::Client::new();
+
+    let (_, course_id, tutor_token) =
+        create_tutor_with_course(&client, &address, &unique_email("archivist")).await;
+    let course = format!("{}/courses/{}", &address, course_id);
+    let add_lesson = |module: String| {
+        let client = &client;
+        let tutor_token = &tutor_token;
+        async move {
+            let lesson: serde_json::Value = client
+                .post(format!("{module}/lessons"))
+                .bearer_auth(tutor_token)
+                .json(&serde_json::json!({ "title": "Chapter", "published": true }))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            format!("{module}/lessons/{}", lesson["lesson_id"].as_str().unwrap())
+        }
+    };
+    let mut modules = Vec::new();
+    for title in ["Week 1", "Week 2", "Week 3"] {
+        let module: serde_json::Value = client
+            .post(format!("{course}/modules"))
+            .bearer_auth(&tutor_token)
+            .json(&serde_json::json!({ "title": title }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        modules.push(format!(
+            "{course}/modules/{}",
+            module["module_id"].as_str().unwrap()
+        ));
+    }
+    let first = add_lesson(modules[0].clone()).await;
+    let second = add_lesson(modules[0].clone()).await;
+    let third = add_lesson(modules[1].clone()).await;
+    let fourth = add_lesson(modules[2].clone()).await;
+
+    let upload_dir = &upload_dir;
+    let attach = |lesson: &str, text: &str| {
+        let (content_type, body) = multipart_file(
+            "file",
+            "notes.pdf",
+            "application/pdf",
+            format!("%PDF-1.7\n{text}").as_bytes(),
+        );
+        let request = client
+            .post(format!("{lesson}/materials"))
+            .bearer_auth(&tutor_token)
+            .header("Content-Type", content_type)
+            .body(body);
+        async move {
+            let material: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            let sha256 = material["sha256"].as_str().unwrap().to_string();
+            upload_dir.join("blobs").join(&sha256[..2]).join(sha256)
+        }
+    };
+    let shared = attach(&first, "shared").await;
+    assert_eq!(shared, attach(&second, "shared").await);
+    let own = attach(&second, "own").await;
+    let module_file = attach(&third, "module").await;
+    let course_file = attach(&fourth, "course").await;
+    assert!(shared.exists() && own.exists() && module_file.exists() && course_file.exists());
+
+    let delete = |url: &str| client.delete(url).bearer_auth(&tutor_token).send();
+    assert_eq!(204, delete(&first).await.unwrap().status().as_u16());
+    // The second lesson still has the shared file
+    assert!(shared.exists());
+    assert_eq!(204, delete(&second).await.unwrap().status().as_u16());
+    assert!(!shared.exists() && !own.exists());
+    assert_eq!(204, delete(&modules[1]).await.unwrap().status().as_u16());
+    assert!(!module_file.exists());
+    assert!(course_file.exists());
+    assert_eq!(204, delete(&course).await.unwrap().status().as_u16());
+    assert!(!course_file.exists());
+}
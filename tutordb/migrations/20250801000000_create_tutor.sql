@@ -0,0 +1,21 @@
+-- Baseline matching the original `eazytutors` dump. Written to be a no-op
+-- on databases restored from that dump so they join the migration history.
+
+DO $$
+BEGIN
+    CREATE TYPE course_type_enum AS ENUM ('FREE', 'PAID');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS tutor (
+    id UUID PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL UNIQUE,
+    courses BIGINT NOT NULL DEFAULT 0,
+    rating NUMERIC(3,2) NOT NULL DEFAULT 0  -- e.g., 4.50
+);
+
+-- The dump declared rating without a default
+ALTER TABLE tutor ALTER COLUMN rating SET DEFAULT 0;
@@ -0,0 +1,13 @@
+CREATE TABLE course (
+    id UUID PRIMARY KEY NOT NULL,
+    tutor_id UUID NOT NULL REFERENCES tutor (id),
+    name TEXT NOT NULL,
+    course_type course_type_enum NOT NULL DEFAULT 'FREE',
+    posted_time TIMESTAMP,
+    rating TEXT,
+    enrolled BIGINT NOT NULL DEFAULT 0,
+    enrolled_limit BIGINT NOT NULL DEFAULT 50,
+    CHECK (enrolled >= 0 AND enrolled <= enrolled_limit)
+);
+
+CREATE INDEX course_tutor_id_idx ON course (tutor_id);
@@ -0,0 +1,15 @@
+CREATE TABLE student (
+    id UUID PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL UNIQUE
+);
+
+-- One row per seat taken; course.enrolled is kept equal to the row count
+CREATE TABLE enrollment (
+    course_id UUID NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    enrolled_at TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (course_id, student_id)
+);
+
+CREATE INDEX enrollment_student_id_idx ON enrollment (student_id);
@@ -0,0 +1,8 @@
+-- Students queued for a full course, served in `id` order
+CREATE TABLE waitlist (
+    id BIGSERIAL PRIMARY KEY,
+    course_id UUID NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    joined_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (course_id, student_id)
+);
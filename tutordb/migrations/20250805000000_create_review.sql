@@ -0,0 +1,18 @@
+-- Nothing ever wrote course.rating; it now holds the average of the course's
+-- reviews, NULL until the first one
+ALTER TABLE course
+    ALTER COLUMN rating TYPE NUMERIC(3,2)
+    USING CASE WHEN rating ~ '^[0-5](\.[0-9]{1,2})?$' THEN rating::NUMERIC(3,2) END;
+
+-- One review per student and course; posting again replaces it
+CREATE TABLE review (
+    course_id UUID NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    rating SMALLINT NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (course_id, student_id)
+);
+
+CREATE INDEX review_student_id_idx ON review (student_id);
@@ -0,0 +1,15 @@
+-- PAID courses carry a price in an ISO 4217 currency; FREE courses carry neither
+ALTER TABLE course
+    ADD COLUMN price NUMERIC(10,2),
+    ADD COLUMN currency CHAR(3);
+
+-- Paid courses created before prices existed are listed at 0 until edited
+UPDATE course SET price = 0, currency = 'USD' WHERE course_type = 'PAID';
+
+ALTER TABLE course
+    ADD CONSTRAINT course_price_check CHECK (
+        (course_type = 'FREE' AND price IS NULL AND currency IS NULL)
+        OR (course_type = 'PAID' AND price >= 0 AND currency IS NOT NULL)
+    );
+
+CREATE INDEX course_type_price_idx ON course (course_type, price);
@@ -0,0 +1,10 @@
+-- Free text shown with a course and searched alongside its name
+ALTER TABLE course ADD COLUMN description TEXT NOT NULL DEFAULT '';
+
+-- Name matches outrank description matches
+ALTER TABLE course ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'A')
+    || setweight(to_tsvector('english', description), 'B')
+) STORED;
+
+CREATE INDEX course_search_vector_idx ON course USING GIN (search_vector);
@@ -0,0 +1,3 @@
+-- Argon2 PHC string. Tutors created before sign-up existed, or through the
+-- admin CLI, have none and cannot log in.
+ALTER TABLE tutor ADD COLUMN password_hash TEXT;
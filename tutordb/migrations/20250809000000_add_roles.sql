@@ -0,0 +1,10 @@
+-- Roles the HTTP API authorises against. A tutor account is either a plain
+-- tutor or an admin; student accounts always act as 'student'.
+CREATE TYPE user_role AS ENUM ('admin', 'tutor', 'student');
+
+ALTER TABLE tutor
+    ADD COLUMN role user_role NOT NULL DEFAULT 'tutor',
+    ADD CONSTRAINT tutor_role_check CHECK (role IN ('admin', 'tutor'));
+
+-- Students log in the same way tutors do
+ALTER TABLE student ADD COLUMN password_hash TEXT;
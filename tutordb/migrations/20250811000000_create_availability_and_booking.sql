@@ -0,0 +1,54 @@
+-- Lets the booking exclusion constraint compare tutor ids with `=` in GiST
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Weekly hours a tutor can be booked, as wall-clock times in `timezone` (an
+-- IANA name) so they follow daylight saving changes
+CREATE TABLE availability_rule (
+    id BIGSERIAL PRIMARY KEY,
+    tutor_id UUID NOT NULL REFERENCES tutor (id) ON DELETE CASCADE,
+    -- ISO weekday, 1 = Monday .. 7 = Sunday
+    weekday SMALLINT NOT NULL CHECK (weekday BETWEEN 1 AND 7),
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL,
+    timezone TEXT NOT NULL,
+    CHECK (start_time < end_time)
+);
+
+CREATE INDEX availability_rule_tutor_idx ON availability_rule (tutor_id);
+
+CREATE TYPE availability_kind AS ENUM ('blocked', 'extra');
+
+-- One-off changes on top of the weekly rules: time off, or extra hours
+CREATE TABLE availability_exception (
+    id UUID PRIMARY KEY,
+    tutor_id UUID NOT NULL REFERENCES tutor (id) ON DELETE CASCADE,
+    kind availability_kind NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    reason TEXT NOT NULL DEFAULT '',
+    CHECK (starts_at < ends_at)
+);
+
+CREATE INDEX availability_exception_tutor_idx ON availability_exception (tutor_id, starts_at);
+
+CREATE TYPE booking_status AS ENUM ('confirmed', 'cancelled');
+
+CREATE TABLE booking (
+    id UUID PRIMARY KEY,
+    tutor_id UUID NOT NULL REFERENCES tutor (id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    note TEXT NOT NULL DEFAULT '',
+    status booking_status NOT NULL DEFAULT 'confirmed',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    cancelled_at TIMESTAMPTZ,
+    CHECK (starts_at < ends_at),
+    -- However requests race, a tutor's confirmed bookings never overlap
+    CONSTRAINT booking_no_overlap EXCLUDE USING gist (
+        tutor_id WITH =,
+        tstzrange(starts_at, ends_at) WITH &&
+    ) WHERE (status = 'confirmed')
+);
+
+CREATE INDEX booking_student_idx ON booking (student_id);
@@ -0,0 +1,6 @@
+-- Exceptions imported from an .ics file remember the event's UID, so
+-- importing the same calendar again updates them instead of duplicating them
+ALTER TABLE availability_exception ADD COLUMN ical_uid TEXT;
+
+CREATE UNIQUE INDEX availability_exception_ical_uid_idx
+    ON availability_exception (tutor_id, ical_uid);
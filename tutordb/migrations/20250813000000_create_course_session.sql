@@ -0,0 +1,23 @@
+-- When a course is actually taught. tutor_id copies the course's so the
+-- exclusion constraint can keep each tutor's sessions apart.
+CREATE TABLE course_session (
+    id UUID PRIMARY KEY,
+    course_id UUID NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    tutor_id UUID NOT NULL REFERENCES tutor (id) ON DELETE CASCADE,
+    -- Shared by the sessions one recurrence rule created
+    series_id UUID,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    location TEXT,
+    meeting_url TEXT,
+    -- Seats for this session when it differs from course.enrolled_limit
+    capacity BIGINT CHECK (capacity > 0),
+    CHECK (starts_at < ends_at),
+    CHECK (location IS NOT NULL OR meeting_url IS NOT NULL),
+    CONSTRAINT course_session_no_overlap EXCLUDE USING gist (
+        tutor_id WITH =,
+        tstzrange(starts_at, ends_at) WITH &&
+    )
+);
+
+CREATE INDEX course_session_course_idx ON course_session (course_id, starts_at);
@@ -0,0 +1,21 @@
+CREATE TYPE attendance_status AS ENUM ('present', 'absent', 'late', 'excused');
+
+-- Who came to which session; at most one mark per student and session
+CREATE TABLE attendance (
+    session_id UUID NOT NULL REFERENCES course_session (id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    status attendance_status NOT NULL,
+    -- Set when the student checked in with a code rather than being marked
+    checked_in BOOLEAN NOT NULL DEFAULT FALSE,
+    marked_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (session_id, student_id)
+);
+
+CREATE INDEX attendance_student_idx ON attendance (student_id);
+
+-- The code students enter to check themselves in; one live code per session
+CREATE TABLE session_check_in (
+    session_id UUID PRIMARY KEY REFERENCES course_session (id) ON DELETE CASCADE,
+    code TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
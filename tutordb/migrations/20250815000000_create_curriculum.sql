@@ -0,0 +1,24 @@
+-- A course's syllabus: ordered modules, each holding ordered lessons.
+-- Positions are unique per parent but checked at commit so a reorder can
+-- swap them within one transaction.
+CREATE TABLE course_module (
+    id UUID PRIMARY KEY,
+    course_id UUID NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    position INT NOT NULL CHECK (position >= 0),
+    CONSTRAINT course_module_position UNIQUE (course_id, position) DEFERRABLE INITIALLY DEFERRED
+);
+
+CREATE TABLE lesson (
+    id UUID PRIMARY KEY,
+    module_id UUID NOT NULL REFERENCES course_module (id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    -- Markdown, rendered by clients
+    body TEXT NOT NULL DEFAULT '',
+    duration_minutes INT CHECK (duration_minutes > 0),
+    position INT NOT NULL CHECK (position >= 0),
+    -- Drafts are only shown to the course's tutor
+    published BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT lesson_position UNIQUE (module_id, position) DEFERRABLE INITIALLY DEFERRED
+);
@@ -0,0 +1,15 @@
+-- Files attached to lessons. The bytes live in the blob store under their
+-- SHA-256, so identical uploads share one stored copy.
+CREATE TABLE lesson_material (
+    id UUID PRIMARY KEY,
+    lesson_id UUID NOT NULL REFERENCES lesson (id) ON DELETE CASCADE,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    sha256 TEXT NOT NULL CHECK (sha256 ~ '^[0-9a-f]{64}$'),
+    uploaded_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- Attaching the same file to a lesson twice keeps the first
+    CONSTRAINT lesson_material_content UNIQUE (lesson_id, sha256)
+);
+
+CREATE INDEX lesson_material_sha256_idx ON lesson_material (sha256);
@@ -1,6 +1,38 @@
 
-mod models;
-mod repositories;
+pub mod models;
+pub mod repositories;
+
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+/// Schema history from `tutordb/migrations`, embedded at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Set to `1` or `true` to keep startup from applying pending migrations.
+pub const SKIP_MIGRATIONS_VAR: &str = "SKIP_MIGRATIONS";
+
+pub fn migrations_enabled() -> bool {
+	!matches!(
+		std::env::var(SKIP_MIGRATIONS_VAR).as_deref(),
+		Ok("1") | Ok("true")
+	)
+}
+
+pub async fn run_migrations(pool:&PgPool)->Result<(),MigrateError>{
+	MIGRATOR.run(pool).await
+}
+
 pub struct EazyTutor{
 	pub pool:sqlx::PgPool,
 }
+
+impl EazyTutor{
+	/// Connects and, unless `SKIP_MIGRATIONS` is set, brings the schema up to date.
+	pub async fn connect(database_url:&str)->Result<Self,sqlx::Error>{
+	let pool=PgPoolOptions::new().max_connections(5).connect(database_url).await?;
+	if migrations_enabled(){
+	run_migrations(&pool).await?;
+	}
+	Ok(EazyTutor{pool})
+	}
+}
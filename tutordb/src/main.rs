@@ -1,3 +1,298 @@
-fn main() {
-    println!("Hello, world!");
+use clap::{Parser, Subcommand};
+use sqlx::PgPool;
+use bigdecimal::BigDecimal;
+use tutordb::models::courses::{
+    Course, CourseFilter, CourseType, PageRequest, DEFAULT_ENROLLED_LIMIT,
+};
+use tutordb::models::role::Role;
+use tutordb::models::tutor::Tutor;
+use tutordb::repositories::{
+    audit_repository, course_repository, stats_repository, tutor_repository,
+};
+use tutordb::EazyTutor;
+use uuid::Uuid;
+
+/// Admin CLI for the eazytutors database.
+#[derive(Debug, Parser)]
+#[command(name = "tutordb", version)]
+struct Cli {
+    /// Postgres connection string, read from `.env` when not given.
+    #[arg(long, env = "DATABASE_URL", hide_env_values = true)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Apply pending migrations, even when SKIP_MIGRATIONS is set.
+    Migrate,
+    /// Manage tutors.
+    #[command(subcommand)]
+    Tutor(TutorCommand),
+    /// Manage courses.
+    #[command(subcommand)]
+    Course(CourseCommand),
+    /// Insert demo tutors and courses; tutors that already exist are skipped.
+    Seed,
+    /// Print row counts and enrolment totals.
+    Stats,
+    /// Print recent admin actions, newest first.
+    Audit {
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TutorCommand {
+    Create { name: String, email: String },
+    List,
+    Find { id: Uuid },
+    Delete { id: Uuid },
+    /// Grant or revoke admin rights: `admin` or `tutor`.
+    Role { id: Uuid, role: Role },
+}
+
+#[derive(Debug, Subcommand)]
+enum CourseCommand {
+    Create {
+        tutor_id: Uuid,
+        name: String,
+        /// FREE or PAID.
+        #[arg(long = "type", default_value = "FREE")]
+        course_type: CourseType,
+        #[arg(long, default_value_t = DEFAULT_ENROLLED_LIMIT)]
+        limit: i64,
+        /// Required for PAID courses.
+        #[arg(long)]
+        price: Option<BigDecimal>,
+        /// ISO 4217 code, used with --price.
+        #[arg(long, default_value = "USD")]
+        currency: String,
+    },
+    List {
+        /// Only list courses posted by this tutor.
+        #[arg(long)]
+        tutor: Option<Uuid>,
+    },
+    Find { id: Uuid },
+    Delete { id: Uuid },
+}
+
+const DEMO_TUTORS: [(&str, &str, &[&str]); 3] = [
+    ("Ada Lovelace", "ada@eazytutors.dev", &["Analytical Engines", "Intro to Algorithms"]),
+    ("Alan Turing", "alan@eazytutors.dev", &["Computability"]),
+    ("Grace Hopper", "grace@eazytutors.dev", &["Compilers 101", "COBOL for Beginners"]),
+];
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), sqlx::Error> {
+    if let Command::Migrate = cli.command {
+        let pool = PgPool::connect(&cli.database_url).await?;
+        tutordb::run_migrations(&pool).await?;
+        println!("Migrations applied");
+        return Ok(());
+    }
+
+    let EazyTutor { pool } = EazyTutor::connect(&cli.database_url).await?;
+    match cli.command {
+        Command::Migrate => unreachable!("handled above"),
+        Command::Tutor(command) => tutor_command(&pool, command).await,
+        Command::Course(command) => course_command(&pool, command).await,
+        Command::Seed => seed(&pool).await,
+        Command::Stats => {
+            let stats = stats_repository::table_stats(&pool).await?;
+            println!("tutors:   {}", stats.tutors);
+            println!("courses:  {}", stats.courses);
+            println!("enrolled: {} / {} seats", stats.enrolled, stats.enrolled_limit);
+            Ok(())
+        }
+        Command::Audit { limit } => {
+            for entry in audit_repository::list_audit(&pool, limit).await? {
+                println!(
+                    "{}  {}  {:<24} {}",
+                    entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    entry.actor_id,
+                    entry.action,
+                    entry.detail
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn tutor_command(pool: &PgPool, command: TutorCommand) -> Result<(), sqlx::Error> {
+    match command {
+        TutorCommand::Create { name, email } => {
+            print_tutor(&tutor_repository::create_tutor(pool, name, email).await?);
+        }
+        TutorCommand::List => {
+            for tutor in tutor_repository::list_tutors(pool).await? {
+                print_tutor(&tutor);
+            }
+        }
+        TutorCommand::Find { id } => print_tutor(&tutor_repository::find_tutor(id, pool).await?),
+        TutorCommand::Delete { id } => {
+            let tutor = tutor_repository::delete_tutor(pool, id).await?;
+            println!("Deleted tutor {}", tutor.id);
+        }
+        TutorCommand::Role { id, role } => {
+            tutor_repository::set_tutor_role(pool, id, role).await?;
+            println!("Tutor {id} is now {role}");
+        }
+    }
+    Ok(())
+}
+
+async fn course_command(pool: &PgPool, command: CourseCommand) -> Result<(), sqlx::Error> {
+    match command {
+        CourseCommand::Create { tutor_id, name, course_type, limit, price, currency } => {
+            let mut course = Course::new(tutor_id, name, Some(chrono::Utc::now().naive_utc()));
+            course.course_type = course_type;
+            course.enrolled_limit = limit;
+            if course_type == CourseType::Paid {
+                course.price = price;
+                course.currency = Some(currency.to_ascii_uppercase());
+            }
+            print_course(&course_repository::create_course(pool, course).await?);
+        }
+        CourseCommand::List { tutor } => {
+            let filter = CourseFilter {
+                tutor_id: tutor,
+                ..Default::default()
+            };
+            let page = course_repository::list_courses(pool, &filter, &PageRequest::default()).await?;
+            for course in page.courses {
+                print_course(&course);
+            }
+        }
+        CourseCommand::Find { id } => print_course(&course_repository::find_course(pool, id).await?),
+        CourseCommand::Delete { id } => {
+            let course = course_repository::delete_course(pool, id).await?;
+            println!("Deleted course {}", course.id);
+        }
+    }
+    Ok(())
+}
+
+async fn seed(pool: &PgPool) -> Result<(), sqlx::Error> {
+    for (name, email, courses) in DEMO_TUTORS {
+        match tutor_repository::find_tutor_by_details(pool, name, email).await {
+            Ok(_) => {
+                println!("Skipping existing tutor {email}");
+                continue;
+            }
+            Err(sqlx::Error::RowNotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        let tutor = tutor_repository::create_tutor(pool, name.to_string(), email.to_string()).await?;
+        for course_name in courses {
+            let course = Course::new(
+                tutor.id,
+                course_name.to_string(),
+                Some(chrono::Utc::now().naive_utc()),
+            );
+            course_repository::create_course(pool, course).await?;
+        }
+        println!("Seeded {} with {} course(s)", tutor.email, courses.len());
+    }
+    Ok(())
+}
+
+fn print_tutor(tutor: &Tutor) {
+    println!(
+        "{}  {:<24} {:<32} courses={} rating={}",
+        tutor.id, tutor.name, tutor.email, tutor.courses, tutor.rating
+    );
+}
+
+fn print_course(course: &Course) {
+    let price = match (&course.price, &course.currency) {
+        (Some(price), Some(currency)) => format!(" price={price} {currency}"),
+        _ => String::new(),
+    };
+    println!(
+        "{}  {:<32} {} tutor={} enrolled={}/{}{}",
+        course.id,
+        course.name,
+        course.course_type,
+        course.tutor_id,
+        course.enrolled,
+        course.enrolled_limit,
+        price
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_course_create() {
+        let cli = Cli::try_parse_from([
+            "tutordb",
+            "--database-url",
+            "postgres://localhost/test",
+            "course",
+            "create",
+            "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "Rust",
+            "--type",
+            "paid",
+            "--limit",
+            "10",
+            "--price",
+            "19.99",
+        ])
+        .expect("Failed to parse");
+
+        match cli.command {
+            Command::Course(CourseCommand::Create { course_type, limit, price, currency, .. }) => {
+                assert_eq!(course_type, CourseType::Paid);
+                assert_eq!(limit, 10);
+                assert_eq!(price, Some("19.99".parse().unwrap()));
+                assert_eq!(currency, "USD");
+            }
+            other => panic!("unexpected command {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_tutor_role() {
+        let cli = Cli::try_parse_from([
+            "tutordb",
+            "--database-url",
+            "postgres://localhost/test",
+            "tutor",
+            "role",
+            "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "Admin",
+        ])
+        .expect("Failed to parse");
+
+        match cli.command {
+            Command::Tutor(TutorCommand::Role { role, .. }) => assert_eq!(role, Role::Admin),
+            other => panic!("unexpected command {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,sqlx::Type)]
+#[sqlx(type_name = "attendance_status", rename_all = "lowercase")]
+pub enum AttendanceStatus{
+	Present,
+	Absent,
+	Late,
+	Excused
+}
+
+impl fmt::Display for AttendanceStatus{
+	fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+	match self{
+	AttendanceStatus::Present=>write!(f,"present"),
+	AttendanceStatus::Absent=>write!(f,"absent"),
+	AttendanceStatus::Late=>write!(f,"late"),
+	AttendanceStatus::Excused=>write!(f,"excused"),
+	}
+	}
+}
+
+impl FromStr for AttendanceStatus{
+	type Err=String;
+
+	fn from_str(s:&str)->Result<Self,Self::Err>{
+	match s.to_ascii_lowercase().as_str(){
+	"present"=>Ok(AttendanceStatus::Present),
+	"absent"=>Ok(AttendanceStatus::Absent),
+	"late"=>Ok(AttendanceStatus::Late),
+	"excused"=>Ok(AttendanceStatus::Excused),
+	other=>Err(format!("unknown attendance status `{other}`, expected present, absent, late or excused")),
+	}
+	}
+}
+
+/// A student's attendance at one course session.
+#[derive(Debug,Clone)]
+pub struct Attendance{
+	pub session_id:Uuid,
+	pub student_id:Uuid,
+	pub status:AttendanceStatus,
+	/// The student checked in with a code rather than being marked.
+	pub checked_in:bool,
+	pub marked_at:DateTime<Utc>,
+}
+
+/// The code students enter to check in to a session.
+#[derive(Debug,Clone)]
+pub struct CheckInCode{
+	pub session_id:Uuid,
+	pub code:String,
+	pub expires_at:DateTime<Utc>,
+}
+
+/// What a check-in attempt turned into.
+#[derive(Debug)]
+pub enum CheckInOutcome{
+	/// Recorded, or the mark the tutor had already given.
+	CheckedIn(Attendance),
+	/// No live code for the session matches.
+	InvalidCode,
+}
+
+/// One student's attendance across one course's sessions.
+#[derive(Debug,Clone)]
+pub struct AttendanceTally{
+	pub course_id:Uuid,
+	pub student_id:Uuid,
+	/// Sessions of the course that have started.
+	pub sessions_held:i64,
+	pub present:i64,
+	pub absent:i64,
+	pub late:i64,
+	pub excused:i64,
+}
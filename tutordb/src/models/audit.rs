@@ -0,0 +1,13 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+/// One row of the `audit_log`, newest entries first when listed.
+#[derive(Debug)]
+pub struct AuditEntry{
+	pub id:i64,
+	/// Tutor id of the admin who acted.
+	pub actor_id:Uuid,
+	pub action:String,
+	pub detail:String,
+	pub created_at:NaiveDateTime,
+}
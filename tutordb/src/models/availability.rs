@@ -0,0 +1,72 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// A weekly window in which a tutor can be booked.
+#[derive(Debug,Clone,PartialEq)]
+pub struct AvailabilityRule{
+	/// ISO weekday, 1 = Monday .. 7 = Sunday.
+	pub weekday:i16,
+	pub start_time:NaiveTime,
+	pub end_time:NaiveTime,
+	/// IANA name the times are local to, e.g. `Europe/London`.
+	pub timezone:String,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,sqlx::Type)]
+#[sqlx(type_name = "availability_kind", rename_all = "lowercase")]
+pub enum AvailabilityKind{
+	/// Time off inside the weekly hours.
+	Blocked,
+	/// Bookable time outside the weekly hours.
+	Extra
+}
+
+impl fmt::Display for AvailabilityKind{
+	fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+	match self{
+	AvailabilityKind::Blocked=>write!(f,"blocked"),
+	AvailabilityKind::Extra=>write!(f,"extra"),
+	}
+	}
+}
+
+impl FromStr for AvailabilityKind{
+	type Err=String;
+
+	fn from_str(s:&str)->Result<Self,Self::Err>{
+	match s.to_ascii_lowercase().as_str(){
+	"blocked"=>Ok(AvailabilityKind::Blocked),
+	"extra"=>Ok(AvailabilityKind::Extra),
+	other=>Err(format!("unknown availability kind `{other}`, expected blocked or extra")),
+	}
+	}
+}
+
+/// A one-off change to a tutor's weekly availability.
+#[derive(Debug,Clone)]
+pub struct AvailabilityException{
+	pub id:Uuid,
+	pub tutor_id:Uuid,
+	pub kind:AvailabilityKind,
+	pub starts_at:DateTime<Utc>,
+	pub ends_at:DateTime<Utc>,
+	pub reason:String,
+	/// UID of the calendar event it was imported from, if any.
+	pub ical_uid:Option<String>,
+}
+
+impl AvailabilityException{
+	pub fn new(tutor_id:Uuid,kind:AvailabilityKind,starts_at:DateTime<Utc>,ends_at:DateTime<Utc>,reason:String)->Self{
+	AvailabilityException{
+	id:Uuid::new_v4(),
+	tutor_id,
+	kind,
+	starts_at,
+	ends_at,
+	reason,
+	ical_uid:None,
+	}
+	}
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,sqlx::Type)]
+#[sqlx(type_name = "booking_status", rename_all = "lowercase")]
+pub enum BookingStatus{
+	Confirmed,
+	Cancelled
+}
+
+impl fmt::Display for BookingStatus{
+	fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+	match self{
+	BookingStatus::Confirmed=>write!(f,"confirmed"),
+	BookingStatus::Cancelled=>write!(f,"cancelled"),
+	}
+	}
+}
+
+/// A student's one-to-one session with a tutor.
+#[derive(Debug,Clone)]
+pub struct Booking{
+	pub id:Uuid,
+	pub tutor_id:Uuid,
+	pub student_id:Uuid,
+	pub starts_at:DateTime<Utc>,
+	pub ends_at:DateTime<Utc>,
+	pub note:String,
+	pub status:BookingStatus,
+	pub created_at:DateTime<Utc>,
+	pub cancelled_at:Option<DateTime<Utc>>,
+}
+
+impl Booking{
+	pub fn new(tutor_id:Uuid,student_id:Uuid,starts_at:DateTime<Utc>,ends_at:DateTime<Utc>,note:String)->Self{
+	Booking{
+	id:Uuid::new_v4(),
+	tutor_id,
+	student_id,
+	starts_at,
+	ends_at,
+	note,
+	status:BookingStatus::Confirmed,
+	created_at:Utc::now(),
+	cancelled_at:None,
+	}
+	}
+}
+
+/// What a booking request turned into.
+#[derive(Debug)]
+pub enum BookingOutcome{
+	Booked(Booking),
+	/// The tutor already has a confirmed booking overlapping the slot.
+	Overlaps,
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// One scheduled meeting of a course.
+#[derive(Debug,Clone)]
+pub struct CourseSession{
+	pub id:Uuid,
+	pub course_id:Uuid,
+	pub tutor_id:Uuid,
+	/// Shared by the sessions one recurrence rule created.
+	pub series_id:Option<Uuid>,
+	pub starts_at:DateTime<Utc>,
+	pub ends_at:DateTime<Utc>,
+	pub location:Option<String>,
+	pub meeting_url:Option<String>,
+	/// Seats for this session, when different from the course's limit.
+	pub capacity:Option<i64>,
+}
+
+/// What scheduling a batch of sessions turned into.
+#[derive(Debug)]
+pub enum ScheduleOutcome{
+	Scheduled(Vec<CourseSession>),
+	/// One of the sessions overlaps another of the tutor's; none were saved.
+	Overlaps,
+}
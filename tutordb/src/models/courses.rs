@@ -1,15 +1,136 @@
-pub Enum CourseType{
-	FREE,
-	PAID
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Seats offered by a new course unless set otherwise.
+pub const DEFAULT_ENROLLED_LIMIT: i64 = 50;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,sqlx::Type)]
+#[sqlx(type_name = "course_type_enum", rename_all = "UPPERCASE")]
+pub enum CourseType{
+	Free,
+	Paid
+}
+
+impl fmt::Display for CourseType{
+	fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+	match self{
+	CourseType::Free=>write!(f,"FREE"),
+	CourseType::Paid=>write!(f,"PAID"),
+	}
+	}
 }
-#[derive(Debug,sql::FromRow)]
+
+impl FromStr for CourseType{
+	type Err=String;
+
+	fn from_str(s:&str)->Result<Self,Self::Err>{
+	match s.to_ascii_uppercase().as_str(){
+	"FREE"=>Ok(CourseType::Free),
+	"PAID"=>Ok(CourseType::Paid),
+	other=>Err(format!("unknown course type `{other}`, expected FREE or PAID")),
+	}
+	}
+}
+
+#[derive(Debug,sqlx::FromRow)]
 pub struct Course{
 	pub id:Uuid,
 	pub tutor_id:Uuid,
 	pub name:String,
-	pub rating:Option<String>,
+	pub description:String,
+	pub course_type:CourseType,
+	pub posted_time:Option<NaiveDateTime>,
+	/// Average review rating, `None` until the course is first reviewed.
+	pub rating:Option<BigDecimal>,
 	pub enrolled:i64,
-	pub enrolled_limit:i64
+	pub enrolled_limit:i64,
+	/// Set, with `currency`, exactly when the course is PAID.
+	pub price:Option<BigDecimal>,
+	/// ISO 4217 code such as `USD`.
+	pub currency:Option<String>
 }
 
+impl Course{
+	pub fn new(tutor_id:Uuid,name:String,posted_time:Option<NaiveDateTime>)->Self{
+	Course{
+	id:Uuid::new_v4(),
+	tutor_id,
+	name,
+	description:String::new(),
+	course_type:CourseType::Free,
+	posted_time,
+	rating:None,
+	enrolled:0,
+	enrolled_limit:DEFAULT_ENROLLED_LIMIT,
+	price:None,
+	currency:None
+	}
+	}
 
+	pub fn is_full(&self)->bool{
+	self.enrolled>=self.enrolled_limit
+	}
+}
+
+/// Narrows a course listing; `None` fields match everything. FREE courses
+/// count as costing 0 for the price bounds.
+#[derive(Debug,Default,Clone)]
+pub struct CourseFilter{
+	pub tutor_id:Option<Uuid>,
+	pub course_type:Option<CourseType>,
+	pub min_price:Option<BigDecimal>,
+	pub max_price:Option<BigDecimal>,
+	/// Only courses posted strictly after this time.
+	pub posted_after:Option<NaiveDateTime>,
+}
+
+/// Column a course listing is ordered by. Unset ratings and posting times
+/// sort last in either direction.
+#[derive(Debug,Default,Clone,Copy,PartialEq,Eq)]
+pub enum CourseSort{
+	#[default]
+	PostedTime,
+	Name,
+	Rating,
+	Price
+}
+
+impl CourseSort{
+	pub fn as_str(&self)->&'static str{
+	match self{
+	CourseSort::PostedTime=>"posted_time",
+	CourseSort::Name=>"name",
+	CourseSort::Rating=>"rating",
+	CourseSort::Price=>"price",
+	}
+	}
+}
+
+/// Which slice of a sorted listing to return; no `limit` returns the rest.
+#[derive(Debug,Default,Clone,Copy)]
+pub struct PageRequest{
+	pub sort:CourseSort,
+	pub descending:bool,
+	pub limit:Option<i64>,
+	pub offset:i64,
+}
+
+#[derive(Debug)]
+pub struct CoursePage{
+	pub courses:Vec<Course>,
+	/// Matching courses across all pages.
+	pub total:i64,
+}
+
+/// A full-text search match. Highlights wrap matched words in
+/// `<mark>`/`</mark>` and are not HTML-escaped.
+#[derive(Debug)]
+pub struct CourseSearchHit{
+	pub course:Course,
+	pub rank:f32,
+	pub name_highlight:String,
+	pub description_highlight:String,
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A titled group of lessons within a course.
+#[derive(Debug,Clone)]
+pub struct CourseModule{
+	pub id:Uuid,
+	pub course_id:Uuid,
+	pub title:String,
+	/// Zero-based place among the course's modules.
+	pub position:i32,
+}
+
+#[derive(Debug,Clone)]
+pub struct Lesson{
+	pub id:Uuid,
+	pub module_id:Uuid,
+	pub title:String,
+	/// Markdown.
+	pub body:String,
+	pub duration_minutes:Option<i32>,
+	/// Zero-based place among the module's lessons.
+	pub position:i32,
+	pub published:bool,
+	pub updated_at:DateTime<Utc>,
+}
+
+/// What reordering a course's modules or a module's lessons turned into.
+#[derive(Debug)]
+pub enum ReorderOutcome<T>{
+	/// Everything in its new order.
+	Reordered(Vec<T>),
+	/// The ids given were not exactly the ones being ordered; nothing moved.
+	Mismatch,
+}
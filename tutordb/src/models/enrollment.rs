@@ -0,0 +1,28 @@
+use crate::models::waitlist::WaitlistEntry;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub struct Enrollment{
+	pub course_id:Uuid,
+	pub student_id:Uuid,
+	pub enrolled_at:NaiveDateTime,
+}
+
+/// What an enrolment request turned into.
+#[derive(Debug)]
+pub enum EnrollOutcome{
+	Enrolled(Enrollment),
+	/// The course was full, so the student joined its waitlist.
+	Waitlisted(WaitlistEntry),
+	AlreadyEnrolled,
+	AlreadyWaitlisted,
+}
+
+/// What a withdrawal removed.
+#[derive(Debug)]
+pub enum WithdrawOutcome{
+	/// The student gave up a seat, which went to the head of the waitlist if any.
+	Unenrolled{promoted:Option<Enrollment>},
+	LeftWaitlist,
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A file attached to a lesson; its bytes are stored under `sha256`.
+#[derive(Debug,Clone)]
+pub struct Material{
+	pub id:Uuid,
+	pub lesson_id:Uuid,
+	pub filename:String,
+	pub content_type:String,
+	pub size_bytes:i64,
+	/// Lowercase hex.
+	pub sha256:String,
+	pub uploaded_at:DateTime<Utc>,
+}
+
+/// What attaching a file to a lesson turned into.
+#[derive(Debug)]
+pub enum AttachOutcome{
+	Attached(Material),
+	/// The lesson already had a file with the same content; nothing was added.
+	AlreadyAttached(Material),
+}
@@ -1 +1,15 @@
-pub mod tutor;
\ No newline at end of file
+pub mod tutor;
+pub mod courses;
+pub mod stats;
+pub mod student;
+pub mod enrollment;
+pub mod waitlist;
+pub mod review;
+pub mod role;
+pub mod audit;
+pub mod availability;
+pub mod booking;
+pub mod course_session;
+pub mod attendance;
+pub mod curriculum;
+pub mod material;
@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+/// Lowest and highest star rating a review may give.
+pub const MIN_RATING: i16 = 1;
+pub const MAX_RATING: i16 = 5;
+
+#[derive(Debug)]
+pub struct Review{
+	pub course_id:Uuid,
+	pub student_id:Uuid,
+	pub rating:i16,
+	pub body:String,
+	pub created_at:NaiveDateTime,
+	pub updated_at:NaiveDateTime,
+}
+
+/// What posting a review did.
+#[derive(Debug)]
+pub enum ReviewOutcome{
+	Created(Review),
+	/// The student had reviewed the course before; their review was replaced.
+	Updated(Review),
+	/// Only enrolled students may review a course.
+	NotEnrolled,
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,sqlx::Type)]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+pub enum Role{
+	Admin,
+	Tutor,
+	Student
+}
+
+impl fmt::Display for Role{
+	fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+	match self{
+	Role::Admin=>write!(f,"admin"),
+	Role::Tutor=>write!(f,"tutor"),
+	Role::Student=>write!(f,"student"),
+	}
+	}
+}
+
+impl FromStr for Role{
+	type Err=String;
+
+	fn from_str(s:&str)->Result<Self,Self::Err>{
+	match s.to_ascii_lowercase().as_str(){
+	"admin"=>Ok(Role::Admin),
+	"tutor"=>Ok(Role::Tutor),
+	"student"=>Ok(Role::Student),
+	other=>Err(format!("unknown role `{other}`, expected admin, tutor or student")),
+	}
+	}
+}
@@ -0,0 +1,8 @@
+/// Row counts reported by the admin CLI's `stats` command.
+#[derive(Debug)]
+pub struct TableStats{
+	pub tutors:i64,
+	pub courses:i64,
+	pub enrolled:i64,
+	pub enrolled_limit:i64,
+}
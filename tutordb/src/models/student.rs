@@ -0,0 +1,22 @@
+pub struct Student{
+	pub id:uuid::Uuid,
+	pub name:String,
+	pub email:String,
+}
+
+impl Student{
+	pub fn new(name:String,email:String)->Self{
+	Student{
+	id:uuid::Uuid::new_v4(),
+	name,
+	email,
+	}
+	}
+}
+
+/// What login needs to check a student's password.
+#[derive(Debug)]
+pub struct StudentCredentials{
+	pub id:uuid::Uuid,
+	pub password_hash:Option<String>,
+}
@@ -1,3 +1,4 @@
+use crate::models::role::Role;
 use bigdecimal::BigDecimal;
 
 pub struct Tutor{
@@ -19,3 +20,13 @@ impl Tutor{
 	}
 	}
 }
+
+/// What login needs to check a tutor's password; kept apart from `Tutor`
+/// so the hash is never loaded along with ordinary tutor reads.
+#[derive(Debug)]
+pub struct TutorCredentials{
+	pub id:uuid::Uuid,
+	pub password_hash:Option<String>,
+	/// `Tutor` or `Admin`.
+	pub role:Role,
+}
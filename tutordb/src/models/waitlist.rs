@@ -0,0 +1,11 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub struct WaitlistEntry{
+	pub course_id:Uuid,
+	pub student_id:Uuid,
+	pub joined_at:NaiveDateTime,
+	/// 1-based place in the queue at the time it was read.
+	pub position:i64,
+}
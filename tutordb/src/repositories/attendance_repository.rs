@@ -0,0 +1,324 @@
+use crate::models::attendance::{
+    Attendance, AttendanceStatus, AttendanceTally, CheckInCode, CheckInOutcome,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Records or overwrites each student's mark for the session, all or none.
+pub async fn mark_attendance(
+    pool: &PgPool,
+    session_id: Uuid,
+    marks: &[(Uuid, AttendanceStatus)],
+) -> Result<Vec<Attendance>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut marked = Vec::with_capacity(marks.len());
+    for (student_id, status) in marks {
+        let attendance = sqlx::query_as!(
+            Attendance,
+            r#"
+            INSERT INTO attendance (session_id, student_id, status)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (session_id, student_id) DO UPDATE
+            SET status = EXCLUDED.status, checked_in = FALSE, marked_at = now()
+            RETURNING session_id, student_id, status as "status: AttendanceStatus", checked_in, marked_at
+            "#,
+            session_id,
+            student_id,
+            *status as AttendanceStatus
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        marked.push(attendance);
+    }
+    tx.commit().await?;
+
+    Ok(marked)
+}
+
+/// Everyone marked or checked in for the session.
+pub async fn list_session_attendance(
+    pool: &PgPool,
+    session_id: Uuid,
+) -> Result<Vec<Attendance>, sqlx::Error> {
+    let attendance = sqlx::query_as!(
+        Attendance,
+        r#"
+        SELECT session_id, student_id, status as "status: AttendanceStatus", checked_in, marked_at
+        FROM attendance
+        WHERE session_id = $1
+        ORDER BY marked_at
+        "#,
+        session_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attendance)
+}
+
+/// Replaces the session's check-in code.
+pub async fn open_check_in(pool: &PgPool, code: CheckInCode) -> Result<CheckInCode, sqlx::Error> {
+    let code = sqlx::query_as!(
+        CheckInCode,
+        r#"
+        INSERT INTO session_check_in (session_id, code, expires_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (session_id) DO UPDATE
+        SET code = EXCLUDED.code, expires_at = EXCLUDED.expires_at
+        RETURNING session_id, code, expires_at
+        "#,
+        code.session_id,
+        code.code,
+        code.expires_at
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(code)
+}
+
+/// Checks the student in with `status` if `code` is the session's live code.
+/// A mark the tutor already gave is kept rather than overwritten.
+pub async fn check_in(
+    pool: &PgPool,
+    session_id: Uuid,
+    student_id: Uuid,
+    code: &str,
+    status: AttendanceStatus,
+) -> Result<CheckInOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let live = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM session_check_in
+            WHERE session_id = $1 AND code = $2 AND expires_at > now()
+        ) as "live!"
+        "#,
+        session_id,
+        code
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if !live {
+        return Ok(CheckInOutcome::InvalidCode);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO attendance (session_id, student_id, status, checked_in)
+        VALUES ($1, $2, $3, TRUE)
+        ON CONFLICT (session_id, student_id) DO NOTHING
+        "#,
+        session_id,
+        student_id,
+        status as AttendanceStatus
+    )
+    .execute(&mut *tx)
+    .await?;
+    let attendance = sqlx::query_as!(
+        Attendance,
+        r#"
+        SELECT session_id, student_id, status as "status: AttendanceStatus", checked_in, marked_at
+        FROM attendance
+        WHERE session_id = $1 AND student_id = $2
+        "#,
+        session_id,
+        student_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(CheckInOutcome::CheckedIn(attendance))
+}
+
+/// A tally per student enrolled in, or marked for, the course, counting the
+/// sessions that started by `now`.
+pub async fn course_attendance(
+    pool: &PgPool,
+    course_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<AttendanceTally>, sqlx::Error> {
+    let tallies = sqlx::query_as!(
+        AttendanceTally,
+        r#"
+        WITH students AS (
+            SELECT student_id FROM enrollment WHERE course_id = $1
+            UNION
+            SELECT a.student_id
+            FROM attendance a JOIN course_session s ON s.id = a.session_id
+            WHERE s.course_id = $1
+        )
+        SELECT
+            $1::uuid as "course_id!",
+            st.student_id as "student_id!",
+            (SELECT count(*) FROM course_session WHERE course_id = $1 AND starts_at <= $2) as "sessions_held!",
+            count(*) FILTER (WHERE a.status = 'present') as "present!",
+            count(*) FILTER (WHERE a.status = 'absent') as "absent!",
+            count(*) FILTER (WHERE a.status = 'late') as "late!",
+            count(*) FILTER (WHERE a.status = 'excused') as "excused!"
+        FROM students st
+        LEFT JOIN (attendance a JOIN course_session s ON s.id = a.session_id AND s.course_id = $1)
+            ON a.student_id = st.student_id
+        GROUP BY st.student_id
+        ORDER BY st.student_id
+        "#,
+        course_id,
+        now
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tallies)
+}
+
+/// A tally per course the student is enrolled in or was marked for,
+/// counting the sessions that started by `now`.
+pub async fn student_attendance(
+    pool: &PgPool,
+    student_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<AttendanceTally>, sqlx::Error> {
+    let tallies = sqlx::query_as!(
+        AttendanceTally,
+        r#"
+        WITH courses AS (
+            SELECT course_id FROM enrollment WHERE student_id = $1
+            UNION
+            SELECT s.course_id
+            FROM attendance a JOIN course_session s ON s.id = a.session_id
+            WHERE a.student_id = $1
+        )
+        SELECT
+            c.course_id as "course_id!",
+            $1::uuid as "student_id!",
+            (SELECT count(*) FROM course_session WHERE course_id = c.course_id AND starts_at <= $2) as "sessions_held!",
+            count(*) FILTER (WHERE a.status = 'present') as "present!",
+            count(*) FILTER (WHERE a.status = 'absent') as "absent!",
+            count(*) FILTER (WHERE a.status = 'late') as "late!",
+            count(*) FILTER (WHERE a.status = 'excused') as "excused!"
+        FROM courses c
+        LEFT JOIN (course_session s JOIN attendance a ON a.session_id = s.id AND a.student_id = $1)
+            ON s.course_id = c.course_id
+        GROUP BY c.course_id
+        ORDER BY c.course_id
+        "#,
+        student_id,
+        now
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tallies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::course_session::{CourseSession, ScheduleOutcome};
+    use crate::models::courses::Course;
+    use crate::repositories::course_repository::create_course;
+    use crate::repositories::course_session_repository::schedule_course_sessions;
+    use crate::repositories::enrollment_repository::enroll_student;
+    use crate::repositories::student_repository::create_student;
+    use crate::repositories::tutor_repository::create_tutor;
+    use chrono::Duration;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    async fn new_student(pool: &PgPool) -> Uuid {
+        create_student(pool, "Pupil".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create student")
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_attendance_is_marked_and_tallied() {
+        let pool = setup_db().await;
+        let tutor = create_tutor(&pool, "Register".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create tutor");
+        let course = create_course(&pool, Course::new(tutor.id, "Roll Call".to_string(), None))
+            .await
+            .expect("Failed to create course");
+        // Two sessions held already, one still to come
+        let now = Utc::now();
+        let sessions: Vec<CourseSession> = [-14, -7, 7]
+            .into_iter()
+            .map(|days| CourseSession {
+                id: Uuid::new_v4(),
+                course_id: course.id,
+                tutor_id: tutor.id,
+                series_id: None,
+                starts_at: now + Duration::days(days),
+                ends_at: now + Duration::days(days) + Duration::hours(1),
+                location: Some("Hall".to_string()),
+                meeting_url: None,
+                capacity: None,
+            })
+            .collect();
+        let sessions = match schedule_course_sessions(&pool, sessions).await.expect("Failed to schedule") {
+            ScheduleOutcome::Scheduled(sessions) => sessions,
+            other => panic!("expected sessions, got {other:?}"),
+        };
+        let (ada, bo) = (new_student(&pool).await, new_student(&pool).await);
+        enroll_student(&pool, course.id, ada).await.expect("Failed to enroll");
+        enroll_student(&pool, course.id, bo).await.expect("Failed to enroll");
+
+        mark_attendance(&pool, sessions[0].id, &[(ada, AttendanceStatus::Present), (bo, AttendanceStatus::Absent)])
+            .await
+            .expect("Failed to mark");
+        // Marking again overwrites
+        let marked = mark_attendance(&pool, sessions[0].id, &[(bo, AttendanceStatus::Excused)])
+            .await
+            .expect("Failed to mark");
+        assert_eq!(marked[0].status, AttendanceStatus::Excused);
+        assert_eq!(list_session_attendance(&pool, sessions[0].id).await.unwrap().len(), 2);
+
+        // Check-in needs the live code and keeps the tutor's marks
+        let code = CheckInCode {
+            session_id: sessions[1].id,
+            code: "K7M2QX".to_string(),
+            expires_at: Utc::now() + Duration::minutes(10),
+        };
+        open_check_in(&pool, code).await.expect("Failed to open check-in");
+        let outcome = check_in(&pool, sessions[1].id, ada, "WRONG1", AttendanceStatus::Present).await.unwrap();
+        assert!(matches!(outcome, CheckInOutcome::InvalidCode));
+        let outcome = check_in(&pool, sessions[2].id, ada, "K7M2QX", AttendanceStatus::Present).await.unwrap();
+        assert!(matches!(outcome, CheckInOutcome::InvalidCode));
+        match check_in(&pool, sessions[1].id, ada, "K7M2QX", AttendanceStatus::Late).await.unwrap() {
+            CheckInOutcome::CheckedIn(attendance) => {
+                assert_eq!(attendance.status, AttendanceStatus::Late);
+                assert!(attendance.checked_in);
+            }
+            other => panic!("expected a check-in, got {other:?}"),
+        }
+        mark_attendance(&pool, sessions[1].id, &[(bo, AttendanceStatus::Absent)]).await.unwrap();
+        match check_in(&pool, sessions[1].id, bo, "K7M2QX", AttendanceStatus::Present).await.unwrap() {
+            CheckInOutcome::CheckedIn(attendance) => assert_eq!(attendance.status, AttendanceStatus::Absent),
+            other => panic!("expected the tutor's mark, got {other:?}"),
+        }
+
+        let tallies = course_attendance(&pool, course.id, Utc::now()).await.expect("Failed to tally");
+        assert_eq!(tallies.len(), 2);
+        let ada_tally = tallies.iter().find(|t| t.student_id == ada).unwrap();
+        assert_eq!((ada_tally.sessions_held, ada_tally.present, ada_tally.late), (2, 1, 1));
+        let bo_tally = tallies.iter().find(|t| t.student_id == bo).unwrap();
+        assert_eq!((bo_tally.absent, bo_tally.excused), (1, 1));
+
+        let tallies = student_attendance(&pool, bo, Utc::now()).await.expect("Failed to tally");
+        assert_eq!(tallies.len(), 1);
+        assert_eq!((tallies[0].course_id, tallies[0].sessions_held, tallies[0].absent), (course.id, 2, 1));
+        assert!(student_attendance(&pool, new_student(&pool).await, Utc::now()).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::models::audit::AuditEntry;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn record_audit(
+    pool: &PgPool,
+    actor_id: Uuid,
+    action: &str,
+    detail: &str,
+) -> Result<AuditEntry, sqlx::Error> {
+    let entry = sqlx::query_as!(
+        AuditEntry,
+        r#"
+        INSERT INTO audit_log (actor_id, action, detail)
+        VALUES ($1, $2, $3)
+        RETURNING id, actor_id, action, detail, created_at
+        "#,
+        actor_id,
+        action,
+        detail
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(entry)
+}
+
+/// The most recent `limit` entries, newest first.
+pub async fn list_audit(pool: &PgPool, limit: i64) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let entries = sqlx::query_as!(
+        AuditEntry,
+        r#"
+        SELECT id, actor_id, action, detail, created_at
+        FROM audit_log
+        ORDER BY created_at DESC, id DESC
+        LIMIT $1
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_record_and_list_audit() {
+        let pool = setup_db().await;
+
+        let actor_id = Uuid::new_v4();
+        let entry = record_audit(&pool, actor_id, "tutor.search", "email contains \"ada\"")
+            .await
+            .expect("Failed to record audit entry");
+        assert_eq!(entry.actor_id, actor_id);
+        assert_eq!(entry.action, "tutor.search");
+
+        // Other tests may log concurrently, so look for ours rather than
+        // assuming it is first
+        let entries = list_audit(&pool, 100).await.expect("Failed to list audit log");
+        assert!(entries.iter().any(|e| e.id == entry.id));
+        assert!(entries.windows(2).all(|w| w[0].created_at >= w[1].created_at));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::models::availability::{AvailabilityException, AvailabilityKind, AvailabilityRule};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Swaps the tutor's weekly rules for `rules` in one transaction.
+/// `RowNotFound` if the tutor does not exist.
+pub async fn replace_availability_rules(
+    pool: &PgPool,
+    tutor_id: Uuid,
+    rules: &[AvailabilityRule],
+) -> Result<Vec<AvailabilityRule>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    // Serialises concurrent replacements for the same tutor
+    sqlx::query!("SELECT id FROM tutor WHERE id = $1 FOR UPDATE", tutor_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    sqlx::query!("DELETE FROM availability_rule WHERE tutor_id = $1", tutor_id)
+        .execute(&mut *tx)
+        .await?;
+    for rule in rules {
+        sqlx::query!(
+            r#"
+            INSERT INTO availability_rule (tutor_id, weekday, start_time, end_time, timezone)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            tutor_id,
+            rule.weekday,
+            rule.start_time,
+            rule.end_time,
+            rule.timezone
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    list_availability_rules(pool, tutor_id).await
+}
+
+/// The tutor's weekly rules, Monday first.
+pub async fn list_availability_rules(
+    pool: &PgPool,
+    tutor_id: Uuid,
+) -> Result<Vec<AvailabilityRule>, sqlx::Error> {
+    let rules = sqlx::query_as!(
+        AvailabilityRule,
+        r#"
+        SELECT weekday, start_time, end_time, timezone
+        FROM availability_rule
+        WHERE tutor_id = $1
+        ORDER BY weekday, start_time
+        "#,
+        tutor_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rules)
+}
+
+pub async fn create_availability_exception(
+    pool: &PgPool,
+    exception: AvailabilityException,
+) -> Result<AvailabilityException, sqlx::Error> {
+    let inserted = sqlx::query_as!(
+        AvailabilityException,
+        r#"
+        INSERT INTO availability_exception (id, tutor_id, kind, starts_at, ends_at, reason, ical_uid)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, tutor_id, kind as "kind: AvailabilityKind", starts_at, ends_at, reason, ical_uid
+        "#,
+        exception.id,
+        exception.tutor_id,
+        exception.kind as AvailabilityKind,
+        exception.starts_at,
+        exception.ends_at,
+        exception.reason,
+        exception.ical_uid
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(inserted)
+}
+
+/// The tutor's exceptions overlapping `[from, to)`, earliest first.
+pub async fn list_availability_exceptions(
+    pool: &PgPool,
+    tutor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<AvailabilityException>, sqlx::Error> {
+    let exceptions = sqlx::query_as!(
+        AvailabilityException,
+        r#"
+        SELECT id, tutor_id, kind as "kind: AvailabilityKind", starts_at, ends_at, reason, ical_uid
+        FROM availability_exception
+        WHERE tutor_id = $1 AND starts_at < $3 AND ends_at > $2
+        ORDER BY starts_at
+        "#,
+        tutor_id,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(exceptions)
+}
+
+/// Applies an .ics import in one transaction: `exceptions` are inserted, or
+/// update the tutor's exception with the same `ical_uid`, and exceptions
+/// imported from the `cancelled_uids` events are removed.
+pub async fn import_availability_exceptions(
+    pool: &PgPool,
+    tutor_id: Uuid,
+    exceptions: Vec<AvailabilityException>,
+    cancelled_uids: &[String],
+) -> Result<Vec<AvailabilityException>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut imported = Vec::with_capacity(exceptions.len());
+    for exception in exceptions {
+        let upserted = sqlx::query_as!(
+            AvailabilityException,
+            r#"
+            INSERT INTO availability_exception (id, tutor_id, kind, starts_at, ends_at, reason, ical_uid)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (tutor_id, ical_uid) DO UPDATE
+            SET kind = EXCLUDED.kind, starts_at = EXCLUDED.starts_at,
+                ends_at = EXCLUDED.ends_at, reason = EXCLUDED.reason
+            RETURNING id, tutor_id, kind as "kind: AvailabilityKind", starts_at, ends_at, reason, ical_uid
+            "#,
+            exception.id,
+            tutor_id,
+            exception.kind as AvailabilityKind,
+            exception.starts_at,
+            exception.ends_at,
+            exception.reason,
+            exception.ical_uid
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        imported.push(upserted);
+    }
+    sqlx::query!(
+        "DELETE FROM availability_exception WHERE tutor_id = $1 AND ical_uid = ANY($2)",
+        tutor_id,
+        cancelled_uids
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(imported)
+}
+
+/// `RowNotFound` if the tutor has no such exception.
+pub async fn delete_availability_exception(
+    pool: &PgPool,
+    tutor_id: Uuid,
+    exception_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let deleted = sqlx::query!(
+        "DELETE FROM availability_exception WHERE id = $1 AND tutor_id = $2",
+        exception_id,
+        tutor_id
+    )
+    .execute(pool)
+    .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::tutor_repository::create_tutor;
+    use chrono::{Duration, NaiveTime};
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    async fn new_tutor(pool: &PgPool) -> Uuid {
+        let email = format!("{}@example.com", Uuid::new_v4());
+        create_tutor(pool, "Available".to_string(), email)
+            .await
+            .expect("Failed to create tutor")
+            .id
+    }
+
+    fn rule(weekday: i16, start: u32, end: u32) -> AvailabilityRule {
+        AvailabilityRule {
+            weekday,
+            start_time: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+            timezone: "Europe/London".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_availability_rules() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor(&pool).await;
+
+        let rules = replace_availability_rules(&pool, tutor_id, &[rule(3, 9, 12), rule(1, 14, 17)])
+            .await
+            .expect("Failed to set rules");
+        assert_eq!(rules, vec![rule(1, 14, 17), rule(3, 9, 12)]);
+
+        let rules = replace_availability_rules(&pool, tutor_id, &[rule(5, 8, 10)])
+            .await
+            .expect("Failed to replace rules");
+        assert_eq!(rules, vec![rule(5, 8, 10)]);
+        assert_eq!(list_availability_rules(&pool, tutor_id).await.unwrap().len(), 1);
+
+        // An end before the start breaks the table's CHECK
+        let result = replace_availability_rules(&pool, tutor_id, &[rule(2, 12, 9)]).await;
+        assert!(matches!(result, Err(sqlx::Error::Database(_))));
+        assert_eq!(list_availability_rules(&pool, tutor_id).await.unwrap(), vec![rule(5, 8, 10)]);
+
+        let result = replace_availability_rules(&pool, Uuid::new_v4(), &[]).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_availability_exceptions() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor(&pool).await;
+
+        let start = Utc::now() + Duration::days(2);
+        let exception = AvailabilityException::new(
+            tutor_id,
+            AvailabilityKind::Blocked,
+            start,
+            start + Duration::hours(3),
+            "Dentist".to_string(),
+        );
+        let created = create_availability_exception(&pool, exception)
+            .await
+            .expect("Failed to create exception");
+        assert_eq!(created.kind, AvailabilityKind::Blocked);
+
+        let window = list_availability_exceptions(&pool, tutor_id, start, start + Duration::days(1))
+            .await
+            .expect("Failed to list exceptions");
+        assert_eq!(window.len(), 1);
+        let before = list_availability_exceptions(&pool, tutor_id, Utc::now(), start)
+            .await
+            .unwrap();
+        assert!(before.is_empty());
+
+        delete_availability_exception(&pool, tutor_id, created.id)
+            .await
+            .expect("Failed to delete exception");
+        let result = delete_availability_exception(&pool, tutor_id, created.id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_import_availability_exceptions() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor(&pool).await;
+
+        let start = Utc::now() + Duration::days(5);
+        let imported = |uid: &str, hours: i64| AvailabilityException {
+            ical_uid: Some(uid.to_string()),
+            ..AvailabilityException::new(
+                tutor_id,
+                AvailabilityKind::Blocked,
+                start,
+                start + Duration::hours(hours),
+                "Busy".to_string(),
+            )
+        };
+        import_availability_exceptions(&pool, tutor_id, vec![imported("a", 1), imported("b", 1)], &[])
+            .await
+            .expect("Failed to import");
+
+        // Re-importing moves `a` rather than adding a copy, and drops `b`
+        let reimported = import_availability_exceptions(
+            &pool,
+            tutor_id,
+            vec![imported("a", 2)],
+            &["b".to_string()],
+        )
+        .await
+        .expect("Failed to re-import");
+        assert_eq!(reimported[0].ends_at - reimported[0].starts_at, Duration::hours(2));
+
+        let listed = list_availability_exceptions(&pool, tutor_id, start, start + Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, reimported[0].id);
+        assert_eq!(listed[0].ical_uid.as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::models::booking::{Booking, BookingOutcome, BookingStatus};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Name of the exclusion constraint keeping a tutor's bookings apart.
+const NO_OVERLAP: &str = "booking_no_overlap";
+
+/// Books the slot unless one of the tutor's confirmed bookings overlaps it,
+/// which the `booking_no_overlap` constraint checks atomically.
+pub async fn create_booking(pool: &PgPool, booking: Booking) -> Result<BookingOutcome, sqlx::Error> {
+    let inserted = sqlx::query_as!(
+        Booking,
+        r#"
+        INSERT INTO booking (id, tutor_id, student_id, starts_at, ends_at, note, status, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, tutor_id, student_id, starts_at, ends_at, note,
+            status as "status: BookingStatus", created_at, cancelled_at
+        "#,
+        booking.id,
+        booking.tutor_id,
+        booking.student_id,
+        booking.starts_at,
+        booking.ends_at,
+        booking.note,
+        booking.status as BookingStatus,
+        booking.created_at
+    )
+    .fetch_one(pool)
+    .await;
+
+    match inserted {
+        Ok(booking) => Ok(BookingOutcome::Booked(booking)),
+        Err(sqlx::Error::Database(e)) if e.constraint() == Some(NO_OVERLAP) => {
+            Ok(BookingOutcome::Overlaps)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn find_booking(pool: &PgPool, booking_id: Uuid) -> Result<Booking, sqlx::Error> {
+    let booking = sqlx::query_as!(
+        Booking,
+        r#"
+        SELECT id, tutor_id, student_id, starts_at, ends_at, note,
+            status as "status: BookingStatus", created_at, cancelled_at
+        FROM booking
+        WHERE id = $1
+        "#,
+        booking_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// The tutor's bookings overlapping `[from, to)`, cancelled ones included,
+/// earliest first.
+pub async fn list_tutor_bookings(
+    pool: &PgPool,
+    tutor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Booking>, sqlx::Error> {
+    let bookings = sqlx::query_as!(
+        Booking,
+        r#"
+        SELECT id, tutor_id, student_id, starts_at, ends_at, note,
+            status as "status: BookingStatus", created_at, cancelled_at
+        FROM booking
+        WHERE tutor_id = $1 AND starts_at < $3 AND ends_at > $2
+        ORDER BY starts_at
+        "#,
+        tutor_id,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bookings)
+}
+
+/// Cancels a confirmed booking, freeing its slot. `RowNotFound` if there is
+/// no such booking or it was already cancelled.
+pub async fn cancel_booking(pool: &PgPool, booking_id: Uuid) -> Result<Booking, sqlx::Error> {
+    let booking = sqlx::query_as!(
+        Booking,
+        r#"
+        UPDATE booking
+        SET status = 'cancelled', cancelled_at = now()
+        WHERE id = $1 AND status = 'confirmed'
+        RETURNING id, tutor_id, student_id, starts_at, ends_at, note,
+            status as "status: BookingStatus", created_at, cancelled_at
+        "#,
+        booking_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::student_repository::create_student;
+    use crate::repositories::tutor_repository::create_tutor;
+    use chrono::Duration;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    async fn tutor_and_student(pool: &PgPool) -> (Uuid, Uuid) {
+        let tutor = create_tutor(pool, "Booked".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create tutor");
+        let student = create_student(pool, "Booker".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create student");
+        (tutor.id, student.id)
+    }
+
+    fn booked(outcome: BookingOutcome) -> Booking {
+        match outcome {
+            BookingOutcome::Booked(booking) => booking,
+            other => panic!("expected a booking, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_bookings_are_rejected() {
+        let pool = setup_db().await;
+        let (tutor_id, student_id) = tutor_and_student(&pool).await;
+        let start = Utc::now() + Duration::days(3);
+
+        let first = booked(
+            create_booking(&pool, Booking::new(tutor_id, student_id, start, start + Duration::hours(1), String::new()))
+                .await
+                .expect("Failed to book"),
+        );
+        assert_eq!(first.status, BookingStatus::Confirmed);
+
+        let overlapping = Booking::new(
+            tutor_id,
+            student_id,
+            start + Duration::minutes(30),
+            start + Duration::minutes(90),
+            String::new(),
+        );
+        let outcome = create_booking(&pool, overlapping).await.unwrap();
+        assert!(matches!(outcome, BookingOutcome::Overlaps));
+
+        // Back-to-back is fine: the ranges are half-open
+        let adjacent = Booking::new(tutor_id, student_id, start + Duration::hours(1), start + Duration::hours(2), String::new());
+        booked(create_booking(&pool, adjacent).await.unwrap());
+
+        // Cancelling frees the slot
+        let cancelled = cancel_booking(&pool, first.id).await.expect("Failed to cancel");
+        assert_eq!(cancelled.status, BookingStatus::Cancelled);
+        assert!(cancelled.cancelled_at.is_some());
+        let rebooked = Booking::new(tutor_id, student_id, start, start + Duration::hours(1), String::new());
+        booked(create_booking(&pool, rebooked).await.unwrap());
+
+        let result = cancel_booking(&pool, first.id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+
+        let bookings = list_tutor_bookings(&pool, tutor_id, start, start + Duration::days(1))
+            .await
+            .expect("Failed to list bookings");
+        assert_eq!(bookings.len(), 3);
+        assert!(bookings.windows(2).all(|w| w[0].starts_at <= w[1].starts_at));
+        assert_eq!(find_booking(&pool, first.id).await.unwrap().status, BookingStatus::Cancelled);
+    }
+}
@@ -1 +1,596 @@
-use tutordb::models::;
\ No newline at end of file
+use crate::models::courses::{
+    Course, CourseFilter, CoursePage, CourseSearchHit, CourseType, PageRequest,
+};
+use crate::repositories::review_repository::refresh_tutor_rating;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+pub async fn create_course(pool: &PgPool, course: Course) -> Result<Course, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let inserted_course = sqlx::query_as!(
+        Course,
+        r#"
+        INSERT INTO course
+            (id, tutor_id, name, course_type, posted_time, rating, enrolled, enrolled_limit,
+            price, currency, description)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        "#,
+        course.id,
+        course.tutor_id,
+        course.name,
+        course.course_type as CourseType,
+        course.posted_time,
+        course.rating,
+        course.enrolled,
+        course.enrolled_limit,
+        course.price,
+        course.currency,
+        course.description
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Keep the tutor's course counter in step with the course table
+    sqlx::query!(
+        "UPDATE tutor SET courses = courses + 1 WHERE id = $1",
+        inserted_course.tutor_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(inserted_course)
+}
+
+pub async fn find_course(pool: &PgPool, course_id: Uuid) -> Result<Course, sqlx::Error> {
+    let course = sqlx::query_as!(
+        Course,
+        r#"
+        SELECT id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        FROM course
+        WHERE id = $1
+        "#,
+        course_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(course)
+}
+
+pub async fn list_tutor_courses(pool: &PgPool, tutor_id: Uuid) -> Result<Vec<Course>, sqlx::Error> {
+    let courses = sqlx::query_as!(
+        Course,
+        r#"
+        SELECT id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        FROM course
+        WHERE tutor_id = $1
+        ORDER BY posted_time
+        "#,
+        tutor_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(courses)
+}
+
+/// One page of the courses matching `filter`, ties broken by id so pages
+/// are stable.
+pub async fn list_courses(
+    pool: &PgPool,
+    filter: &CourseFilter,
+    page: &PageRequest,
+) -> Result<CoursePage, sqlx::Error> {
+    let courses = sqlx::query_as!(
+        Course,
+        r#"
+        SELECT id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        FROM course
+        WHERE ($1::UUID IS NULL OR tutor_id = $1)
+            AND ($2::course_type_enum IS NULL OR course_type = $2)
+            AND ($3::NUMERIC IS NULL OR COALESCE(price, 0) >= $3)
+            AND ($4::NUMERIC IS NULL OR COALESCE(price, 0) <= $4)
+            AND ($5::TIMESTAMP IS NULL OR posted_time > $5)
+        ORDER BY
+            CASE WHEN $6 = 'posted_time' AND NOT $7 THEN posted_time END ASC NULLS LAST,
+            CASE WHEN $6 = 'posted_time' AND $7 THEN posted_time END DESC NULLS LAST,
+            CASE WHEN $6 = 'name' AND NOT $7 THEN lower(name) END ASC,
+            CASE WHEN $6 = 'name' AND $7 THEN lower(name) END DESC,
+            CASE WHEN $6 = 'rating' AND NOT $7 THEN rating END ASC NULLS LAST,
+            CASE WHEN $6 = 'rating' AND $7 THEN rating END DESC NULLS LAST,
+            CASE WHEN $6 = 'price' AND NOT $7 THEN COALESCE(price, 0) END ASC,
+            CASE WHEN $6 = 'price' AND $7 THEN COALESCE(price, 0) END DESC,
+            id
+        LIMIT $8 OFFSET $9
+        "#,
+        filter.tutor_id,
+        filter.course_type as Option<CourseType>,
+        filter.min_price,
+        filter.max_price,
+        filter.posted_after,
+        page.sort.as_str(),
+        page.descending,
+        page.limit,
+        page.offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "total!"
+        FROM course
+        WHERE ($1::UUID IS NULL OR tutor_id = $1)
+            AND ($2::course_type_enum IS NULL OR course_type = $2)
+            AND ($3::NUMERIC IS NULL OR COALESCE(price, 0) >= $3)
+            AND ($4::NUMERIC IS NULL OR COALESCE(price, 0) <= $4)
+            AND ($5::TIMESTAMP IS NULL OR posted_time > $5)
+        "#,
+        filter.tutor_id,
+        filter.course_type as Option<CourseType>,
+        filter.min_price,
+        filter.max_price,
+        filter.posted_after
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CoursePage { courses, total })
+}
+
+/// Courses whose name or description match `query`, best match first.
+/// `query` uses web search syntax: quoted phrases, `or`, and `-` to exclude.
+pub async fn search_courses(
+    pool: &PgPool,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<CourseSearchHit>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, tutor_id, name, description, course_type as "course_type: CourseType",
+            posted_time, rating, enrolled, enrolled_limit, price, currency,
+            ts_rank(search_vector, query) as "rank!",
+            ts_headline('english', name, query,
+                'StartSel=<mark>, StopSel=</mark>, HighlightAll=true') as "name_highlight!",
+            ts_headline('english', description, query,
+                'StartSel=<mark>, StopSel=</mark>, MaxFragments=2') as "description_highlight!"
+        FROM course, websearch_to_tsquery('english', $1) query
+        WHERE search_vector @@ query
+        ORDER BY "rank!" DESC, id
+        LIMIT $2
+        "#,
+        query,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| CourseSearchHit {
+            course: Course {
+                id: row.id,
+                tutor_id: row.tutor_id,
+                name: row.name,
+                description: row.description,
+                course_type: row.course_type,
+                posted_time: row.posted_time,
+                rating: row.rating,
+                enrolled: row.enrolled,
+                enrolled_limit: row.enrolled_limit,
+                price: row.price,
+                currency: row.currency,
+            },
+            rank: row.rank,
+            name_highlight: row.name_highlight,
+            description_highlight: row.description_highlight,
+        })
+        .collect())
+}
+
+pub async fn update_course(pool: &PgPool, course: Course) -> Result<Course, sqlx::Error> {
+    let updated_course = sqlx::query_as!(
+        Course,
+        r#"
+        UPDATE course
+        SET name = $2, course_type = $3, posted_time = $4, enrolled_limit = $5, price = $6,
+            currency = $7, description = $8
+        WHERE id = $1
+        RETURNING id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        "#,
+        course.id,
+        course.name,
+        course.course_type as CourseType,
+        course.posted_time,
+        course.enrolled_limit,
+        course.price,
+        course.currency,
+        course.description
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(updated_course)
+}
+
+/// Takes one seat, failing with `RowNotFound` if the course is missing or
+/// already full. The check and increment happen in a single statement so
+/// concurrent enrolments cannot oversell.
+pub async fn increment_enrolled<'e>(
+    executor: impl PgExecutor<'e>,
+    course_id: Uuid,
+) -> Result<Course, sqlx::Error> {
+    let course = sqlx::query_as!(
+        Course,
+        r#"
+        UPDATE course
+        SET enrolled = enrolled + 1
+        WHERE id = $1 AND enrolled < enrolled_limit
+        RETURNING id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        "#,
+        course_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(course)
+}
+
+/// Frees one seat, failing with `RowNotFound` if nobody is enrolled.
+pub async fn decrement_enrolled<'e>(
+    executor: impl PgExecutor<'e>,
+    course_id: Uuid,
+) -> Result<Course, sqlx::Error> {
+    let course = sqlx::query_as!(
+        Course,
+        r#"
+        UPDATE course
+        SET enrolled = enrolled - 1
+        WHERE id = $1 AND enrolled > 0
+        RETURNING id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        "#,
+        course_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(course)
+}
+
+pub async fn delete_course(pool: &PgPool, course_id: Uuid) -> Result<Course, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let deleted_course = sqlx::query_as!(
+        Course,
+        r#"
+        DELETE FROM course
+        WHERE id = $1
+        RETURNING id, tutor_id, name, description, course_type as "course_type: CourseType", posted_time,
+            rating, enrolled, enrolled_limit, price, currency
+        "#,
+        course_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE tutor SET courses = courses - 1 WHERE id = $1",
+        deleted_course.tutor_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    // The course's reviews went with it
+    refresh_tutor_rating(&mut *tx, deleted_course.tutor_id).await?;
+
+    tx.commit().await?;
+    Ok(deleted_course)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::courses::CourseSort;
+    use crate::repositories::tutor_repository::create_tutor;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    async fn new_tutor_id(pool: &PgPool) -> Uuid {
+        let email = format!("{}@example.com", Uuid::new_v4());
+        create_tutor(pool, "Course Owner".to_string(), email)
+            .await
+            .expect("Failed to create tutor")
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_course() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let course = Course::new(tutor_id, "Rust 101".to_string(), None);
+        let course_id = course.id;
+
+        let created = create_course(&pool, course)
+            .await
+            .expect("Failed to create course");
+        assert_eq!(created.id, course_id);
+        assert_eq!(created.enrolled, 0);
+
+        let fetched = find_course(&pool, course_id)
+            .await
+            .expect("Failed to find course");
+        assert_eq!(fetched.tutor_id, tutor_id);
+        assert_eq!(fetched.name, "Rust 101");
+    }
+
+    #[tokio::test]
+    async fn test_list_tutor_courses() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        for name in ["First", "Second"] {
+            create_course(&pool, Course::new(tutor_id, name.to_string(), None))
+                .await
+                .expect("Failed to create course");
+        }
+
+        let courses = list_tutor_courses(&pool, tutor_id)
+            .await
+            .expect("Failed to list courses");
+        assert_eq!(courses.len(), 2);
+        assert!(courses.iter().all(|c| c.tutor_id == tutor_id));
+    }
+
+    #[tokio::test]
+    async fn test_update_course() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let mut course = create_course(&pool, Course::new(tutor_id, "Draft".to_string(), None))
+            .await
+            .expect("Failed to create course");
+        course.name = "Final".to_string();
+
+        let updated = update_course(&pool, course)
+            .await
+            .expect("Failed to update course");
+        assert_eq!(updated.name, "Final");
+
+        let result = update_course(&pool, Course::new(tutor_id, "Ghost".to_string(), None)).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_course() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let course = create_course(&pool, Course::new(tutor_id, "Short lived".to_string(), None))
+            .await
+            .expect("Failed to create course");
+
+        let deleted = delete_course(&pool, course.id)
+            .await
+            .expect("Failed to delete course");
+        assert_eq!(deleted.id, course.id);
+
+        let result = find_course(&pool, course.id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+
+        let tutor = crate::repositories::tutor_repository::find_tutor(tutor_id, &pool)
+            .await
+            .expect("Failed to find tutor");
+        assert_eq!(tutor.courses, 0);
+    }
+
+    #[tokio::test]
+    async fn test_course_type_round_trip() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let mut course = Course::new(tutor_id, "Paid Course".to_string(), None);
+        course.course_type = CourseType::Paid;
+        course.price = Some(BigDecimal::from_str("49.99").unwrap());
+        course.currency = Some("USD".to_string());
+
+        let created = create_course(&pool, course)
+            .await
+            .expect("Failed to create course");
+        assert_eq!(created.course_type, CourseType::Paid);
+
+        let fetched = find_course(&pool, created.id)
+            .await
+            .expect("Failed to find course");
+        assert_eq!(fetched.course_type, CourseType::Paid);
+        assert_eq!(fetched.price, Some(BigDecimal::from_str("49.99").unwrap()));
+        assert_eq!(fetched.currency.as_deref(), Some("USD"));
+    }
+
+    #[tokio::test]
+    async fn test_search_courses_ranks_name_matches_first() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+        // Unique so other tests' courses never match
+        let word = format!("zq{}", Uuid::new_v4().simple());
+
+        let mut in_description = Course::new(tutor_id, "Evening class".to_string(), None);
+        in_description.description = format!("Covers {word} basics");
+        let in_description = create_course(&pool, in_description).await.unwrap();
+        let in_name = create_course(&pool, Course::new(tutor_id, format!("Intro to {word}"), None))
+            .await
+            .unwrap();
+
+        let hits = search_courses(&pool, &word, 10).await.expect("Failed to search");
+        let ids: Vec<Uuid> = hits.iter().map(|hit| hit.course.id).collect();
+        assert_eq!(ids, vec![in_name.id, in_description.id]);
+        assert!(hits[0].rank > hits[1].rank);
+        assert_eq!(hits[0].name_highlight, format!("Intro to <mark>{word}</mark>"));
+        assert!(hits[1].description_highlight.contains(&format!("<mark>{word}</mark>")));
+
+        let hits = search_courses(&pool, &format!("{word} -intro"), 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_paid_course_requires_price() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let mut course = Course::new(tutor_id, "Unpriced".to_string(), None);
+        course.course_type = CourseType::Paid;
+        let result = create_course(&pool, course).await;
+        assert!(
+            matches!(result, Err(sqlx::Error::Database(e)) if e.is_check_violation()),
+            "Expected the price check to reject the course"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_courses_by_type_and_price() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let free = create_course(&pool, Course::new(tutor_id, "Free".to_string(), None))
+            .await
+            .expect("Failed to create course");
+        let mut paid = Vec::new();
+        for price in ["10.00", "25.50", "99.00"] {
+            let mut course = Course::new(tutor_id, format!("Paid {price}"), None);
+            course.course_type = CourseType::Paid;
+            course.price = Some(BigDecimal::from_str(price).unwrap());
+            course.currency = Some("EUR".to_string());
+            paid.push(create_course(&pool, course).await.expect("Failed to create course").id);
+        }
+
+        let filter = CourseFilter {
+            tutor_id: Some(tutor_id),
+            course_type: Some(CourseType::Paid),
+            min_price: Some(BigDecimal::from(20)),
+            max_price: Some(BigDecimal::from(100)),
+            ..Default::default()
+        };
+        let by_price = PageRequest {
+            sort: CourseSort::Price,
+            ..Default::default()
+        };
+        let ids: Vec<Uuid> = list_courses(&pool, &filter, &by_price)
+            .await
+            .expect("Failed to list courses")
+            .courses
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![paid[1], paid[2]]);
+
+        // Free courses sit at price 0
+        let filter = CourseFilter {
+            tutor_id: Some(tutor_id),
+            max_price: Some(BigDecimal::from(0)),
+            ..Default::default()
+        };
+        let ids: Vec<Uuid> = list_courses(&pool, &filter, &PageRequest::default())
+            .await
+            .expect("Failed to list courses")
+            .courses
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![free.id]);
+    }
+
+    #[tokio::test]
+    async fn test_list_courses_sorted_and_paged() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let base = chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        for (day, name) in ["Bravo", "alpha", "Delta", "charlie"].into_iter().enumerate() {
+            let posted = base + chrono::Duration::days(day as i64);
+            create_course(&pool, Course::new(tutor_id, name.to_string(), Some(posted)))
+                .await
+                .expect("Failed to create course");
+        }
+        let filter = CourseFilter {
+            tutor_id: Some(tutor_id),
+            ..Default::default()
+        };
+        let names = |page: CoursePage| page.courses.into_iter().map(|c| c.name).collect::<Vec<_>>();
+
+        let page = PageRequest {
+            sort: CourseSort::Name,
+            limit: Some(3),
+            ..Default::default()
+        };
+        let first = list_courses(&pool, &filter, &page).await.unwrap();
+        assert_eq!(first.total, 4);
+        assert_eq!(names(first), ["alpha", "Bravo", "charlie"]);
+
+        let page = PageRequest { offset: 3, ..page };
+        assert_eq!(names(list_courses(&pool, &filter, &page).await.unwrap()), ["Delta"]);
+
+        let page = PageRequest {
+            descending: true,
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(names(list_courses(&pool, &filter, &page).await.unwrap()), ["charlie", "Delta"]);
+
+        let filter = CourseFilter {
+            posted_after: Some(base + chrono::Duration::days(1)),
+            ..filter
+        };
+        let page = list_courses(&pool, &filter, &PageRequest::default()).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(names(page), ["Delta", "charlie"]);
+    }
+
+    #[tokio::test]
+    async fn test_enrolled_counter_respects_limit() {
+        let pool = setup_db().await;
+        let tutor_id = new_tutor_id(&pool).await;
+
+        let mut course = Course::new(tutor_id, "Tiny Class".to_string(), None);
+        course.enrolled_limit = 2;
+        let course = create_course(&pool, course)
+            .await
+            .expect("Failed to create course");
+
+        increment_enrolled(&pool, course.id).await.expect("first seat");
+        let full = increment_enrolled(&pool, course.id).await.expect("second seat");
+        assert!(full.is_full());
+
+        let result = increment_enrolled(&pool, course.id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+
+        let freed = decrement_enrolled(&pool, course.id).await.expect("free seat");
+        assert_eq!(freed.enrolled, 1);
+        decrement_enrolled(&pool, course.id).await.expect("free seat");
+
+        let result = decrement_enrolled(&pool, course.id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::models::course_session::{CourseSession, ScheduleOutcome};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Name of the exclusion constraint keeping a tutor's sessions apart.
+const NO_OVERLAP: &str = "course_session_no_overlap";
+
+/// Saves every session or, if any overlaps another of the tutor's sessions,
+/// none of them.
+pub async fn schedule_course_sessions(
+    pool: &PgPool,
+    sessions: Vec<CourseSession>,
+) -> Result<ScheduleOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut scheduled = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        let inserted = sqlx::query_as!(
+            CourseSession,
+            r#"
+            INSERT INTO course_session
+                (id, course_id, tutor_id, series_id, starts_at, ends_at, location, meeting_url, capacity)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, course_id, tutor_id, series_id, starts_at, ends_at, location, meeting_url, capacity
+            "#,
+            session.id,
+            session.course_id,
+            session.tutor_id,
+            session.series_id,
+            session.starts_at,
+            session.ends_at,
+            session.location,
+            session.meeting_url,
+            session.capacity
+        )
+        .fetch_one(&mut *tx)
+        .await;
+
+        match inserted {
+            Ok(session) => scheduled.push(session),
+            // Dropping the transaction rolls back the sessions before it
+            Err(sqlx::Error::Database(e)) if e.constraint() == Some(NO_OVERLAP) => {
+                return Ok(ScheduleOutcome::Overlaps);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    tx.commit().await?;
+
+    Ok(ScheduleOutcome::Scheduled(scheduled))
+}
+
+pub async fn find_course_session(pool: &PgPool, session_id: Uuid) -> Result<CourseSession, sqlx::Error> {
+    let session = sqlx::query_as!(
+        CourseSession,
+        r#"
+        SELECT id, course_id, tutor_id, series_id, starts_at, ends_at, location, meeting_url, capacity
+        FROM course_session
+        WHERE id = $1
+        "#,
+        session_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(session)
+}
+
+/// Up to `limit` of the course's sessions still running at `from`, earliest
+/// first.
+pub async fn list_course_sessions(
+    pool: &PgPool,
+    course_id: Uuid,
+    from: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<CourseSession>, sqlx::Error> {
+    let sessions = sqlx::query_as!(
+        CourseSession,
+        r#"
+        SELECT id, course_id, tutor_id, series_id, starts_at, ends_at, location, meeting_url, capacity
+        FROM course_session
+        WHERE course_id = $1 AND ends_at > $2
+        ORDER BY starts_at
+        LIMIT $3
+        "#,
+        course_id,
+        from,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Up to `limit` sessions still running at `from` across the courses the
+/// student holds a seat in, earliest first. Waitlisted courses are left out.
+pub async fn list_student_sessions(
+    pool: &PgPool,
+    student_id: Uuid,
+    from: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<CourseSession>, sqlx::Error> {
+    let sessions = sqlx::query_as!(
+        CourseSession,
+        r#"
+        SELECT s.id, s.course_id, s.tutor_id, s.series_id, s.starts_at, s.ends_at,
+            s.location, s.meeting_url, s.capacity
+        FROM course_session s
+        JOIN enrollment e ON e.course_id = s.course_id
+        WHERE e.student_id = $1 AND s.ends_at > $2
+        ORDER BY s.starts_at
+        LIMIT $3
+        "#,
+        student_id,
+        from,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// The tutor's sessions overlapping `[from, to)`, earliest first.
+pub async fn list_tutor_sessions(
+    pool: &PgPool,
+    tutor_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<CourseSession>, sqlx::Error> {
+    let sessions = sqlx::query_as!(
+        CourseSession,
+        r#"
+        SELECT id, course_id, tutor_id, series_id, starts_at, ends_at, location, meeting_url, capacity
+        FROM course_session
+        WHERE tutor_id = $1 AND starts_at < $3 AND ends_at > $2
+        ORDER BY starts_at
+        "#,
+        tutor_id,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// `RowNotFound` if the course has no such session.
+pub async fn delete_course_session(
+    pool: &PgPool,
+    course_id: Uuid,
+    session_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let deleted = sqlx::query!(
+        "DELETE FROM course_session WHERE id = $1 AND course_id = $2",
+        session_id,
+        course_id
+    )
+    .execute(pool)
+    .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::courses::Course;
+    use crate::repositories::course_repository::create_course;
+    use crate::repositories::enrollment_repository::enroll_student;
+    use crate::repositories::student_repository::create_student;
+    use crate::repositories::tutor_repository::create_tutor;
+    use chrono::Duration;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    fn session(course: &Course, starts_at: DateTime<Utc>, series_id: Option<Uuid>) -> CourseSession {
+        CourseSession {
+            id: Uuid::new_v4(),
+            course_id: course.id,
+            tutor_id: course.tutor_id,
+            series_id,
+            starts_at,
+            ends_at: starts_at + Duration::hours(1),
+            location: Some("Room 4".to_string()),
+            meeting_url: None,
+            capacity: None,
+        }
+    }
+
+    fn scheduled(outcome: ScheduleOutcome) -> Vec<CourseSession> {
+        match outcome {
+            ScheduleOutcome::Scheduled(sessions) => sessions,
+            other => panic!("expected sessions, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_scheduled_without_overlaps() {
+        let pool = setup_db().await;
+        let tutor = create_tutor(&pool, "Lecturer".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create tutor");
+        let algebra = create_course(&pool, Course::new(tutor.id, "Algebra".to_string(), None))
+            .await
+            .expect("Failed to create course");
+        let geometry = create_course(&pool, Course::new(tutor.id, "Geometry".to_string(), None))
+            .await
+            .expect("Failed to create course");
+        let start = Utc::now() + Duration::days(2);
+
+        let series = Some(Uuid::new_v4());
+        let weekly: Vec<CourseSession> =
+            (0..3).map(|week| session(&algebra, start + Duration::weeks(week), series)).collect();
+        let sessions = scheduled(schedule_course_sessions(&pool, weekly).await.expect("Failed to schedule"));
+        assert_eq!(sessions.len(), 3);
+        assert!(sessions.iter().all(|s| s.series_id == series));
+
+        // The second clashes with week two of algebra, so neither is saved
+        let clashing = vec![
+            session(&geometry, start + Duration::days(1), None),
+            session(&geometry, start + Duration::weeks(1) + Duration::minutes(30), None),
+        ];
+        let outcome = schedule_course_sessions(&pool, clashing).await.unwrap();
+        assert!(matches!(outcome, ScheduleOutcome::Overlaps));
+        let listed = list_tutor_sessions(&pool, tutor.id, start, start + Duration::weeks(4))
+            .await
+            .expect("Failed to list sessions");
+        assert_eq!(listed.len(), 3);
+
+        let upcoming = list_course_sessions(&pool, algebra.id, start + Duration::minutes(30), 2)
+            .await
+            .expect("Failed to list sessions");
+        assert_eq!(upcoming.iter().map(|s| s.id).collect::<Vec<_>>(), vec![sessions[0].id, sessions[1].id]);
+
+        let student = create_student(&pool, "Attendee".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create student");
+        assert!(list_student_sessions(&pool, student.id, start, 10).await.unwrap().is_empty());
+        enroll_student(&pool, algebra.id, student.id).await.expect("Failed to enroll");
+        assert_eq!(list_student_sessions(&pool, student.id, start, 10).await.unwrap().len(), 3);
+
+        assert_eq!(find_course_session(&pool, sessions[0].id).await.unwrap().course_id, algebra.id);
+        delete_course_session(&pool, algebra.id, sessions[0].id).await.expect("Failed to delete");
+        let result = delete_course_session(&pool, geometry.id, sessions[1].id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+        assert_eq!(list_course_sessions(&pool, algebra.id, start, 10).await.unwrap().len(), 2);
+        let result = find_course_session(&pool, sessions[0].id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+}
@@ -0,0 +1,357 @@
+use crate::models::curriculum::{CourseModule, Lesson, ReorderOutcome};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// Same ids, in any order
+fn same_ids(current: &[Uuid], ids: &[Uuid]) -> bool {
+    let mut current = current.to_vec();
+    let mut ids = ids.to_vec();
+    current.sort();
+    ids.sort();
+    current == ids
+}
+
+/// Adds a module after the course's others. `RowNotFound` if the course
+/// already has `max_modules` modules.
+pub async fn append_module(
+    pool: &PgPool,
+    module_id: Uuid,
+    course_id: Uuid,
+    title: &str,
+    max_modules: i64,
+) -> Result<CourseModule, sqlx::Error> {
+    let module = sqlx::query_as!(
+        CourseModule,
+        r#"
+        INSERT INTO course_module (id, course_id, title, position)
+        SELECT $1, $2, $3, COALESCE(max(position) + 1, 0)
+        FROM course_module WHERE course_id = $2
+        HAVING count(*) < $4
+        RETURNING id, course_id, title, position
+        "#,
+        module_id,
+        course_id,
+        title,
+        max_modules
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(module)
+}
+
+/// The course's modules in order.
+pub async fn list_modules(pool: &PgPool, course_id: Uuid) -> Result<Vec<CourseModule>, sqlx::Error> {
+    let modules = sqlx::query_as!(
+        CourseModule,
+        r#"
+        SELECT id, course_id, title, position
+        FROM course_module
+        WHERE course_id = $1
+        ORDER BY position
+        "#,
+        course_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(modules)
+}
+
+/// `RowNotFound` if the course has no such module.
+pub async fn rename_module(
+    pool: &PgPool,
+    course_id: Uuid,
+    module_id: Uuid,
+    title: &str,
+) -> Result<CourseModule, sqlx::Error> {
+    let module = sqlx::query_as!(
+        CourseModule,
+        r#"
+        UPDATE course_module SET title = $3
+        WHERE id = $2 AND course_id = $1
+        RETURNING id, course_id, title, position
+        "#,
+        course_id,
+        module_id,
+        title
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(module)
+}
+
+/// Deletes the module and its lessons. `RowNotFound` if the course has no
+/// such module.
+pub async fn delete_module(pool: &PgPool, course_id: Uuid, module_id: Uuid) -> Result<(), sqlx::Error> {
+    let deleted = sqlx::query!(
+        "DELETE FROM course_module WHERE id = $1 AND course_id = $2",
+        module_id,
+        course_id
+    )
+    .execute(pool)
+    .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
+/// Puts the course's modules in the order of `module_ids`, which must name
+/// each of them once.
+pub async fn reorder_modules(
+    pool: &PgPool,
+    course_id: Uuid,
+    module_ids: &[Uuid],
+) -> Result<ReorderOutcome<CourseModule>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let current = sqlx::query_scalar!(
+        "SELECT id FROM course_module WHERE course_id = $1 FOR UPDATE",
+        course_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    if !same_ids(&current, module_ids) {
+        return Ok(ReorderOutcome::Mismatch);
+    }
+
+    let mut modules = sqlx::query_as!(
+        CourseModule,
+        r#"
+        UPDATE course_module m SET position = o.ord - 1
+        FROM unnest($2::uuid[]) WITH ORDINALITY AS o(id, ord)
+        WHERE m.id = o.id AND m.course_id = $1
+        RETURNING m.id, m.course_id, m.title, m.position
+        "#,
+        course_id,
+        module_ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    modules.sort_by_key(|m| m.position);
+    Ok(ReorderOutcome::Reordered(modules))
+}
+
+/// Adds a lesson after the module's others; `position` and `updated_at` are
+/// set here. `RowNotFound` if the module already has `max_lessons` lessons.
+pub async fn append_lesson(pool: &PgPool, lesson: Lesson, max_lessons: i64) -> Result<Lesson, sqlx::Error> {
+    let lesson = sqlx::query_as!(
+        Lesson,
+        r#"
+        INSERT INTO lesson (id, module_id, title, body, duration_minutes, position, published)
+        SELECT $1, $2, $3, $4, $5, COALESCE(max(position) + 1, 0), $6
+        FROM lesson WHERE module_id = $2
+        HAVING count(*) < $7
+        RETURNING id, module_id, title, body, duration_minutes, position, published, updated_at
+        "#,
+        lesson.id,
+        lesson.module_id,
+        lesson.title,
+        lesson.body,
+        lesson.duration_minutes,
+        lesson.published,
+        max_lessons
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(lesson)
+}
+
+/// Saves the lesson's title, body, duration and published flag.
+/// `RowNotFound` if the module has no such lesson.
+pub async fn update_lesson(pool: &PgPool, lesson: Lesson) -> Result<Lesson, sqlx::Error> {
+    let lesson = sqlx::query_as!(
+        Lesson,
+        r#"
+        UPDATE lesson
+        SET title = $3, body = $4, duration_minutes = $5, published = $6, updated_at = now()
+        WHERE id = $1 AND module_id = $2
+        RETURNING id, module_id, title, body, duration_minutes, position, published, updated_at
+        "#,
+        lesson.id,
+        lesson.module_id,
+        lesson.title,
+        lesson.body,
+        lesson.duration_minutes,
+        lesson.published
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(lesson)
+}
+
+/// `RowNotFound` if the module has no such lesson.
+pub async fn delete_lesson(pool: &PgPool, module_id: Uuid, lesson_id: Uuid) -> Result<(), sqlx::Error> {
+    let deleted = sqlx::query!(
+        "DELETE FROM lesson WHERE id = $1 AND module_id = $2",
+        lesson_id,
+        module_id
+    )
+    .execute(pool)
+    .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
+/// Every lesson of the course, or only the published ones, ordered by
+/// module and then by lesson.
+pub async fn list_course_lessons(
+    pool: &PgPool,
+    course_id: Uuid,
+    published_only: bool,
+) -> Result<Vec<Lesson>, sqlx::Error> {
+    let lessons = sqlx::query_as!(
+        Lesson,
+        r#"
+        SELECT l.id, l.module_id, l.title, l.body, l.duration_minutes, l.position, l.published,
+            l.updated_at
+        FROM lesson l
+        JOIN course_module m ON m.id = l.module_id
+        WHERE m.course_id = $1 AND (l.published OR NOT $2)
+        ORDER BY m.position, l.position
+        "#,
+        course_id,
+        published_only
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(lessons)
+}
+
+/// Puts the module's lessons in the order of `lesson_ids`, which must name
+/// each of them, drafts included, once.
+pub async fn reorder_lessons(
+    pool: &PgPool,
+    module_id: Uuid,
+    lesson_ids: &[Uuid],
+) -> Result<ReorderOutcome<Lesson>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let current = sqlx::query_scalar!(
+        "SELECT id FROM lesson WHERE module_id = $1 FOR UPDATE",
+        module_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    if !same_ids(&current, lesson_ids) {
+        return Ok(ReorderOutcome::Mismatch);
+    }
+
+    let mut lessons = sqlx::query_as!(
+        Lesson,
+        r#"
+        UPDATE lesson l SET position = o.ord - 1
+        FROM unnest($2::uuid[]) WITH ORDINALITY AS o(id, ord)
+        WHERE l.id = o.id AND l.module_id = $1
+        RETURNING l.id, l.module_id, l.title, l.body, l.duration_minutes, l.position, l.published,
+            l.updated_at
+        "#,
+        module_id,
+        lesson_ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    lessons.sort_by_key(|l| l.position);
+    Ok(ReorderOutcome::Reordered(lessons))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::courses::Course;
+    use crate::repositories::course_repository::create_course;
+    use crate::repositories::tutor_repository::create_tutor;
+    use chrono::Utc;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    fn lesson(module_id: Uuid, title: &str, published: bool) -> Lesson {
+        Lesson {
+            id: Uuid::new_v4(),
+            module_id,
+            title: title.to_string(),
+            body: format!("# {title}"),
+            duration_minutes: Some(30),
+            position: 0,
+            published,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_curriculum_is_ordered_and_reordered() {
+        let pool = setup_db().await;
+        let tutor = create_tutor(&pool, "Author".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create tutor");
+        let course = create_course(&pool, Course::new(tutor.id, "Syllabus".to_string(), None))
+            .await
+            .expect("Failed to create course");
+
+        let basics = append_module(&pool, Uuid::new_v4(), course.id, "Basics", 2).await.expect("Failed to add module");
+        let advanced = append_module(&pool, Uuid::new_v4(), course.id, "Advanced", 2).await.expect("Failed to add module");
+        assert_eq!((basics.position, advanced.position), (0, 1));
+        let extra = append_module(&pool, Uuid::new_v4(), course.id, "Extra", 2).await;
+        assert!(matches!(extra, Err(sqlx::Error::RowNotFound)));
+        let first = append_lesson(&pool, lesson(basics.id, "Intro", true), 2).await.expect("Failed to add lesson");
+        let second = append_lesson(&pool, lesson(basics.id, "Setup", false), 2).await.expect("Failed to add lesson");
+        append_lesson(&pool, lesson(advanced.id, "Deep Dive", true), 2).await.expect("Failed to add lesson");
+        assert_eq!((first.position, second.position), (0, 1));
+        let extra = append_lesson(&pool, lesson(basics.id, "Extra", true), 2).await;
+        assert!(matches!(extra, Err(sqlx::Error::RowNotFound)));
+
+        // Swapping positions passes the deferred uniqueness check
+        let outcome = reorder_modules(&pool, course.id, &[advanced.id, basics.id]).await.unwrap();
+        match outcome {
+            ReorderOutcome::Reordered(modules) => {
+                assert_eq!(modules.iter().map(|m| m.id).collect::<Vec<_>>(), vec![advanced.id, basics.id]);
+            }
+            other => panic!("expected modules, got {other:?}"),
+        }
+        let outcome = reorder_modules(&pool, course.id, &[advanced.id]).await.unwrap();
+        assert!(matches!(outcome, ReorderOutcome::Mismatch));
+        let outcome = reorder_lessons(&pool, basics.id, &[second.id, first.id]).await.unwrap();
+        assert!(matches!(outcome, ReorderOutcome::Reordered(ref lessons) if lessons[0].id == second.id));
+
+        let titles = |lessons: Vec<Lesson>| lessons.into_iter().map(|l| l.title).collect::<Vec<_>>();
+        let all = list_course_lessons(&pool, course.id, false).await.expect("Failed to list lessons");
+        assert_eq!(titles(all), vec!["Deep Dive", "Setup", "Intro"]);
+        let published = list_course_lessons(&pool, course.id, true).await.expect("Failed to list lessons");
+        assert_eq!(titles(published), vec!["Deep Dive", "Intro"]);
+
+        let mut draft = second.clone();
+        draft.published = true;
+        draft.duration_minutes = None;
+        let updated = update_lesson(&pool, draft).await.expect("Failed to update lesson");
+        assert!(updated.published && updated.updated_at > second.updated_at);
+        let mut elsewhere = second.clone();
+        elsewhere.module_id = advanced.id;
+        assert!(matches!(update_lesson(&pool, elsewhere).await, Err(sqlx::Error::RowNotFound)));
+
+        assert_eq!(rename_module(&pool, course.id, basics.id, "Foundations").await.unwrap().title, "Foundations");
+        delete_lesson(&pool, basics.id, first.id).await.expect("Failed to delete lesson");
+        assert!(matches!(delete_lesson(&pool, basics.id, first.id).await, Err(sqlx::Error::RowNotFound)));
+        delete_module(&pool, course.id, advanced.id).await.expect("Failed to delete module");
+        assert_eq!(list_modules(&pool, course.id).await.unwrap().len(), 1);
+        assert_eq!(list_course_lessons(&pool, course.id, false).await.unwrap().len(), 1);
+    }
+}
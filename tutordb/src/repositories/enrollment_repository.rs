@@ -0,0 +1,332 @@
+use crate::models::enrollment::{EnrollOutcome, Enrollment, WithdrawOutcome};
+use crate::models::waitlist::WaitlistEntry;
+use crate::repositories::course_repository::{decrement_enrolled, increment_enrolled};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+// Row-locks the course so enrolments, withdrawals and promotions on it are
+// serialised. `RowNotFound` if the course does not exist.
+async fn lock_course(conn: &mut PgConnection, course_id: Uuid) -> Result<(i64, i64), sqlx::Error> {
+    let seats = sqlx::query!(
+        "SELECT enrolled, enrolled_limit FROM course WHERE id = $1 FOR UPDATE",
+        course_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok((seats.enrolled, seats.enrolled_limit))
+}
+
+async fn insert_enrollment(
+    conn: &mut PgConnection,
+    course_id: Uuid,
+    student_id: Uuid,
+) -> Result<Enrollment, sqlx::Error> {
+    sqlx::query_as!(
+        Enrollment,
+        r#"
+        INSERT INTO enrollment (course_id, student_id)
+        VALUES ($1, $2)
+        RETURNING course_id, student_id, enrolled_at
+        "#,
+        course_id,
+        student_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Enrolls a student, or queues them on the waitlist when the course is full.
+pub async fn enroll_student(
+    pool: &PgPool,
+    course_id: Uuid,
+    student_id: Uuid,
+) -> Result<EnrollOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let (enrolled, enrolled_limit) = lock_course(&mut tx, course_id).await?;
+
+    let state = sqlx::query!(
+        r#"
+        SELECT
+            EXISTS (SELECT 1 FROM enrollment WHERE course_id = $1 AND student_id = $2)
+                as "enrolled!",
+            EXISTS (SELECT 1 FROM waitlist WHERE course_id = $1 AND student_id = $2)
+                as "waitlisted!"
+        "#,
+        course_id,
+        student_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if state.enrolled {
+        return Ok(EnrollOutcome::AlreadyEnrolled);
+    }
+    if state.waitlisted {
+        return Ok(EnrollOutcome::AlreadyWaitlisted);
+    }
+
+    if enrolled < enrolled_limit {
+        let enrollment = insert_enrollment(&mut tx, course_id, student_id).await?;
+        increment_enrolled(&mut *tx, course_id).await?;
+        tx.commit().await?;
+        return Ok(EnrollOutcome::Enrolled(enrollment));
+    }
+
+    let entry = sqlx::query!(
+        r#"
+        INSERT INTO waitlist (course_id, student_id)
+        VALUES ($1, $2)
+        RETURNING joined_at
+        "#,
+        course_id,
+        student_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let position = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM waitlist WHERE course_id = $1"#,
+        course_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(EnrollOutcome::Waitlisted(WaitlistEntry {
+        course_id,
+        student_id,
+        joined_at: entry.joined_at,
+        position,
+    }))
+}
+
+/// Withdraws a student from the course or its waitlist. A freed seat goes
+/// straight to the head of the waitlist. `RowNotFound` if the student was
+/// in neither.
+pub async fn withdraw_student(
+    pool: &PgPool,
+    course_id: Uuid,
+    student_id: Uuid,
+) -> Result<WithdrawOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    lock_course(&mut tx, course_id).await?;
+
+    let withdrawn = sqlx::query!(
+        "DELETE FROM enrollment WHERE course_id = $1 AND student_id = $2",
+        course_id,
+        student_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if withdrawn.rows_affected() == 0 {
+        let left = sqlx::query!(
+            "DELETE FROM waitlist WHERE course_id = $1 AND student_id = $2",
+            course_id,
+            student_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if left.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        tx.commit().await?;
+        return Ok(WithdrawOutcome::LeftWaitlist);
+    }
+
+    let head = sqlx::query_scalar!(
+        r#"
+        DELETE FROM waitlist
+        WHERE id = (SELECT id FROM waitlist WHERE course_id = $1 ORDER BY id LIMIT 1)
+        RETURNING student_id
+        "#,
+        course_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    // A promotion keeps the seat count unchanged
+    let promoted = match head {
+        Some(next_student_id) => {
+            Some(insert_enrollment(&mut tx, course_id, next_student_id).await?)
+        }
+        None => {
+            decrement_enrolled(&mut *tx, course_id).await?;
+            None
+        }
+    };
+
+    tx.commit().await?;
+    Ok(WithdrawOutcome::Unenrolled { promoted })
+}
+
+pub async fn list_course_enrollments(
+    pool: &PgPool,
+    course_id: Uuid,
+) -> Result<Vec<Enrollment>, sqlx::Error> {
+    let enrollments = sqlx::query_as!(
+        Enrollment,
+        r#"
+        SELECT course_id, student_id, enrolled_at
+        FROM enrollment
+        WHERE course_id = $1
+        ORDER BY enrolled_at
+        "#,
+        course_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(enrollments)
+}
+
+pub async fn list_waitlist(pool: &PgPool, course_id: Uuid) -> Result<Vec<WaitlistEntry>, sqlx::Error> {
+    let entries = sqlx::query_as!(
+        WaitlistEntry,
+        r#"
+        SELECT course_id, student_id, joined_at,
+            ROW_NUMBER() OVER (ORDER BY id) as "position!"
+        FROM waitlist
+        WHERE course_id = $1
+        ORDER BY id
+        "#,
+        course_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::courses::Course;
+    use crate::repositories::course_repository::{create_course, find_course};
+    use crate::repositories::student_repository::create_student;
+    use crate::repositories::tutor_repository::create_tutor;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    async fn new_course(pool: &PgPool, enrolled_limit: i64) -> Uuid {
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let tutor = create_tutor(pool, "Owner".to_string(), email)
+            .await
+            .expect("Failed to create tutor");
+
+        let mut course = Course::new(tutor.id, "Seminar".to_string(), None);
+        course.enrolled_limit = enrolled_limit;
+        create_course(pool, course)
+            .await
+            .expect("Failed to create course")
+            .id
+    }
+
+    async fn new_student(pool: &PgPool) -> Uuid {
+        let email = format!("{}@example.com", Uuid::new_v4());
+        create_student(pool, "Student".to_string(), email)
+            .await
+            .expect("Failed to create student")
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_enroll_and_withdraw() {
+        let pool = setup_db().await;
+        let course_id = new_course(&pool, 5).await;
+        let student_id = new_student(&pool).await;
+
+        let outcome = enroll_student(&pool, course_id, student_id)
+            .await
+            .expect("Failed to enroll");
+        assert!(matches!(outcome, EnrollOutcome::Enrolled(e) if e.student_id == student_id));
+        assert_eq!(find_course(&pool, course_id).await.unwrap().enrolled, 1);
+
+        // A second enrolment of the same student is rejected
+        let outcome = enroll_student(&pool, course_id, student_id)
+            .await
+            .expect("Failed to enroll");
+        assert!(matches!(outcome, EnrollOutcome::AlreadyEnrolled));
+
+        let outcome = withdraw_student(&pool, course_id, student_id)
+            .await
+            .expect("Failed to withdraw");
+        assert!(matches!(outcome, WithdrawOutcome::Unenrolled { promoted: None }));
+        assert_eq!(find_course(&pool, course_id).await.unwrap().enrolled, 0);
+
+        let result = withdraw_student(&pool, course_id, student_id).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_waitlist_promotion() {
+        let pool = setup_db().await;
+        let course_id = new_course(&pool, 1).await;
+        let seated = new_student(&pool).await;
+        let first_in_line = new_student(&pool).await;
+        let second_in_line = new_student(&pool).await;
+
+        enroll_student(&pool, course_id, seated).await.unwrap();
+        for (student_id, expected_position) in [(first_in_line, 1), (second_in_line, 2)] {
+            let outcome = enroll_student(&pool, course_id, student_id).await.unwrap();
+            assert!(
+                matches!(outcome, EnrollOutcome::Waitlisted(entry) if entry.position == expected_position)
+            );
+        }
+        let outcome = enroll_student(&pool, course_id, first_in_line).await.unwrap();
+        assert!(matches!(outcome, EnrollOutcome::AlreadyWaitlisted));
+
+        let outcome = withdraw_student(&pool, course_id, seated).await.unwrap();
+        match outcome {
+            WithdrawOutcome::Unenrolled { promoted: Some(enrollment) } => {
+                assert_eq!(enrollment.student_id, first_in_line)
+            }
+            other => panic!("expected a promotion, got {other:?}"),
+        }
+        assert_eq!(find_course(&pool, course_id).await.unwrap().enrolled, 1);
+
+        let waitlist = list_waitlist(&pool, course_id).await.unwrap();
+        assert_eq!(waitlist.len(), 1);
+        assert_eq!(waitlist[0].student_id, second_in_line);
+        assert_eq!(waitlist[0].position, 1);
+
+        let outcome = withdraw_student(&pool, course_id, second_in_line).await.unwrap();
+        assert!(matches!(outcome, WithdrawOutcome::LeftWaitlist));
+        assert!(list_waitlist(&pool, course_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_enrollments_do_not_oversell() {
+        let pool = setup_db().await;
+        let course_id = new_course(&pool, 3).await;
+
+        let mut handles = Vec::new();
+        for _ in 0..10 {
+            let student_id = new_student(&pool).await;
+            let pool = pool.clone();
+            handles.push(tokio::spawn(async move {
+                enroll_student(&pool, course_id, student_id).await
+            }));
+        }
+
+        let mut seated = 0;
+        for handle in handles {
+            if let EnrollOutcome::Enrolled(_) = handle.await.unwrap().expect("Failed to enroll") {
+                seated += 1;
+            }
+        }
+
+        assert_eq!(seated, 3);
+        assert_eq!(find_course(&pool, course_id).await.unwrap().enrolled, 3);
+        assert_eq!(list_course_enrollments(&pool, course_id).await.unwrap().len(), 3);
+        assert_eq!(list_waitlist(&pool, course_id).await.unwrap().len(), 7);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::models::material::{AttachOutcome, Material};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Attaches the file to its lesson unless the lesson already holds the same
+/// content, in which case the existing material is returned.
+pub async fn attach_material(pool: &PgPool, material: Material) -> Result<AttachOutcome, sqlx::Error> {
+    let attached = sqlx::query_as!(
+        Material,
+        r#"
+        INSERT INTO lesson_material (id, lesson_id, filename, content_type, size_bytes, sha256)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (lesson_id, sha256) DO NOTHING
+        RETURNING id, lesson_id, filename, content_type, size_bytes, sha256, uploaded_at
+        "#,
+        material.id,
+        material.lesson_id,
+        material.filename,
+        material.content_type,
+        material.size_bytes,
+        material.sha256
+    )
+    .fetch_optional(pool)
+    .await?;
+    if let Some(material) = attached {
+        return Ok(AttachOutcome::Attached(material));
+    }
+
+    let existing = sqlx::query_as!(
+        Material,
+        r#"
+        SELECT id, lesson_id, filename, content_type, size_bytes, sha256, uploaded_at
+        FROM lesson_material
+        WHERE lesson_id = $1 AND sha256 = $2
+        "#,
+        material.lesson_id,
+        material.sha256
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(AttachOutcome::AlreadyAttached(existing))
+}
+
+/// The lesson's materials, oldest first.
+pub async fn list_lesson_materials(pool: &PgPool, lesson_id: Uuid) -> Result<Vec<Material>, sqlx::Error> {
+    let materials = sqlx::query_as!(
+        Material,
+        r#"
+        SELECT id, lesson_id, filename, content_type, size_bytes, sha256, uploaded_at
+        FROM lesson_material
+        WHERE lesson_id = $1
+        ORDER BY uploaded_at
+        "#,
+        lesson_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(materials)
+}
+
+/// The materials of every lesson in `lesson_ids`.
+pub async fn list_materials_for_lessons(
+    pool: &PgPool,
+    lesson_ids: &[Uuid],
+) -> Result<Vec<Material>, sqlx::Error> {
+    let materials = sqlx::query_as!(
+        Material,
+        r#"
+        SELECT id, lesson_id, filename, content_type, size_bytes, sha256, uploaded_at
+        FROM lesson_material
+        WHERE lesson_id = ANY($1)
+        ORDER BY uploaded_at
+        "#,
+        lesson_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(materials)
+}
+
+/// `RowNotFound` if the lesson has no such material.
+pub async fn find_material(
+    pool: &PgPool,
+    lesson_id: Uuid,
+    material_id: Uuid,
+) -> Result<Material, sqlx::Error> {
+    let material = sqlx::query_as!(
+        Material,
+        r#"
+        SELECT id, lesson_id, filename, content_type, size_bytes, sha256, uploaded_at
+        FROM lesson_material
+        WHERE id = $1 AND lesson_id = $2
+        "#,
+        material_id,
+        lesson_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(material)
+}
+
+/// Detaches and returns the material. `RowNotFound` if the lesson has no
+/// such material.
+pub async fn delete_material(
+    pool: &PgPool,
+    lesson_id: Uuid,
+    material_id: Uuid,
+) -> Result<Material, sqlx::Error> {
+    let material = sqlx::query_as!(
+        Material,
+        r#"
+        DELETE FROM lesson_material
+        WHERE id = $1 AND lesson_id = $2
+        RETURNING id, lesson_id, filename, content_type, size_bytes, sha256, uploaded_at
+        "#,
+        material_id,
+        lesson_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(material)
+}
+
+/// Whether any lesson still has a file with this content.
+pub async fn content_in_use(pool: &PgPool, sha256: &str) -> Result<bool, sqlx::Error> {
+    let in_use = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM lesson_material WHERE sha256 = $1) as "in_use!""#,
+        sha256
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(in_use)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::courses::Course;
+    use crate::models::curriculum::Lesson;
+    use crate::repositories::course_repository::create_course;
+    use crate::repositories::curriculum_repository::{append_lesson, append_module};
+    use crate::repositories::tutor_repository::create_tutor;
+    use chrono::Utc;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    fn material(lesson_id: Uuid, filename: &str, sha256: &str) -> Material {
+        Material {
+            id: Uuid::new_v4(),
+            lesson_id,
+            filename: filename.to_string(),
+            content_type: "application/pdf".to_string(),
+            size_bytes: 1024,
+            sha256: sha256.to_string(),
+            uploaded_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_materials_are_attached_once_per_content() {
+        let pool = setup_db().await;
+        let tutor = create_tutor(&pool, "Uploader".to_string(), format!("{}@example.com", Uuid::new_v4()))
+            .await
+            .expect("Failed to create tutor");
+        let course = create_course(&pool, Course::new(tutor.id, "Handouts".to_string(), None))
+            .await
+            .expect("Failed to create course");
+        let module = append_module(&pool, Uuid::new_v4(), course.id, "Week 1", 1).await.expect("Failed to add module");
+        let lesson = append_lesson(&pool, Lesson {
+            id: Uuid::new_v4(),
+            module_id: module.id,
+            title: "Reading".to_string(),
+            body: String::new(),
+            duration_minutes: None,
+            position: 0,
+            published: true,
+            updated_at: Utc::now(),
+        }, 1)
+        .await
+        .expect("Failed to add lesson");
+        // Unique per run so other runs' rows do not count as uses
+        let sha256 = format!("{:0>64}", Uuid::new_v4().simple().to_string());
+        let other_sha256 = format!("{:f>64}", Uuid::new_v4().simple().to_string());
+
+        let first = match attach_material(&pool, material(lesson.id, "notes.pdf", &sha256)).await.unwrap() {
+            AttachOutcome::Attached(material) => material,
+            other => panic!("expected a new material, got {other:?}"),
+        };
+        match attach_material(&pool, material(lesson.id, "copy.pdf", &sha256)).await.unwrap() {
+            AttachOutcome::AlreadyAttached(existing) => assert_eq!(existing.id, first.id),
+            other => panic!("expected the existing material, got {other:?}"),
+        }
+        attach_material(&pool, material(lesson.id, "slides.pdf", &other_sha256)).await.unwrap();
+        let listed = list_lesson_materials(&pool, lesson.id).await.expect("Failed to list materials");
+        assert_eq!(listed.iter().map(|m| m.filename.as_str()).collect::<Vec<_>>(), vec!["notes.pdf", "slides.pdf"]);
+        assert_eq!(find_material(&pool, lesson.id, first.id).await.unwrap().sha256, sha256);
+        let other_lesson = Uuid::new_v4();
+        assert_eq!(list_materials_for_lessons(&pool, &[lesson.id, other_lesson]).await.unwrap().len(), 2);
+        assert!(list_materials_for_lessons(&pool, &[other_lesson]).await.unwrap().is_empty());
+
+        assert!(content_in_use(&pool, &sha256).await.unwrap());
+        assert_eq!(delete_material(&pool, lesson.id, first.id).await.unwrap().sha256, sha256);
+        assert!(!content_in_use(&pool, &sha256).await.unwrap());
+        assert!(matches!(delete_material(&pool, lesson.id, first.id).await, Err(sqlx::Error::RowNotFound)));
+        assert!(matches!(find_material(&pool, lesson.id, first.id).await, Err(sqlx::Error::RowNotFound)));
+    }
+}
@@ -1,2 +1,13 @@
-mod tutor_repository;
-mod course_repository;
\ No newline at end of file
+pub mod tutor_repository;
+pub mod course_repository;
+pub mod stats_repository;
+pub mod student_repository;
+pub mod enrollment_repository;
+pub mod review_repository;
+pub mod audit_repository;
+pub mod availability_repository;
+pub mod booking_repository;
+pub mod course_session_repository;
+pub mod attendance_repository;
+pub mod curriculum_repository;
+pub mod material_repository;
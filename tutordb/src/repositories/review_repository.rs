@@ -0,0 +1,243 @@
+use crate::models::review::{Review, ReviewOutcome};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// Creates or replaces a student's review of a course, then recomputes the
+/// course average and the tutor's aggregate in the same transaction.
+/// `RowNotFound` if the course does not exist.
+pub async fn post_review(
+    pool: &PgPool,
+    course_id: Uuid,
+    student_id: Uuid,
+    rating: i16,
+    body: String,
+) -> Result<ReviewOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Reviews of any of the tutor's courses feed the same aggregate, so they
+    // are serialised on the tutor row
+    let tutor_id = sqlx::query_scalar!(
+        r#"
+        SELECT t.id
+        FROM course c JOIN tutor t ON t.id = c.tutor_id
+        WHERE c.id = $1
+        FOR UPDATE OF t
+        "#,
+        course_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let enrolled = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM enrollment WHERE course_id = $1 AND student_id = $2)
+            as "enrolled!"
+        "#,
+        course_id,
+        student_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if !enrolled {
+        return Ok(ReviewOutcome::NotEnrolled);
+    }
+
+    // `xmax` is 0 only for freshly inserted rows
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO review (course_id, student_id, rating, body)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (course_id, student_id) DO UPDATE
+        SET rating = EXCLUDED.rating, body = EXCLUDED.body, updated_at = now()
+        RETURNING course_id, student_id, rating, body, created_at, updated_at,
+            (xmax = 0) as "created!"
+        "#,
+        course_id,
+        student_id,
+        rating,
+        body
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    refresh_course_rating(&mut *tx, course_id).await?;
+    refresh_tutor_rating(&mut *tx, tutor_id).await?;
+    tx.commit().await?;
+
+    let review = Review {
+        course_id: row.course_id,
+        student_id: row.student_id,
+        rating: row.rating,
+        body: row.body,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    };
+    Ok(if row.created {
+        ReviewOutcome::Created(review)
+    } else {
+        ReviewOutcome::Updated(review)
+    })
+}
+
+pub async fn list_course_reviews(pool: &PgPool, course_id: Uuid) -> Result<Vec<Review>, sqlx::Error> {
+    let reviews = sqlx::query_as!(
+        Review,
+        r#"
+        SELECT course_id, student_id, rating, body, created_at, updated_at
+        FROM review
+        WHERE course_id = $1
+        ORDER BY updated_at DESC
+        "#,
+        course_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(reviews)
+}
+
+/// Sets `course.rating` to the average of its reviews, NULL if it has none.
+pub async fn refresh_course_rating<'e>(
+    executor: impl PgExecutor<'e>,
+    course_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE course
+        SET rating = (SELECT ROUND(AVG(rating), 2) FROM review WHERE course_id = $1)
+        WHERE id = $1
+        "#,
+        course_id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Sets `tutor.rating` to the average of every review across the tutor's
+/// courses, so busier courses weigh more. 0 while nothing is reviewed.
+pub async fn refresh_tutor_rating<'e>(
+    executor: impl PgExecutor<'e>,
+    tutor_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE tutor
+        SET rating = COALESCE(
+            (SELECT ROUND(AVG(r.rating), 2)
+             FROM review r JOIN course c ON c.id = r.course_id
+             WHERE c.tutor_id = $1),
+            0)
+        WHERE id = $1
+        "#,
+        tutor_id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::courses::Course;
+    use crate::repositories::course_repository::{create_course, delete_course, find_course};
+    use crate::repositories::enrollment_repository::enroll_student;
+    use crate::repositories::student_repository::create_student;
+    use crate::repositories::tutor_repository::{create_tutor, find_tutor};
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    async fn new_course(pool: &PgPool, tutor_id: Uuid) -> Uuid {
+        create_course(pool, Course::new(tutor_id, "Reviewed".to_string(), None))
+            .await
+            .expect("Failed to create course")
+            .id
+    }
+
+    async fn enrolled_student(pool: &PgPool, course_id: Uuid) -> Uuid {
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let student = create_student(pool, "Reviewer".to_string(), email)
+            .await
+            .expect("Failed to create student");
+        enroll_student(pool, course_id, student.id)
+            .await
+            .expect("Failed to enroll");
+        student.id
+    }
+
+    fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reviews_roll_up_into_ratings() {
+        let pool = setup_db().await;
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let tutor = create_tutor(&pool, "Rated".to_string(), email).await.unwrap();
+        let first_course = new_course(&pool, tutor.id).await;
+        let second_course = new_course(&pool, tutor.id).await;
+
+        let alice = enrolled_student(&pool, first_course).await;
+        let bob = enrolled_student(&pool, first_course).await;
+        let carol = enrolled_student(&pool, second_course).await;
+
+        let outcome = post_review(&pool, first_course, alice, 5, "Great".to_string())
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ReviewOutcome::Created(r) if r.rating == 5));
+        post_review(&pool, first_course, bob, 4, "Good".to_string()).await.unwrap();
+        post_review(&pool, second_course, carol, 1, "Meh".to_string()).await.unwrap();
+
+        let course = find_course(&pool, first_course).await.unwrap();
+        assert_eq!(course.rating, Some(decimal("4.50")));
+        let tutor_rating = find_tutor(tutor.id, &pool).await.unwrap().rating;
+        assert_eq!(tutor_rating, decimal("3.33"));
+
+        // Posting again replaces the earlier review
+        let outcome = post_review(&pool, first_course, bob, 2, "Worse".to_string())
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ReviewOutcome::Updated(r) if r.body == "Worse"));
+        assert_eq!(list_course_reviews(&pool, first_course).await.unwrap().len(), 2);
+        let course = find_course(&pool, first_course).await.unwrap();
+        assert_eq!(course.rating, Some(decimal("3.50")));
+
+        delete_course(&pool, second_course).await.unwrap();
+        let tutor_rating = find_tutor(tutor.id, &pool).await.unwrap().rating;
+        assert_eq!(tutor_rating, decimal("3.50"));
+    }
+
+    #[tokio::test]
+    async fn test_only_enrolled_students_review() {
+        let pool = setup_db().await;
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let tutor = create_tutor(&pool, "Strict".to_string(), email).await.unwrap();
+        let course_id = new_course(&pool, tutor.id).await;
+
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let outsider = create_student(&pool, "Outsider".to_string(), email).await.unwrap();
+
+        let outcome = post_review(&pool, course_id, outsider.id, 1, "Spam".to_string())
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ReviewOutcome::NotEnrolled));
+        assert_eq!(find_course(&pool, course_id).await.unwrap().rating, None);
+
+        let result = post_review(&pool, Uuid::new_v4(), outsider.id, 3, String::new()).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::models::stats::TableStats;
+use sqlx::PgPool;
+
+pub async fn table_stats(pool: &PgPool) -> Result<TableStats, sqlx::Error> {
+    let stats = sqlx::query_as!(
+        TableStats,
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM tutor) as "tutors!",
+            (SELECT COUNT(*) FROM course) as "courses!",
+            (SELECT COALESCE(SUM(enrolled), 0)::BIGINT FROM course) as "enrolled!",
+            (SELECT COALESCE(SUM(enrolled_limit), 0)::BIGINT FROM course) as "enrolled_limit!"
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_table_stats() {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        let stats = table_stats(&pool).await.expect("Failed to read stats");
+        assert!(stats.tutors >= 0);
+        assert!(stats.enrolled <= stats.enrolled_limit);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::models::student::{Student, StudentCredentials};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_student(
+    pool: &PgPool,
+    name: String,
+    email: String,
+) -> Result<Student, sqlx::Error> {
+    let student = Student::new(name, email);
+
+    let inserted_student = sqlx::query_as!(
+        Student,
+        r#"
+        INSERT INTO student (id, name, email)
+        VALUES ($1, $2, $3)
+        RETURNING id, name, email
+        "#,
+        student.id,
+        student.name,
+        student.email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(inserted_student)
+}
+
+/// Creates a student who can log in with the password behind `password_hash`.
+pub async fn create_student_with_password(
+    pool: &PgPool,
+    name: String,
+    email: String,
+    password_hash: String,
+) -> Result<Student, sqlx::Error> {
+    let student = Student::new(name, email);
+
+    let inserted_student = sqlx::query_as!(
+        Student,
+        r#"
+        INSERT INTO student (id, name, email, password_hash)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, name, email
+        "#,
+        student.id,
+        student.name,
+        student.email,
+        password_hash
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(inserted_student)
+}
+
+pub async fn find_student_credentials(
+    pool: &PgPool,
+    email: &str,
+) -> Result<StudentCredentials, sqlx::Error> {
+    let credentials = sqlx::query_as!(
+        StudentCredentials,
+        r#"
+        SELECT id, password_hash
+        FROM student
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(credentials)
+}
+
+pub async fn find_student(pool: &PgPool, student_id: Uuid) -> Result<Student, sqlx::Error> {
+    let student = sqlx::query_as!(
+        Student,
+        r#"
+        SELECT id, name, email
+        FROM student
+        WHERE id = $1
+        "#,
+        student_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(student)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set for tests");
+
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        crate::run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_student() {
+        let pool = setup_db().await;
+        let email = format!("student-{}@example.com", Uuid::new_v4());
+
+        let student = create_student(&pool, "Sam".to_string(), email.clone())
+            .await
+            .expect("Failed to create student");
+
+        let fetched = find_student(&pool, student.id)
+            .await
+            .expect("Failed to find student");
+        assert_eq!(fetched.name, "Sam");
+        assert_eq!(fetched.email, email);
+
+        let result = create_student(&pool, "Other Sam".to_string(), email).await;
+        assert!(result.is_err(), "Expected unique violation on email");
+    }
+
+    #[tokio::test]
+    async fn test_student_credentials() {
+        let pool = setup_db().await;
+        let email = format!("student-{}@example.com", Uuid::new_v4());
+
+        let student = create_student_with_password(
+            &pool,
+            "Pat".to_string(),
+            email.clone(),
+            "$argon2id$stub".to_string(),
+        )
+        .await
+        .expect("Failed to create student");
+
+        let credentials = find_student_credentials(&pool, &email)
+            .await
+            .expect("Failed to find credentials");
+        assert_eq!(credentials.id, student.id);
+        assert_eq!(credentials.password_hash.as_deref(), Some("$argon2id$stub"));
+
+        let result = find_student_credentials(&pool, "nobody@example.com").await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+}
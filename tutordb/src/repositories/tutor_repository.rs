@@ -1,7 +1,7 @@
-use crate::models::tutor::Tutor;
+use crate::models::role::Role;
+use crate::models::tutor::{Tutor, TutorCredentials};
 use sqlx::PgPool;
 use uuid::Uuid;
-use bigdecimal::BigDecimal;
 
 pub async fn create_tutor(
     pool: &PgPool,
@@ -29,6 +29,82 @@ pub async fn create_tutor(
     Ok(inserted_tutor)
 }
 
+/// Creates a tutor who can log in with the password behind `password_hash`.
+pub async fn create_tutor_with_password(
+    pool: &PgPool,
+    name: String,
+    email: String,
+    password_hash: String,
+) -> Result<Tutor, sqlx::Error> {
+    let tutor = Tutor::new(name, email);
+
+    let inserted_tutor = sqlx::query_as!(
+        Tutor,
+        r#"
+        INSERT INTO tutor (id, name, email, courses, rating, password_hash)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, name, email, courses, rating as "rating:bigdecimal::BigDecimal"
+        "#,
+        tutor.id,
+        tutor.name,
+        tutor.email,
+        tutor.courses,
+        tutor.rating,
+        password_hash
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(inserted_tutor)
+}
+
+pub async fn find_tutor_credentials(
+    pool: &PgPool,
+    email: &str,
+) -> Result<TutorCredentials, sqlx::Error> {
+    let credentials = sqlx::query_as!(
+        TutorCredentials,
+        r#"
+        SELECT id, password_hash, role as "role: Role"
+        FROM tutor
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(credentials)
+}
+
+pub async fn find_tutor_role(pool: &PgPool, tutor_id: Uuid) -> Result<Role, sqlx::Error> {
+    let role = sqlx::query_scalar!(
+        r#"SELECT role as "role: Role" FROM tutor WHERE id = $1"#,
+        tutor_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(role)
+}
+
+/// Makes a tutor an admin or back. `RowNotFound` if the tutor does not
+/// exist; the `tutor_role_check` constraint rejects `Role::Student`.
+pub async fn set_tutor_role(pool: &PgPool, tutor_id: Uuid, role: Role) -> Result<(), sqlx::Error> {
+    let updated = sqlx::query!(
+        "UPDATE tutor SET role = $2 WHERE id = $1",
+        tutor_id,
+        role as Role
+    )
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
 pub async fn find_tutor(
     tutor_id: Uuid,
     pool: &PgPool,
@@ -48,10 +124,110 @@ pub async fn find_tutor(
     Ok(tutor)
 }
 
+pub async fn find_tutor_by_details(
+    pool: &PgPool,
+    name: &str,
+    email: &str,
+) -> Result<Tutor, sqlx::Error> {
+    let tutor = sqlx::query_as!(
+        Tutor,
+        r#"
+        SELECT id, name, email, courses, rating as "rating: bigdecimal::BigDecimal"
+        FROM tutor
+        WHERE name = $1 AND email = $2
+        "#,
+        name,
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(tutor)
+}
+
+/// Tutors whose email contains `fragment`, ignoring case. `%` and `_` in
+/// `fragment` match literally.
+pub async fn search_tutors_by_email(
+    pool: &PgPool,
+    fragment: &str,
+    limit: i64,
+) -> Result<Vec<Tutor>, sqlx::Error> {
+    let pattern = format!(
+        "%{}%",
+        fragment.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    );
+    let tutors = sqlx::query_as!(
+        Tutor,
+        r#"
+        SELECT id, name, email, courses, rating as "rating: bigdecimal::BigDecimal"
+        FROM tutor
+        WHERE email ILIKE $1
+        ORDER BY email
+        LIMIT $2
+        "#,
+        pattern,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tutors)
+}
+
+pub async fn list_tutors(pool: &PgPool) -> Result<Vec<Tutor>, sqlx::Error> {
+    let tutors = sqlx::query_as!(
+        Tutor,
+        r#"
+        SELECT id, name, email, courses, rating as "rating: bigdecimal::BigDecimal"
+        FROM tutor
+        ORDER BY name, email
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tutors)
+}
+
+pub async fn update_tutor(pool: &PgPool, tutor: Tutor) -> Result<Tutor, sqlx::Error> {
+    let updated_tutor = sqlx::query_as!(
+        Tutor,
+        r#"
+        UPDATE tutor
+        SET name = $2, email = $3
+        WHERE id = $1
+        RETURNING id, name, email, courses, rating as "rating: bigdecimal::BigDecimal"
+        "#,
+        tutor.id,
+        tutor.name,
+        tutor.email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(updated_tutor)
+}
+
+pub async fn delete_tutor(pool: &PgPool, tutor_id: Uuid) -> Result<Tutor, sqlx::Error> {
+    let deleted_tutor = sqlx::query_as!(
+        Tutor,
+        r#"
+        DELETE FROM tutor
+        WHERE id = $1
+        RETURNING id, name, email, courses, rating as "rating: bigdecimal::BigDecimal"
+        "#,
+        tutor_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(deleted_tutor)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlx::{PgPool, Executor};
+    use sqlx::PgPool;
     use uuid::Uuid;
     use bigdecimal::BigDecimal;
 
@@ -62,21 +238,24 @@ mod tests {
             .expect("DATABASE_URL must be set for tests");
 
         let pool = PgPool::connect(&database_url).await.unwrap();
-
-        // Optionally reset the table before each test
-        pool.execute("TRUNCATE TABLE tutor RESTART IDENTITY;")
+        crate::run_migrations(&pool)
             .await
-            .unwrap();
-
+            .expect("Failed to run migrations");
         pool
     }
 
+    // Tests run concurrently against a shared database, so every tutor gets
+    // its own email instead of truncating the table underneath other tests
+    fn unique_email(prefix: &str) -> String {
+        format!("{prefix}-{}@example.com", Uuid::new_v4())
+    }
+
     #[tokio::test]
     async fn test_create_tutor() {
         let pool = setup_db().await;
 
         let name = "Alice".to_string();
-        let email = "alice@example.com".to_string();
+        let email = unique_email("alice");
 
         let tutor = create_tutor(&pool, name.clone(), email.clone())
             .await
@@ -95,7 +274,7 @@ mod tests {
 
         // First insert a tutor
         let name = "Bob".to_string();
-        let email = "bob@example.com".to_string();
+        let email = unique_email("bob");
 
         let created_tutor = create_tutor(&pool, name.clone(), email.clone())
             .await
@@ -122,4 +301,150 @@ mod tests {
 
         assert!(result.is_err(), "Expected error for non-existent tutor");
     }
+
+    #[tokio::test]
+    async fn test_find_tutor_by_details() {
+        let pool = setup_db().await;
+
+        let email = unique_email("carol");
+        let created_tutor = create_tutor(&pool, "Carol".to_string(), email.clone())
+            .await
+            .expect("Failed to create tutor");
+
+        let fetched_tutor = find_tutor_by_details(&pool, "Carol", &email)
+            .await
+            .expect("Failed to find tutor");
+        assert_eq!(fetched_tutor.id, created_tutor.id);
+
+        let result = find_tutor_by_details(&pool, "Not Carol", &email).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_search_tutors_by_email() {
+        let pool = setup_db().await;
+
+        let marker = Uuid::new_v4().simple().to_string();
+        let email = format!("Jade_{marker}@example.com");
+        let tutor = create_tutor(&pool, "Jade".to_string(), email)
+            .await
+            .expect("Failed to create tutor");
+
+        let found = search_tutors_by_email(&pool, &marker.to_uppercase(), 10)
+            .await
+            .expect("Failed to search tutors");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, tutor.id);
+
+        // As wildcards the underscores would match "e_"
+        let found = search_tutors_by_email(&pool, &format!("jad__{marker}"), 10).await.unwrap();
+        assert!(found.is_empty());
+        let found = search_tutors_by_email(&pool, &format!("jade_{marker}"), 10).await.unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_tutor_duplicate_email() {
+        let pool = setup_db().await;
+
+        let email = unique_email("dave");
+        create_tutor(&pool, "Dave".to_string(), email.clone())
+            .await
+            .expect("Failed to create tutor");
+
+        let result = create_tutor(&pool, "Other Dave".to_string(), email).await;
+        let is_unique_violation = match result {
+            Err(sqlx::Error::Database(e)) => e.is_unique_violation(),
+            _ => false,
+        };
+        assert!(is_unique_violation, "Expected unique violation on email");
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_tutor() {
+        let pool = setup_db().await;
+
+        let mut tutor = create_tutor(&pool, "Erin".to_string(), unique_email("erin"))
+            .await
+            .expect("Failed to create tutor");
+        tutor.name = "Erin Renamed".to_string();
+        let tutor_id = tutor.id;
+
+        let updated_tutor = update_tutor(&pool, tutor)
+            .await
+            .expect("Failed to update tutor");
+        assert_eq!(updated_tutor.name, "Erin Renamed");
+
+        let deleted_tutor = delete_tutor(&pool, tutor_id)
+            .await
+            .expect("Failed to delete tutor");
+        assert_eq!(deleted_tutor.id, tutor_id);
+
+        let result = find_tutor(tutor_id, &pool).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_tutor_credentials() {
+        let pool = setup_db().await;
+
+        let email = unique_email("gina");
+        let tutor = create_tutor_with_password(
+            &pool,
+            "Gina".to_string(),
+            email.clone(),
+            "$argon2id$stub".to_string(),
+        )
+        .await
+        .expect("Failed to create tutor");
+
+        let credentials = find_tutor_credentials(&pool, &email)
+            .await
+            .expect("Failed to find credentials");
+        assert_eq!(credentials.id, tutor.id);
+        assert_eq!(credentials.password_hash.as_deref(), Some("$argon2id$stub"));
+        assert_eq!(credentials.role, Role::Tutor);
+
+        // Tutors made without a password have no way to log in
+        let email = unique_email("hank");
+        create_tutor(&pool, "Hank".to_string(), email.clone())
+            .await
+            .expect("Failed to create tutor");
+        let credentials = find_tutor_credentials(&pool, &email).await.unwrap();
+        assert_eq!(credentials.password_hash, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_tutor_role() {
+        let pool = setup_db().await;
+
+        let tutor = create_tutor(&pool, "Ivy".to_string(), unique_email("ivy"))
+            .await
+            .expect("Failed to create tutor");
+        assert_eq!(find_tutor_role(&pool, tutor.id).await.unwrap(), Role::Tutor);
+
+        set_tutor_role(&pool, tutor.id, Role::Admin)
+            .await
+            .expect("Failed to promote tutor");
+        assert_eq!(find_tutor_role(&pool, tutor.id).await.unwrap(), Role::Admin);
+
+        // Tutor accounts can never act as students
+        let result = set_tutor_role(&pool, tutor.id, Role::Student).await;
+        assert!(matches!(result, Err(sqlx::Error::Database(_))));
+
+        let result = set_tutor_role(&pool, Uuid::new_v4(), Role::Admin).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_list_tutors() {
+        let pool = setup_db().await;
+
+        let created_tutor = create_tutor(&pool, "Frank".to_string(), unique_email("frank"))
+            .await
+            .expect("Failed to create tutor");
+
+        let tutors = list_tutors(&pool).await.expect("Failed to list tutors");
+        assert!(tutors.iter().any(|t| t.id == created_tutor.id));
+    }
 }